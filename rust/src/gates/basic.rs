@@ -1,7 +1,7 @@
 //! Basic logic gate implementations
 
-use super::gate::{Gate, GateResult};
-use super::state::StateType;
+use super::gate::{DelayCorner, Gate, GateResult, ResetMode};
+use super::state::{resolve_wire_state, StateType, UnknownPolicy};
 
 /// AND Gate
 pub struct AndGate {
@@ -9,6 +9,7 @@ pub struct AndGate {
     inputs: Vec<StateType>,
     outputs: Vec<StateType>,
     delay: u64,
+    unknown_policy: UnknownPolicy,
 }
 
 impl AndGate {
@@ -18,6 +19,7 @@ impl AndGate {
             inputs: vec![StateType::Unknown; input_count],
             outputs: vec![StateType::Unknown; 1],
             delay,
+            unknown_policy: UnknownPolicy::default(),
         }
     }
 }
@@ -34,12 +36,12 @@ impl Gate for AndGate {
         if index < self.inputs.len() { self.inputs[index] = state; }
     }
 
+    fn set_output(&mut self, index: usize, state: StateType) {
+        if index < self.outputs.len() { self.outputs[index] = state; }
+    }
+
     fn evaluate(&mut self) -> GateResult {
-        let mut result = self.inputs.first().copied().unwrap_or(StateType::Unknown);
-        for &input in self.inputs.iter().skip(1) {
-            result = result.and(input);
-        }
-        self.outputs[0] = result;
+        self.outputs[0] = StateType::reduce_and_with_policy(&self.inputs, self.unknown_policy);
         GateResult { outputs: self.outputs.clone(), delay: self.delay }
     }
 
@@ -49,6 +51,7 @@ impl Gate for AndGate {
     }
 
     fn delay(&self) -> u64 { self.delay }
+    fn set_unknown_policy(&mut self, policy: UnknownPolicy) { self.unknown_policy = policy; }
 }
 
 /// OR Gate
@@ -57,6 +60,7 @@ pub struct OrGate {
     inputs: Vec<StateType>,
     outputs: Vec<StateType>,
     delay: u64,
+    unknown_policy: UnknownPolicy,
 }
 
 impl OrGate {
@@ -66,6 +70,7 @@ impl OrGate {
             inputs: vec![StateType::Unknown; input_count],
             outputs: vec![StateType::Unknown; 1],
             delay,
+            unknown_policy: UnknownPolicy::default(),
         }
     }
 }
@@ -82,12 +87,12 @@ impl Gate for OrGate {
         if index < self.inputs.len() { self.inputs[index] = state; }
     }
 
+    fn set_output(&mut self, index: usize, state: StateType) {
+        if index < self.outputs.len() { self.outputs[index] = state; }
+    }
+
     fn evaluate(&mut self) -> GateResult {
-        let mut result = self.inputs.first().copied().unwrap_or(StateType::Unknown);
-        for &input in self.inputs.iter().skip(1) {
-            result = result.or(input);
-        }
-        self.outputs[0] = result;
+        self.outputs[0] = StateType::reduce_or_with_policy(&self.inputs, self.unknown_policy);
         GateResult { outputs: self.outputs.clone(), delay: self.delay }
     }
 
@@ -97,6 +102,7 @@ impl Gate for OrGate {
     }
 
     fn delay(&self) -> u64 { self.delay }
+    fn set_unknown_policy(&mut self, policy: UnknownPolicy) { self.unknown_policy = policy; }
 }
 
 /// NOT Gate (Inverter)
@@ -105,6 +111,7 @@ pub struct NotGate {
     inputs: Vec<StateType>,
     outputs: Vec<StateType>,
     delay: u64,
+    unknown_policy: UnknownPolicy,
 }
 
 impl NotGate {
@@ -114,6 +121,7 @@ impl NotGate {
             inputs: vec![StateType::Unknown; 1],
             outputs: vec![StateType::Unknown; 1],
             delay,
+            unknown_policy: UnknownPolicy::default(),
         }
     }
 }
@@ -130,9 +138,13 @@ impl Gate for NotGate {
         if index < self.inputs.len() { self.inputs[index] = state; }
     }
 
+    fn set_output(&mut self, index: usize, state: StateType) {
+        if index < self.outputs.len() { self.outputs[index] = state; }
+    }
+
     fn evaluate(&mut self) -> GateResult {
         let input = self.inputs.first().copied().unwrap_or(StateType::Unknown);
-        self.outputs[0] = input.not();
+        self.outputs[0] = input.not_with_policy(self.unknown_policy);
         GateResult { outputs: self.outputs.clone(), delay: self.delay }
     }
 
@@ -142,6 +154,7 @@ impl Gate for NotGate {
     }
 
     fn delay(&self) -> u64 { self.delay }
+    fn set_unknown_policy(&mut self, policy: UnknownPolicy) { self.unknown_policy = policy; }
 }
 
 /// XOR Gate
@@ -150,6 +163,7 @@ pub struct XorGate {
     inputs: Vec<StateType>,
     outputs: Vec<StateType>,
     delay: u64,
+    unknown_policy: UnknownPolicy,
 }
 
 impl XorGate {
@@ -159,6 +173,7 @@ impl XorGate {
             inputs: vec![StateType::Unknown; input_count],
             outputs: vec![StateType::Unknown; 1],
             delay,
+            unknown_policy: UnknownPolicy::default(),
         }
     }
 }
@@ -175,12 +190,12 @@ impl Gate for XorGate {
         if index < self.inputs.len() { self.inputs[index] = state; }
     }
 
+    fn set_output(&mut self, index: usize, state: StateType) {
+        if index < self.outputs.len() { self.outputs[index] = state; }
+    }
+
     fn evaluate(&mut self) -> GateResult {
-        let mut result = self.inputs.first().copied().unwrap_or(StateType::Unknown);
-        for &input in self.inputs.iter().skip(1) {
-            result = result.xor(input);
-        }
-        self.outputs[0] = result;
+        self.outputs[0] = StateType::reduce_xor_with_policy(&self.inputs, self.unknown_policy);
         GateResult { outputs: self.outputs.clone(), delay: self.delay }
     }
 
@@ -190,6 +205,7 @@ impl Gate for XorGate {
     }
 
     fn delay(&self) -> u64 { self.delay }
+    fn set_unknown_policy(&mut self, policy: UnknownPolicy) { self.unknown_policy = policy; }
 }
 
 /// NAND Gate (AND + NOT)
@@ -198,6 +214,7 @@ pub struct NandGate {
     inputs: Vec<StateType>,
     outputs: Vec<StateType>,
     delay: u64,
+    unknown_policy: UnknownPolicy,
 }
 
 impl NandGate {
@@ -207,6 +224,7 @@ impl NandGate {
             inputs: vec![StateType::Unknown; input_count],
             outputs: vec![StateType::Unknown; 1],
             delay,
+            unknown_policy: UnknownPolicy::default(),
         }
     }
 }
@@ -223,12 +241,12 @@ impl Gate for NandGate {
         if index < self.inputs.len() { self.inputs[index] = state; }
     }
 
+    fn set_output(&mut self, index: usize, state: StateType) {
+        if index < self.outputs.len() { self.outputs[index] = state; }
+    }
+
     fn evaluate(&mut self) -> GateResult {
-        let mut result = self.inputs.first().copied().unwrap_or(StateType::Unknown);
-        for &input in self.inputs.iter().skip(1) {
-            result = result.and(input);
-        }
-        self.outputs[0] = result.not();
+        self.outputs[0] = StateType::reduce_and_with_policy(&self.inputs, self.unknown_policy).not();
         GateResult { outputs: self.outputs.clone(), delay: self.delay }
     }
 
@@ -238,6 +256,7 @@ impl Gate for NandGate {
     }
 
     fn delay(&self) -> u64 { self.delay }
+    fn set_unknown_policy(&mut self, policy: UnknownPolicy) { self.unknown_policy = policy; }
 }
 
 /// NOR Gate (OR + NOT)
@@ -246,6 +265,7 @@ pub struct NorGate {
     inputs: Vec<StateType>,
     outputs: Vec<StateType>,
     delay: u64,
+    unknown_policy: UnknownPolicy,
 }
 
 impl NorGate {
@@ -255,6 +275,7 @@ impl NorGate {
             inputs: vec![StateType::Unknown; input_count],
             outputs: vec![StateType::Unknown; 1],
             delay,
+            unknown_policy: UnknownPolicy::default(),
         }
     }
 }
@@ -271,12 +292,12 @@ impl Gate for NorGate {
         if index < self.inputs.len() { self.inputs[index] = state; }
     }
 
+    fn set_output(&mut self, index: usize, state: StateType) {
+        if index < self.outputs.len() { self.outputs[index] = state; }
+    }
+
     fn evaluate(&mut self) -> GateResult {
-        let mut result = self.inputs.first().copied().unwrap_or(StateType::Unknown);
-        for &input in self.inputs.iter().skip(1) {
-            result = result.or(input);
-        }
-        self.outputs[0] = result.not();
+        self.outputs[0] = StateType::reduce_or_with_policy(&self.inputs, self.unknown_policy).not();
         GateResult { outputs: self.outputs.clone(), delay: self.delay }
     }
 
@@ -286,6 +307,7 @@ impl Gate for NorGate {
     }
 
     fn delay(&self) -> u64 { self.delay }
+    fn set_unknown_policy(&mut self, policy: UnknownPolicy) { self.unknown_policy = policy; }
 }
 
 /// XNOR Gate (XOR + NOT)
@@ -294,6 +316,7 @@ pub struct XnorGate {
     inputs: Vec<StateType>,
     outputs: Vec<StateType>,
     delay: u64,
+    unknown_policy: UnknownPolicy,
 }
 
 impl XnorGate {
@@ -303,6 +326,7 @@ impl XnorGate {
             inputs: vec![StateType::Unknown; input_count],
             outputs: vec![StateType::Unknown; 1],
             delay,
+            unknown_policy: UnknownPolicy::default(),
         }
     }
 }
@@ -319,12 +343,12 @@ impl Gate for XnorGate {
         if index < self.inputs.len() { self.inputs[index] = state; }
     }
 
+    fn set_output(&mut self, index: usize, state: StateType) {
+        if index < self.outputs.len() { self.outputs[index] = state; }
+    }
+
     fn evaluate(&mut self) -> GateResult {
-        let mut result = self.inputs.first().copied().unwrap_or(StateType::Unknown);
-        for &input in self.inputs.iter().skip(1) {
-            result = result.xor(input);
-        }
-        self.outputs[0] = result.not();
+        self.outputs[0] = StateType::reduce_xor_with_policy(&self.inputs, self.unknown_policy).not();
         GateResult { outputs: self.outputs.clone(), delay: self.delay }
     }
 
@@ -334,6 +358,7 @@ impl Gate for XnorGate {
     }
 
     fn delay(&self) -> u64 { self.delay }
+    fn set_unknown_policy(&mut self, policy: UnknownPolicy) { self.unknown_policy = policy; }
 }
 
 /// Buffer Gate (pass through)
@@ -367,6 +392,10 @@ impl Gate for BufferGate {
         if index < self.inputs.len() { self.inputs[index] = state; }
     }
 
+    fn set_output(&mut self, index: usize, state: StateType) {
+        if index < self.outputs.len() { self.outputs[index] = state; }
+    }
+
     fn evaluate(&mut self) -> GateResult {
         self.outputs[0] = self.inputs.first().copied().unwrap_or(StateType::Unknown);
         GateResult { outputs: self.outputs.clone(), delay: self.delay }
@@ -411,6 +440,10 @@ impl Gate for TriBufferGate {
         if index < self.inputs.len() { self.inputs[index] = state; }
     }
 
+    fn set_output(&mut self, index: usize, state: StateType) {
+        if index < self.outputs.len() { self.outputs[index] = state; }
+    }
+
     fn evaluate(&mut self) -> GateResult {
         let data = self.inputs[0];
         let enable = self.inputs[1];
@@ -430,6 +463,129 @@ impl Gate for TriBufferGate {
     fn delay(&self) -> u64 { self.delay }
 }
 
+/// Transmission gate (inputs: [signal, control], outputs: [out])
+///
+/// A closed switch (control ONE) passes the signal unchanged, including HiZ
+/// and weak levels; an open one (control ZERO) floats the output. Modelled
+/// one way only, from signal to out. A floating signal is passed through
+/// as HiZ whatever the engine's floating input policy.
+pub struct TransmissionGate {
+    id: String,
+    inputs: Vec<StateType>,
+    outputs: Vec<StateType>,
+    delay: u64,
+}
+
+impl TransmissionGate {
+    pub fn new(id: String, delay: u64) -> Self {
+        Self {
+            id,
+            inputs: vec![StateType::Unknown; 2],
+            outputs: vec![StateType::Unknown; 1],
+            delay,
+        }
+    }
+}
+
+impl Gate for TransmissionGate {
+    fn id(&self) -> &str { &self.id }
+    fn gate_type(&self) -> &str { "TGATE" }
+    fn input_count(&self) -> usize { 2 }
+    fn output_count(&self) -> usize { 1 }
+    fn get_inputs(&self) -> &[StateType] { &self.inputs }
+    fn get_outputs(&self) -> &[StateType] { &self.outputs }
+
+    fn set_input(&mut self, index: usize, state: StateType) {
+        if index < self.inputs.len() { self.inputs[index] = state; }
+    }
+
+    fn set_output(&mut self, index: usize, state: StateType) {
+        if index < self.outputs.len() { self.outputs[index] = state; }
+    }
+
+    fn evaluate(&mut self) -> GateResult {
+        let signal = self.inputs[0];
+        self.outputs[0] = match self.inputs[1].to_strong() {
+            StateType::One => signal,
+            StateType::Zero => StateType::HiZ,
+            // Open or closed, a floating signal floats the output
+            _ if signal == StateType::HiZ => StateType::HiZ,
+            _ => StateType::Unknown,
+        };
+        GateResult { outputs: self.outputs.clone(), delay: self.delay }
+    }
+
+    fn reset(&mut self) {
+        self.inputs.fill(StateType::Unknown);
+        self.outputs.fill(StateType::Unknown);
+    }
+
+    fn delay(&self) -> u64 { self.delay }
+
+    fn passes_high_impedance(&self) -> bool { true }
+}
+
+/// Bus transceiver with one inout pin (inputs: [a, oe, pin], outputs: [pin, y])
+///
+/// Output 0 drives the pin with `a` while `oe` is high and releases it (HiZ)
+/// otherwise; input 2 senses the resolved pin and `y` repeats it, so the gate
+/// reads the bus whenever it is not driving.
+pub struct TransceiverGate {
+    id: String,
+    inputs: Vec<StateType>,
+    outputs: Vec<StateType>,
+    delay: u64,
+}
+
+impl TransceiverGate {
+    pub fn new(id: String, delay: u64) -> Self {
+        Self {
+            id,
+            inputs: vec![StateType::Unknown; 3],
+            outputs: vec![StateType::Unknown; 2],
+            delay,
+        }
+    }
+}
+
+impl Gate for TransceiverGate {
+    fn id(&self) -> &str { &self.id }
+    fn gate_type(&self) -> &str { "TRANSCEIVER" }
+    fn input_count(&self) -> usize { 3 }
+    fn output_count(&self) -> usize { 2 }
+    fn get_inputs(&self) -> &[StateType] { &self.inputs }
+    fn get_outputs(&self) -> &[StateType] { &self.outputs }
+
+    fn set_input(&mut self, index: usize, state: StateType) {
+        if index < self.inputs.len() { self.inputs[index] = state; }
+    }
+
+    fn set_output(&mut self, index: usize, state: StateType) {
+        if index < self.outputs.len() { self.outputs[index] = state; }
+    }
+
+    fn evaluate(&mut self) -> GateResult {
+        self.outputs[0] = match self.inputs[1] {
+            StateType::One => self.inputs[0],
+            StateType::Zero => StateType::HiZ,
+            _ => StateType::Unknown,
+        };
+        self.outputs[1] = self.inputs[2];
+        GateResult { outputs: self.outputs.clone(), delay: self.delay }
+    }
+
+    fn reset(&mut self) {
+        self.inputs.fill(StateType::Unknown);
+        self.outputs.fill(StateType::Unknown);
+    }
+
+    fn delay(&self) -> u64 { self.delay }
+
+    fn inout_input(&self, output: usize) -> Option<usize> {
+        (output == 0).then_some(2)
+    }
+}
+
 /// Toggle Switch (User input)
 pub struct ToggleGate {
     id: String,
@@ -456,6 +612,10 @@ impl Gate for ToggleGate {
     fn get_outputs(&self) -> &[StateType] { &self.outputs }
     fn set_input(&mut self, _index: usize, _state: StateType) {}
 
+    fn set_output(&mut self, index: usize, state: StateType) {
+        if index < self.outputs.len() { self.outputs[index] = state; }
+    }
+
     fn evaluate(&mut self) -> GateResult {
         self.outputs[0] = self.state;
         GateResult { outputs: self.outputs.clone(), delay: 0 }
@@ -468,6 +628,16 @@ impl Gate for ToggleGate {
 
     fn delay(&self) -> u64 { 0 }
 
+    fn save_state(&self) -> Vec<u64> {
+        vec![self.state.to_u8() as u64]
+    }
+
+    fn load_state(&mut self, state: &[u64]) {
+        if let Some(&value) = state.first() {
+            self.state = StateType::from_u8(value as u8);
+        }
+    }
+
     fn toggle(&mut self) {
         self.state = if self.state == StateType::Zero {
             StateType::One
@@ -475,13 +645,21 @@ impl Gate for ToggleGate {
             StateType::Zero
         };
     }
+
+    fn set_level(&mut self, state: StateType) {
+        self.state = state;
+    }
 }
 
-/// Clock source (oscillates between ZERO and ONE)
+/// Clock source (oscillates between ZERO and ONE, toggling every `period` time units)
+///
+/// The output at time `t` is ONE when `((t + phase_offset) / period)` is odd,
+/// so clocks with different offsets run skewed against each other.
 pub struct ClockGate {
     id: String,
     outputs: Vec<StateType>,
     period: u64,
+    phase_offset: u64,
     state: StateType,
 }
 
@@ -491,19 +669,16 @@ impl ClockGate {
             id,
             outputs: vec![StateType::Zero; 1],
             period: 10,
+            phase_offset: 0,
             state: StateType::Zero,
         }
     }
 
-    pub fn tick(&mut self, time: u64) -> StateType {
-        let new_state = if (time / self.period) % 2 == 0 {
-            StateType::Zero
-        } else {
-            StateType::One
-        };
-        self.state = new_state;
-        self.outputs[0] = new_state;
-        new_state
+    /// `time` shifted by the phase offset, reduced to within one full cycle
+    /// so that large offsets cannot overflow
+    fn shifted(&self, time: u64) -> u64 {
+        let cycle = self.period.saturating_mul(2);
+        time.saturating_add(self.phase_offset % cycle)
     }
 }
 
@@ -516,6 +691,10 @@ impl Gate for ClockGate {
     fn get_outputs(&self) -> &[StateType] { &self.outputs }
     fn set_input(&mut self, _index: usize, _state: StateType) {}
 
+    fn set_output(&mut self, index: usize, state: StateType) {
+        if index < self.outputs.len() { self.outputs[index] = state; }
+    }
+
     fn evaluate(&mut self) -> GateResult {
         self.outputs[0] = self.state;
         GateResult { outputs: self.outputs.clone(), delay: 0 }
@@ -527,6 +706,40 @@ impl Gate for ClockGate {
     }
 
     fn delay(&self) -> u64 { 0 }
+
+    fn save_state(&self) -> Vec<u64> {
+        vec![self.state.to_u8() as u64, self.period, self.phase_offset]
+    }
+
+    fn load_state(&mut self, state: &[u64]) {
+        if let [state, period, rest @ ..] = state {
+            self.state = StateType::from_u8(*state as u8);
+            self.period = (*period).max(1);
+            self.phase_offset = rest.first().copied().unwrap_or(0);
+        }
+    }
+
+    fn tick(&mut self, time: u64) {
+        self.state = if (self.shifted(time) / self.period).is_multiple_of(2) {
+            StateType::Zero
+        } else {
+            StateType::One
+        };
+    }
+
+    fn next_wakeup(&self, time: u64) -> Option<u64> {
+        let shifted = self.shifted(time);
+        let edge = (shifted / self.period + 1).checked_mul(self.period)?;
+        Some(edge - (shifted - time))
+    }
+
+    fn set_period(&mut self, period: u64) {
+        self.period = period.max(1);
+    }
+
+    fn set_phase(&mut self, offset: u64) {
+        self.phase_offset = offset;
+    }
 }
 
 /// Pulse button (momentary HIGH)
@@ -535,6 +748,7 @@ pub struct PulseGate {
     outputs: Vec<StateType>,
     active: bool,
     pulse_end_time: u64,
+    time: u64,
 }
 
 impl PulseGate {
@@ -544,6 +758,7 @@ impl PulseGate {
             outputs: vec![StateType::Zero; 1],
             active: false,
             pulse_end_time: 0,
+            time: 0,
         }
     }
 }
@@ -557,73 +772,3573 @@ impl Gate for PulseGate {
     fn get_outputs(&self) -> &[StateType] { &self.outputs }
     fn set_input(&mut self, _index: usize, _state: StateType) {}
 
+    fn set_output(&mut self, index: usize, state: StateType) {
+        if index < self.outputs.len() { self.outputs[index] = state; }
+    }
+
     fn evaluate(&mut self) -> GateResult {
+        if self.active && self.time >= self.pulse_end_time {
+            self.active = false;
+        }
         self.outputs[0] = if self.active { StateType::One } else { StateType::Zero };
         GateResult { outputs: self.outputs.clone(), delay: 0 }
     }
 
     fn reset(&mut self) {
         self.active = false;
+        self.pulse_end_time = 0;
+        self.time = 0;
         self.outputs[0] = StateType::Zero;
     }
 
     fn delay(&self) -> u64 { 0 }
+
+    fn tick(&mut self, time: u64) {
+        self.time = time;
+    }
+
+    fn next_wakeup(&self, time: u64) -> Option<u64> {
+        (self.active && self.pulse_end_time > time).then_some(self.pulse_end_time)
+    }
+
+    fn fire_pulse(&mut self, end_time: u64) {
+        self.active = true;
+        self.pulse_end_time = end_time;
+    }
+
+    fn save_state(&self) -> Vec<u64> {
+        vec![self.active as u64, self.pulse_end_time, self.time]
+    }
+
+    fn load_state(&mut self, state: &[u64]) {
+        match state {
+            [active, pulse_end_time, time] => {
+                self.active = *active != 0;
+                self.pulse_end_time = *pulse_end_time;
+                self.time = *time;
+            }
+            [active, pulse_end_time] => {
+                self.active = *active != 0;
+                self.pulse_end_time = *pulse_end_time;
+            }
+            _ => {}
+        }
+    }
 }
 
-/// LED Output
-pub struct LedGate {
+/// One-shot monostable (inputs: [trigger], outputs: [Q])
+///
+/// A rising edge on `trigger` drives Q high for `width` time units, after
+/// which the gate wakes itself to drive Q low again. Edges during a pulse are
+/// ignored (non-retriggerable).
+pub struct MonostableGate {
     id: String,
     inputs: Vec<StateType>,
+    previous_inputs: Vec<StateType>,
+    outputs: Vec<StateType>,
+    width: u64,
+    time: u64,
+    pulse_end: Option<u64>,
+    delay: u64,
 }
 
-impl LedGate {
-    pub fn new(id: String) -> Self {
+impl MonostableGate {
+    pub fn new(id: String, width: u64, delay: u64) -> Self {
         Self {
             id,
             inputs: vec![StateType::Unknown; 1],
+            previous_inputs: vec![StateType::Unknown; 1],
+            outputs: vec![StateType::Zero; 1],
+            width: width.max(1),
+            time: 0,
+            pulse_end: None,
+            delay,
         }
     }
 }
 
-impl Gate for LedGate {
+impl Gate for MonostableGate {
     fn id(&self) -> &str { &self.id }
-    fn gate_type(&self) -> &str { "LED" }
+    fn gate_type(&self) -> &str { "ONESHOT" }
     fn input_count(&self) -> usize { 1 }
-    fn output_count(&self) -> usize { 0 }
+    fn output_count(&self) -> usize { 1 }
     fn get_inputs(&self) -> &[StateType] { &self.inputs }
-    fn get_outputs(&self) -> &[StateType] { &[] }
+    fn get_outputs(&self) -> &[StateType] { &self.outputs }
 
     fn set_input(&mut self, index: usize, state: StateType) {
         if index < self.inputs.len() { self.inputs[index] = state; }
     }
 
+    fn set_output(&mut self, index: usize, state: StateType) {
+        if index < self.outputs.len() { self.outputs[index] = state; }
+    }
+
     fn evaluate(&mut self) -> GateResult {
-        GateResult { outputs: vec![], delay: 0 }
+        if self.pulse_end.is_some_and(|end| self.time >= end) {
+            self.pulse_end = None;
+        }
+        if self.pulse_end.is_none() && self.is_rising_edge(0) {
+            self.pulse_end = Some(self.time + self.width);
+        }
+        self.outputs[0] = if self.pulse_end.is_some() { StateType::One } else { StateType::Zero };
+        self.update_previous_inputs();
+        GateResult { outputs: self.outputs.clone(), delay: self.delay }
     }
 
     fn reset(&mut self) {
         self.inputs.fill(StateType::Unknown);
+        self.previous_inputs.fill(StateType::Unknown);
+        self.outputs[0] = StateType::Zero;
+        self.time = 0;
+        self.pulse_end = None;
     }
 
-    fn delay(&self) -> u64 { 0 }
+    fn delay(&self) -> u64 { self.delay }
+    fn is_sequential(&self) -> bool { true }
+
+    fn save_state(&self) -> Vec<u64> {
+        // pulse_end is stored off by one so that 0 means idle
+        vec![self.previous_inputs[0].to_u8() as u64, self.time, self.pulse_end.map_or(0, |end| end + 1)]
+    }
+
+    fn load_state(&mut self, state: &[u64]) {
+        if let [previous, time, pulse_end] = state {
+            self.previous_inputs[0] = StateType::from_u8(*previous as u8);
+            self.time = *time;
+            self.pulse_end = pulse_end.checked_sub(1);
+        }
+    }
+
+    fn is_rising_edge(&self, index: usize) -> bool {
+        self.previous_inputs.get(index) == Some(&StateType::Zero)
+            && self.inputs.get(index) == Some(&StateType::One)
+    }
+
+    fn update_previous_inputs(&mut self) {
+        self.previous_inputs.copy_from_slice(&self.inputs);
+    }
+
+    fn tick(&mut self, time: u64) {
+        self.time = time;
+    }
+
+    fn next_wakeup(&self, time: u64) -> Option<u64> {
+        self.pulse_end.filter(|&end| end > time)
+    }
 }
 
-/// Factory function to create gates by type
-pub fn create_gate(gate_type: &str, id: String, input_count: Option<usize>) -> Box<dyn Gate> {
-    match gate_type {
-        "AND" => Box::new(AndGate::new(id, input_count.unwrap_or(2), 1)),
-        "OR" => Box::new(OrGate::new(id, input_count.unwrap_or(2), 1)),
-        "NOT" => Box::new(NotGate::new(id, 1)),
-        "XOR" => Box::new(XorGate::new(id, input_count.unwrap_or(2), 1)),
-        "NAND" => Box::new(NandGate::new(id, input_count.unwrap_or(2), 1)),
-        "NOR" => Box::new(NorGate::new(id, input_count.unwrap_or(2), 1)),
-        "XNOR" => Box::new(XnorGate::new(id, input_count.unwrap_or(2), 1)),
-        "BUFFER" => Box::new(BufferGate::new(id, 1)),
-        "TRI_BUFFER" => Box::new(TriBufferGate::new(id, 1)),
-        "TOGGLE" => Box::new(ToggleGate::new(id)),
-        "CLOCK" => Box::new(ClockGate::new(id)),
-        "PULSE" => Box::new(PulseGate::new(id)),
-        "LED" => Box::new(LedGate::new(id)),
-        _ => Box::new(BufferGate::new(id, 1)), // Default fallback
+/// Hysteresis buffer (inputs: [in], outputs: [out])
+///
+/// States are discrete, so hysteresis is modelled as persistence: the output
+/// only follows a new input value once `hold` consecutive evaluations have
+/// seen it. While a change is pending the gate wakes itself every time unit
+/// to evaluate again, so a steady input is still taken. A glitch that is
+/// gone by the next evaluation is swallowed.
+pub struct SchmittGate {
+    id: String,
+    inputs: Vec<StateType>,
+    outputs: Vec<StateType>,
+    hold: u32,
+    /// Candidate input value and the evaluations that have seen it so far
+    pending: Option<(StateType, u32)>,
+    delay: u64,
+}
+
+impl SchmittGate {
+    pub fn new(id: String, hold: u32, delay: u64) -> Self {
+        Self {
+            id,
+            inputs: vec![StateType::Unknown; 1],
+            outputs: vec![StateType::Unknown; 1],
+            hold: hold.max(1),
+            pending: None,
+            delay,
+        }
+    }
+}
+
+impl Gate for SchmittGate {
+    fn id(&self) -> &str { &self.id }
+    fn gate_type(&self) -> &str { "SCHMITT" }
+    fn input_count(&self) -> usize { 1 }
+    fn output_count(&self) -> usize { 1 }
+    fn get_inputs(&self) -> &[StateType] { &self.inputs }
+    fn get_outputs(&self) -> &[StateType] { &self.outputs }
+
+    fn set_input(&mut self, index: usize, state: StateType) {
+        if index < self.inputs.len() { self.inputs[index] = state; }
+    }
+
+    fn set_output(&mut self, index: usize, state: StateType) {
+        if index < self.outputs.len() { self.outputs[index] = state; }
+    }
+
+    fn evaluate(&mut self) -> GateResult {
+        let input = self.inputs[0];
+        if input == self.outputs[0] {
+            self.pending = None;
+        } else {
+            let seen = match self.pending {
+                Some((candidate, seen)) if candidate == input => seen + 1,
+                _ => 1,
+            };
+            if seen >= self.hold {
+                self.outputs[0] = input;
+                self.pending = None;
+            } else {
+                self.pending = Some((input, seen));
+            }
+        }
+        GateResult { outputs: self.outputs.clone(), delay: self.delay }
+    }
+
+    fn reset(&mut self) {
+        self.inputs.fill(StateType::Unknown);
+        self.outputs.fill(StateType::Unknown);
+        self.pending = None;
+    }
+
+    fn delay(&self) -> u64 { self.delay }
+    fn is_sequential(&self) -> bool { true }
+
+    fn save_state(&self) -> Vec<u64> {
+        // The candidate is stored off by one so that 0 means none pending
+        match self.pending {
+            Some((candidate, seen)) => vec![candidate.to_u8() as u64 + 1, seen as u64],
+            None => vec![0, 0],
+        }
+    }
+
+    fn load_state(&mut self, state: &[u64]) {
+        if let [candidate, seen] = state {
+            self.pending = candidate.checked_sub(1).map(|c| (StateType::from_u8(c as u8), *seen as u32));
+        }
+    }
+
+    fn next_wakeup(&self, time: u64) -> Option<u64> {
+        self.pending.map(|_| time.saturating_add(1))
+    }
+}
+
+/// Switch debouncer (inputs: [in], outputs: [out])
+///
+/// The output follows an input transition immediately, then ignores further
+/// transitions until `window` time units after it. If the input still
+/// differs from the output when the window closes, the gate wakes itself
+/// and takes the settled value.
+pub struct DebounceGate {
+    id: String,
+    inputs: Vec<StateType>,
+    outputs: Vec<StateType>,
+    window: u64,
+    time: u64,
+    /// Time of the last accepted transition
+    accepted_at: Option<u64>,
+    delay: u64,
+}
+
+impl DebounceGate {
+    pub fn new(id: String, window: u64, delay: u64) -> Self {
+        Self {
+            id,
+            inputs: vec![StateType::Unknown; 1],
+            outputs: vec![StateType::Unknown; 1],
+            window: window.max(1),
+            time: 0,
+            accepted_at: None,
+            delay,
+        }
+    }
+
+    /// End of the lockout window, if one is running at `time`
+    fn window_end(&self, time: u64) -> Option<u64> {
+        self.accepted_at.map(|at| at + self.window).filter(|&end| end > time)
+    }
+}
+
+impl Gate for DebounceGate {
+    fn id(&self) -> &str { &self.id }
+    fn gate_type(&self) -> &str { "DEBOUNCE" }
+    fn input_count(&self) -> usize { 1 }
+    fn output_count(&self) -> usize { 1 }
+    fn get_inputs(&self) -> &[StateType] { &self.inputs }
+    fn get_outputs(&self) -> &[StateType] { &self.outputs }
+
+    fn set_input(&mut self, index: usize, state: StateType) {
+        if index < self.inputs.len() { self.inputs[index] = state; }
+    }
+
+    fn set_output(&mut self, index: usize, state: StateType) {
+        if index < self.outputs.len() { self.outputs[index] = state; }
+    }
+
+    fn evaluate(&mut self) -> GateResult {
+        let input = self.inputs[0];
+        if input != self.outputs[0] && self.window_end(self.time).is_none() {
+            self.outputs[0] = input;
+            self.accepted_at = Some(self.time);
+        }
+        GateResult { outputs: self.outputs.clone(), delay: self.delay }
+    }
+
+    fn reset(&mut self) {
+        self.inputs.fill(StateType::Unknown);
+        self.outputs.fill(StateType::Unknown);
+        self.time = 0;
+        self.accepted_at = None;
+    }
+
+    fn delay(&self) -> u64 { self.delay }
+    fn is_sequential(&self) -> bool { true }
+
+    fn save_state(&self) -> Vec<u64> {
+        // Stored off by one so that 0 means no transition accepted yet
+        vec![self.time, self.accepted_at.map_or(0, |at| at + 1)]
+    }
+
+    fn load_state(&mut self, state: &[u64]) {
+        if let [time, accepted_at] = state {
+            self.time = *time;
+            self.accepted_at = accepted_at.checked_sub(1);
+        }
+    }
+
+    fn tick(&mut self, time: u64) {
+        self.time = time;
+    }
+
+    fn next_wakeup(&self, time: u64) -> Option<u64> {
+        if self.inputs[0] == self.outputs[0] {
+            return None;
+        }
+        self.window_end(time)
+    }
+}
+
+/// Constant source tied to a fixed logic level (CONST_HIGH / CONST_LOW)
+pub struct ConstantGate {
+    id: String,
+    level: StateType,
+    outputs: Vec<StateType>,
+}
+
+impl ConstantGate {
+    pub fn new(id: String, level: StateType) -> Self {
+        Self {
+            id,
+            level,
+            outputs: vec![level; 1],
+        }
+    }
+}
+
+impl Gate for ConstantGate {
+    fn id(&self) -> &str { &self.id }
+    fn gate_type(&self) -> &str {
+        if self.level == StateType::One { "CONST_HIGH" } else { "CONST_LOW" }
+    }
+    fn input_count(&self) -> usize { 0 }
+    fn output_count(&self) -> usize { 1 }
+    fn get_inputs(&self) -> &[StateType] { &[] }
+    fn get_outputs(&self) -> &[StateType] { &self.outputs }
+    fn set_input(&mut self, _index: usize, _state: StateType) {}
+
+    fn evaluate(&mut self) -> GateResult {
+        GateResult { outputs: self.outputs.clone(), delay: 0 }
+    }
+
+    // The level is fixed, so reset has nothing to clear
+    fn reset(&mut self) {}
+
+    fn delay(&self) -> u64 { 0 }
+}
+
+/// Pull-up resistor (weak ONE, overridden by any strong driver on the net)
+pub struct PullUpGate {
+    id: String,
+    outputs: Vec<StateType>,
+}
+
+impl PullUpGate {
+    pub fn new(id: String) -> Self {
+        Self {
+            id,
+            outputs: vec![StateType::WeakOne; 1],
+        }
+    }
+}
+
+impl Gate for PullUpGate {
+    fn id(&self) -> &str { &self.id }
+    fn gate_type(&self) -> &str { "PULLUP" }
+    fn input_count(&self) -> usize { 0 }
+    fn output_count(&self) -> usize { 1 }
+    fn get_inputs(&self) -> &[StateType] { &[] }
+    fn get_outputs(&self) -> &[StateType] { &self.outputs }
+    fn set_input(&mut self, _index: usize, _state: StateType) {}
+
+    fn set_output(&mut self, index: usize, state: StateType) {
+        if index < self.outputs.len() { self.outputs[index] = state; }
+    }
+
+    fn evaluate(&mut self) -> GateResult {
+        self.outputs[0] = StateType::WeakOne;
+        GateResult { outputs: self.outputs.clone(), delay: 0 }
+    }
+
+    fn reset(&mut self) {
+        self.outputs[0] = StateType::WeakOne;
+    }
+
+    fn delay(&self) -> u64 { 0 }
+}
+
+/// Pull-down resistor (weak ZERO, overridden by any strong driver on the net)
+pub struct PullDownGate {
+    id: String,
+    outputs: Vec<StateType>,
+}
+
+impl PullDownGate {
+    pub fn new(id: String) -> Self {
+        Self {
+            id,
+            outputs: vec![StateType::WeakZero; 1],
+        }
+    }
+}
+
+impl Gate for PullDownGate {
+    fn id(&self) -> &str { &self.id }
+    fn gate_type(&self) -> &str { "PULLDOWN" }
+    fn input_count(&self) -> usize { 0 }
+    fn output_count(&self) -> usize { 1 }
+    fn get_inputs(&self) -> &[StateType] { &[] }
+    fn get_outputs(&self) -> &[StateType] { &self.outputs }
+    fn set_input(&mut self, _index: usize, _state: StateType) {}
+
+    fn set_output(&mut self, index: usize, state: StateType) {
+        if index < self.outputs.len() { self.outputs[index] = state; }
+    }
+
+    fn evaluate(&mut self) -> GateResult {
+        self.outputs[0] = StateType::WeakZero;
+        GateResult { outputs: self.outputs.clone(), delay: 0 }
+    }
+
+    fn reset(&mut self) {
+        self.outputs[0] = StateType::WeakZero;
+    }
+
+    fn delay(&self) -> u64 { 0 }
+}
+
+/// LED Output
+pub struct LedGate {
+    id: String,
+    inputs: Vec<StateType>,
+}
+
+impl LedGate {
+    pub fn new(id: String) -> Self {
+        Self {
+            id,
+            inputs: vec![StateType::Unknown; 1],
+        }
+    }
+}
+
+impl Gate for LedGate {
+    fn id(&self) -> &str { &self.id }
+    fn gate_type(&self) -> &str { "LED" }
+    fn input_count(&self) -> usize { 1 }
+    fn output_count(&self) -> usize { 0 }
+    fn get_inputs(&self) -> &[StateType] { &self.inputs }
+    fn get_outputs(&self) -> &[StateType] { &[] }
+
+    fn set_input(&mut self, index: usize, state: StateType) {
+        if index < self.inputs.len() { self.inputs[index] = state; }
+    }
+
+    fn evaluate(&mut self) -> GateResult {
+        GateResult { outputs: vec![], delay: 0 }
+    }
+
+    fn reset(&mut self) {
+        self.inputs.fill(StateType::Unknown);
+    }
+
+    fn delay(&self) -> u64 { 0 }
+}
+
+/// Resolve active-low asynchronous PRE/CLR inputs to a forced Q, if any
+///
+/// Only a ZERO level asserts; both asserted at once yields Conflict.
+fn async_preset_clear(pre: StateType, clr: StateType) -> Option<StateType> {
+    match (pre, clr) {
+        (StateType::Zero, StateType::Zero) => Some(StateType::Conflict),
+        (StateType::Zero, _) => Some(StateType::One),
+        (_, StateType::Zero) => Some(StateType::Zero),
+        _ => None,
+    }
+}
+
+/// D Flip-Flop (inputs: [data, clock] or [data, clock, PRE_n, CLR_n],
+/// outputs: [Q, Q_bar])
+///
+/// Latches `data` on the rising edge of `clock`; holds otherwise. With
+/// asynchronous controls, active-low PRE_n/CLR_n force Q high/low regardless
+/// of the clock (Conflict if both are asserted).
+pub struct DFlipFlopGate {
+    id: String,
+    inputs: Vec<StateType>,
+    previous_inputs: Vec<StateType>,
+    outputs: Vec<StateType>,
+    delay: u64,
+}
+
+impl DFlipFlopGate {
+    pub fn new(id: String, async_controls: bool, delay: u64) -> Self {
+        let input_count = if async_controls { 4 } else { 2 };
+        Self {
+            id,
+            inputs: vec![StateType::Unknown; input_count],
+            previous_inputs: vec![StateType::Unknown; input_count],
+            outputs: vec![StateType::Zero, StateType::One],
+            delay,
+        }
+    }
+}
+
+impl Gate for DFlipFlopGate {
+    fn id(&self) -> &str { &self.id }
+    fn gate_type(&self) -> &str { "DFF" }
+    fn input_count(&self) -> usize { self.inputs.len() }
+    fn output_count(&self) -> usize { 2 }
+    fn get_inputs(&self) -> &[StateType] { &self.inputs }
+    fn get_outputs(&self) -> &[StateType] { &self.outputs }
+
+    fn set_input(&mut self, index: usize, state: StateType) {
+        if index < self.inputs.len() { self.inputs[index] = state; }
+    }
+
+    fn set_output(&mut self, index: usize, state: StateType) {
+        if index < self.outputs.len() { self.outputs[index] = state; }
+    }
+
+    fn evaluate(&mut self) -> GateResult {
+        let forced = match self.inputs[..] {
+            [_, _, pre, clr] => async_preset_clear(pre, clr),
+            _ => None,
+        };
+        if let Some(q) = forced {
+            self.outputs[0] = q;
+            self.outputs[1] = q.not();
+        } else if self.is_rising_edge(1) {
+            let data = self.inputs[0];
+            self.outputs[0] = data;
+            self.outputs[1] = data.not();
+        }
+        self.update_previous_inputs();
+        GateResult { outputs: self.outputs.clone(), delay: self.delay }
+    }
+
+    fn reset(&mut self) {
+        self.inputs.fill(StateType::Unknown);
+        self.previous_inputs.fill(StateType::Unknown);
+        self.outputs[0] = StateType::Zero;
+        self.outputs[1] = StateType::One;
+    }
+
+    fn delay(&self) -> u64 { self.delay }
+    fn is_sequential(&self) -> bool { true }
+
+    fn save_state(&self) -> Vec<u64> {
+        self.previous_inputs.iter().map(|s| s.to_u8() as u64).collect()
+    }
+
+    fn load_state(&mut self, state: &[u64]) {
+        for (previous, &value) in self.previous_inputs.iter_mut().zip(state) {
+            *previous = StateType::from_u8(value as u8);
+        }
+    }
+
+    fn is_rising_edge(&self, index: usize) -> bool {
+        self.previous_inputs.get(index) == Some(&StateType::Zero)
+            && self.inputs.get(index) == Some(&StateType::One)
+    }
+
+    fn update_previous_inputs(&mut self) {
+        self.previous_inputs.copy_from_slice(&self.inputs);
+    }
+}
+
+/// JK Flip-Flop (inputs: [J, K, clock] or [J, K, clock, PRE_n, CLR_n],
+/// outputs: [Q, Q_bar])
+///
+/// On the rising edge of `clock`: J=0,K=0 holds, J=1,K=0 sets, J=0,K=1 resets
+/// and J=1,K=1 toggles. A non-binary J or K drives both outputs Unknown.
+/// Asynchronous PRE_n/CLR_n behave as on `DFlipFlopGate`.
+pub struct JkFlipFlopGate {
+    id: String,
+    inputs: Vec<StateType>,
+    previous_inputs: Vec<StateType>,
+    outputs: Vec<StateType>,
+    delay: u64,
+}
+
+impl JkFlipFlopGate {
+    pub fn new(id: String, async_controls: bool, delay: u64) -> Self {
+        let input_count = if async_controls { 5 } else { 3 };
+        Self {
+            id,
+            inputs: vec![StateType::Unknown; input_count],
+            previous_inputs: vec![StateType::Unknown; input_count],
+            outputs: vec![StateType::Zero, StateType::One],
+            delay,
+        }
+    }
+}
+
+impl Gate for JkFlipFlopGate {
+    fn id(&self) -> &str { &self.id }
+    fn gate_type(&self) -> &str { "JKFF" }
+    fn input_count(&self) -> usize { self.inputs.len() }
+    fn output_count(&self) -> usize { 2 }
+    fn get_inputs(&self) -> &[StateType] { &self.inputs }
+    fn get_outputs(&self) -> &[StateType] { &self.outputs }
+
+    fn set_input(&mut self, index: usize, state: StateType) {
+        if index < self.inputs.len() { self.inputs[index] = state; }
+    }
+
+    fn set_output(&mut self, index: usize, state: StateType) {
+        if index < self.outputs.len() { self.outputs[index] = state; }
+    }
+
+    fn evaluate(&mut self) -> GateResult {
+        let forced = match self.inputs[..] {
+            [_, _, _, pre, clr] => async_preset_clear(pre, clr),
+            _ => None,
+        };
+        if let Some(q) = forced {
+            self.outputs[0] = q;
+            self.outputs[1] = q.not();
+        } else if self.is_rising_edge(2) {
+            let q = match (self.inputs[0], self.inputs[1]) {
+                (StateType::Zero, StateType::Zero) => self.outputs[0],
+                (StateType::One, StateType::Zero) => StateType::One,
+                (StateType::Zero, StateType::One) => StateType::Zero,
+                (StateType::One, StateType::One) => self.outputs[0].not(),
+                _ => StateType::Unknown,
+            };
+            self.outputs[0] = q;
+            self.outputs[1] = q.not();
+        }
+        self.update_previous_inputs();
+        GateResult { outputs: self.outputs.clone(), delay: self.delay }
+    }
+
+    fn reset(&mut self) {
+        self.inputs.fill(StateType::Unknown);
+        self.previous_inputs.fill(StateType::Unknown);
+        self.outputs[0] = StateType::Zero;
+        self.outputs[1] = StateType::One;
+    }
+
+    fn delay(&self) -> u64 { self.delay }
+    fn is_sequential(&self) -> bool { true }
+
+    fn save_state(&self) -> Vec<u64> {
+        self.previous_inputs.iter().map(|s| s.to_u8() as u64).collect()
+    }
+
+    fn load_state(&mut self, state: &[u64]) {
+        for (previous, &value) in self.previous_inputs.iter_mut().zip(state) {
+            *previous = StateType::from_u8(value as u8);
+        }
+    }
+
+    fn is_rising_edge(&self, index: usize) -> bool {
+        self.previous_inputs.get(index) == Some(&StateType::Zero)
+            && self.inputs.get(index) == Some(&StateType::One)
+    }
+
+    fn update_previous_inputs(&mut self) {
+        self.previous_inputs.copy_from_slice(&self.inputs);
+    }
+}
+
+/// T Flip-Flop (inputs: [T, clock], outputs: [Q, Q_bar])
+///
+/// On the rising edge of `clock`: T=1 toggles, T=0 holds. A non-binary T
+/// drives both outputs Unknown.
+pub struct TFlipFlopGate {
+    id: String,
+    inputs: Vec<StateType>,
+    previous_inputs: Vec<StateType>,
+    outputs: Vec<StateType>,
+    delay: u64,
+}
+
+impl TFlipFlopGate {
+    pub fn new(id: String, delay: u64) -> Self {
+        Self {
+            id,
+            inputs: vec![StateType::Unknown; 2],
+            previous_inputs: vec![StateType::Unknown; 2],
+            outputs: vec![StateType::Zero, StateType::One],
+            delay,
+        }
+    }
+}
+
+impl Gate for TFlipFlopGate {
+    fn id(&self) -> &str { &self.id }
+    fn gate_type(&self) -> &str { "TFF" }
+    fn input_count(&self) -> usize { 2 }
+    fn output_count(&self) -> usize { 2 }
+    fn get_inputs(&self) -> &[StateType] { &self.inputs }
+    fn get_outputs(&self) -> &[StateType] { &self.outputs }
+
+    fn set_input(&mut self, index: usize, state: StateType) {
+        if index < self.inputs.len() { self.inputs[index] = state; }
+    }
+
+    fn set_output(&mut self, index: usize, state: StateType) {
+        if index < self.outputs.len() { self.outputs[index] = state; }
+    }
+
+    fn evaluate(&mut self) -> GateResult {
+        if self.is_rising_edge(1) {
+            let q = match self.inputs[0] {
+                StateType::Zero => self.outputs[0],
+                StateType::One => self.outputs[0].not(),
+                _ => StateType::Unknown,
+            };
+            self.outputs[0] = q;
+            self.outputs[1] = q.not();
+        }
+        self.update_previous_inputs();
+        GateResult { outputs: self.outputs.clone(), delay: self.delay }
+    }
+
+    fn reset(&mut self) {
+        self.inputs.fill(StateType::Unknown);
+        self.previous_inputs.fill(StateType::Unknown);
+        self.outputs[0] = StateType::Zero;
+        self.outputs[1] = StateType::One;
+    }
+
+    fn delay(&self) -> u64 { self.delay }
+    fn is_sequential(&self) -> bool { true }
+
+    fn save_state(&self) -> Vec<u64> {
+        self.previous_inputs.iter().map(|s| s.to_u8() as u64).collect()
+    }
+
+    fn load_state(&mut self, state: &[u64]) {
+        for (previous, &value) in self.previous_inputs.iter_mut().zip(state) {
+            *previous = StateType::from_u8(value as u8);
+        }
+    }
+
+    fn is_rising_edge(&self, index: usize) -> bool {
+        self.previous_inputs.get(index) == Some(&StateType::Zero)
+            && self.inputs.get(index) == Some(&StateType::One)
+    }
+
+    fn update_previous_inputs(&mut self) {
+        self.previous_inputs.copy_from_slice(&self.inputs);
+    }
+}
+
+/// SR Latch (inputs: [S, R], outputs: [Q, Q_bar])
+///
+/// Level-sensitive: S sets, R resets, S=0,R=0 holds the stored state.
+/// S=1,R=1 is the forbidden state and drives both outputs to Conflict
+/// until a valid set or reset is applied.
+pub struct SrLatchGate {
+    id: String,
+    inputs: Vec<StateType>,
+    outputs: Vec<StateType>,
+    state: StateType,
+    delay: u64,
+}
+
+impl SrLatchGate {
+    pub fn new(id: String, delay: u64) -> Self {
+        Self {
+            id,
+            inputs: vec![StateType::Unknown; 2],
+            outputs: vec![StateType::Zero, StateType::One],
+            state: StateType::Zero,
+            delay,
+        }
+    }
+}
+
+impl Gate for SrLatchGate {
+    fn id(&self) -> &str { &self.id }
+    fn gate_type(&self) -> &str { "SR_LATCH" }
+    fn input_count(&self) -> usize { 2 }
+    fn output_count(&self) -> usize { 2 }
+    fn get_inputs(&self) -> &[StateType] { &self.inputs }
+    fn get_outputs(&self) -> &[StateType] { &self.outputs }
+
+    fn set_input(&mut self, index: usize, state: StateType) {
+        if index < self.inputs.len() { self.inputs[index] = state; }
+    }
+
+    fn set_output(&mut self, index: usize, state: StateType) {
+        if index < self.outputs.len() { self.outputs[index] = state; }
+    }
+
+    fn evaluate(&mut self) -> GateResult {
+        self.state = match (self.inputs[0], self.inputs[1]) {
+            (StateType::Zero, StateType::Zero) => self.state,
+            (StateType::One, StateType::Zero) => StateType::One,
+            (StateType::Zero, StateType::One) => StateType::Zero,
+            (StateType::One, StateType::One) => StateType::Conflict,
+            _ => StateType::Unknown,
+        };
+        self.outputs[0] = self.state;
+        self.outputs[1] = self.state.not();
+        GateResult { outputs: self.outputs.clone(), delay: self.delay }
+    }
+
+    fn reset(&mut self) {
+        self.inputs.fill(StateType::Unknown);
+        self.state = StateType::Zero;
+        self.outputs[0] = StateType::Zero;
+        self.outputs[1] = StateType::One;
+    }
+
+    fn delay(&self) -> u64 { self.delay }
+    fn is_sequential(&self) -> bool { true }
+
+    fn save_state(&self) -> Vec<u64> {
+        vec![self.state.to_u8() as u64]
+    }
+
+    fn load_state(&mut self, state: &[u64]) {
+        if let Some(&value) = state.first() {
+            self.state = StateType::from_u8(value as u8);
+        }
+    }
+}
+
+/// Interpret input bits (LSB first) as an unsigned value; None if any bit is not 0/1
+fn binary_value(bits: &[StateType]) -> Option<usize> {
+    bits.iter().enumerate().try_fold(0usize, |value, (i, &bit)| match bit {
+        StateType::Zero => Some(value),
+        StateType::One => Some(value | (1 << i)),
+        _ => None,
+    })
+}
+
+/// Maximum select width of a decoder (2^16 outputs)
+const MAX_DECODER_SELECT: usize = 16;
+
+/// Binary Decoder (inputs: [s0..s(n-1), enable?], outputs: 2^n one-hot lines)
+///
+/// `s0` is the least significant select bit. With an enable input, a low
+/// enable forces every output to ZERO. Any unknown select drives all
+/// outputs Unknown.
+pub struct DecoderGate {
+    id: String,
+    inputs: Vec<StateType>,
+    outputs: Vec<StateType>,
+    select_count: usize,
+    delay: u64,
+}
+
+impl DecoderGate {
+    pub fn new(id: String, select_count: usize, has_enable: bool, delay: u64) -> Self {
+        let select_count = select_count.clamp(1, MAX_DECODER_SELECT);
+        Self {
+            id,
+            inputs: vec![StateType::Unknown; select_count + has_enable as usize],
+            outputs: vec![StateType::Unknown; 1 << select_count],
+            select_count,
+            delay,
+        }
+    }
+}
+
+impl Gate for DecoderGate {
+    fn id(&self) -> &str { &self.id }
+    fn gate_type(&self) -> &str {
+        if self.inputs.len() > self.select_count { "DECODER_EN" } else { "DECODER" }
+    }
+    fn input_count(&self) -> usize { self.inputs.len() }
+    fn output_count(&self) -> usize { self.outputs.len() }
+    fn get_inputs(&self) -> &[StateType] { &self.inputs }
+    fn get_outputs(&self) -> &[StateType] { &self.outputs }
+
+    fn set_input(&mut self, index: usize, state: StateType) {
+        if index < self.inputs.len() { self.inputs[index] = state; }
+    }
+
+    fn set_output(&mut self, index: usize, state: StateType) {
+        if index < self.outputs.len() { self.outputs[index] = state; }
+    }
+
+    fn evaluate(&mut self) -> GateResult {
+        let enable = self.inputs.get(self.select_count).copied().unwrap_or(StateType::One);
+        match (enable, binary_value(&self.inputs[..self.select_count])) {
+            (StateType::Zero, _) => self.outputs.fill(StateType::Zero),
+            (StateType::One, Some(selected)) => {
+                self.outputs.fill(StateType::Zero);
+                self.outputs[selected] = StateType::One;
+            }
+            _ => self.outputs.fill(StateType::Unknown),
+        }
+        GateResult { outputs: self.outputs.clone(), delay: self.delay }
+    }
+
+    fn reset(&mut self) {
+        self.inputs.fill(StateType::Unknown);
+        self.outputs.fill(StateType::Unknown);
+    }
+
+    fn delay(&self) -> u64 { self.delay }
+}
+
+/// Priority encoder (inputs: [i0..i(2^n-1)], outputs: [y0..y(n-1), valid])
+///
+/// Encodes the index of the highest asserted input (`y0` least significant)
+/// and raises `valid`. With no input asserted all outputs are ZERO. A
+/// non-binary input above the highest ONE drives all outputs Unknown.
+pub struct PriorityEncoderGate {
+    id: String,
+    inputs: Vec<StateType>,
+    outputs: Vec<StateType>,
+    delay: u64,
+}
+
+impl PriorityEncoderGate {
+    pub fn new(id: String, output_bits: usize, delay: u64) -> Self {
+        let output_bits = output_bits.clamp(1, MAX_DECODER_SELECT);
+        Self {
+            id,
+            inputs: vec![StateType::Unknown; 1 << output_bits],
+            outputs: vec![StateType::Unknown; output_bits + 1],
+            delay,
+        }
+    }
+}
+
+impl Gate for PriorityEncoderGate {
+    fn id(&self) -> &str { &self.id }
+    fn gate_type(&self) -> &str { "PRIORITY_ENC" }
+    fn input_count(&self) -> usize { self.inputs.len() }
+    fn output_count(&self) -> usize { self.outputs.len() }
+    fn get_inputs(&self) -> &[StateType] { &self.inputs }
+    fn get_outputs(&self) -> &[StateType] { &self.outputs }
+
+    fn set_input(&mut self, index: usize, state: StateType) {
+        if index < self.inputs.len() { self.inputs[index] = state; }
+    }
+
+    fn set_output(&mut self, index: usize, state: StateType) {
+        if index < self.outputs.len() { self.outputs[index] = state; }
+    }
+
+    fn evaluate(&mut self) -> GateResult {
+        let highest = self
+            .inputs
+            .iter()
+            .rposition(|&input| input != StateType::Zero)
+            .map(|index| (index, self.inputs[index]));
+        let width = self.outputs.len() - 1;
+        match highest {
+            Some((index, StateType::One)) => {
+                write_word(&mut self.outputs[..width], index as u32);
+                self.outputs[width] = StateType::One;
+            }
+            Some(_) => self.outputs.fill(StateType::Unknown),
+            None => self.outputs.fill(StateType::Zero),
+        }
+        GateResult { outputs: self.outputs.clone(), delay: self.delay }
+    }
+
+    fn reset(&mut self) {
+        self.inputs.fill(StateType::Unknown);
+        self.outputs.fill(StateType::Unknown);
+    }
+
+    fn delay(&self) -> u64 { self.delay }
+}
+
+/// Demultiplexer (inputs: [data, s0..s(n-1)], outputs: 2^n lines)
+///
+/// Routes `data` to the output indexed by the select bits (`s0` least
+/// significant); the other outputs idle at ZERO, or HiZ for the tri-state
+/// variant. Any unknown select drives all outputs Unknown.
+pub struct DemuxGate {
+    id: String,
+    inputs: Vec<StateType>,
+    outputs: Vec<StateType>,
+    idle: StateType,
+    delay: u64,
+}
+
+impl DemuxGate {
+    pub fn new(id: String, select_count: usize, tri_state: bool, delay: u64) -> Self {
+        let select_count = select_count.clamp(1, MAX_DECODER_SELECT);
+        Self {
+            id,
+            inputs: vec![StateType::Unknown; select_count + 1],
+            outputs: vec![StateType::Unknown; 1 << select_count],
+            idle: if tri_state { StateType::HiZ } else { StateType::Zero },
+            delay,
+        }
+    }
+}
+
+impl Gate for DemuxGate {
+    fn id(&self) -> &str { &self.id }
+    fn gate_type(&self) -> &str {
+        if self.idle == StateType::HiZ { "DEMUX2_TRI" } else { "DEMUX2" }
+    }
+    fn input_count(&self) -> usize { self.inputs.len() }
+    fn output_count(&self) -> usize { self.outputs.len() }
+    fn get_inputs(&self) -> &[StateType] { &self.inputs }
+    fn get_outputs(&self) -> &[StateType] { &self.outputs }
+
+    fn set_input(&mut self, index: usize, state: StateType) {
+        if index < self.inputs.len() { self.inputs[index] = state; }
+    }
+
+    fn set_output(&mut self, index: usize, state: StateType) {
+        if index < self.outputs.len() { self.outputs[index] = state; }
+    }
+
+    fn evaluate(&mut self) -> GateResult {
+        match binary_value(&self.inputs[1..]) {
+            Some(selected) => {
+                self.outputs.fill(self.idle);
+                self.outputs[selected] = self.inputs[0];
+            }
+            None => self.outputs.fill(StateType::Unknown),
+        }
+        GateResult { outputs: self.outputs.clone(), delay: self.delay }
+    }
+
+    fn reset(&mut self) {
+        self.inputs.fill(StateType::Unknown);
+        self.outputs.fill(StateType::Unknown);
+    }
+
+    fn delay(&self) -> u64 { self.delay }
+}
+
+/// One-bit full addition using StateType logic: (sum, carry out)
+fn full_add(a: StateType, b: StateType, carry_in: StateType) -> (StateType, StateType) {
+    let sum = a.xor(b).xor(carry_in);
+    let carry_out = a.and(b).or(a.and(carry_in)).or(b.and(carry_in));
+    (sum, carry_out)
+}
+
+/// Half Adder (inputs: [A, B], outputs: [Sum, Carry])
+pub struct HalfAdderGate {
+    id: String,
+    inputs: Vec<StateType>,
+    outputs: Vec<StateType>,
+    delay: u64,
+}
+
+impl HalfAdderGate {
+    pub fn new(id: String, delay: u64) -> Self {
+        Self {
+            id,
+            inputs: vec![StateType::Unknown; 2],
+            outputs: vec![StateType::Unknown; 2],
+            delay,
+        }
+    }
+}
+
+impl Gate for HalfAdderGate {
+    fn id(&self) -> &str { &self.id }
+    fn gate_type(&self) -> &str { "HALF_ADDER" }
+    fn input_count(&self) -> usize { 2 }
+    fn output_count(&self) -> usize { 2 }
+    fn get_inputs(&self) -> &[StateType] { &self.inputs }
+    fn get_outputs(&self) -> &[StateType] { &self.outputs }
+
+    fn set_input(&mut self, index: usize, state: StateType) {
+        if index < self.inputs.len() { self.inputs[index] = state; }
+    }
+
+    fn set_output(&mut self, index: usize, state: StateType) {
+        if index < self.outputs.len() { self.outputs[index] = state; }
+    }
+
+    fn evaluate(&mut self) -> GateResult {
+        let (a, b) = (self.inputs[0], self.inputs[1]);
+        self.outputs[0] = a.xor(b);
+        self.outputs[1] = a.and(b);
+        GateResult { outputs: self.outputs.clone(), delay: self.delay }
+    }
+
+    fn reset(&mut self) {
+        self.inputs.fill(StateType::Unknown);
+        self.outputs.fill(StateType::Unknown);
+    }
+
+    fn delay(&self) -> u64 { self.delay }
+}
+
+/// Full Adder (inputs: [A, B, Cin], outputs: [Sum, Cout])
+pub struct FullAdderGate {
+    id: String,
+    inputs: Vec<StateType>,
+    outputs: Vec<StateType>,
+    delay: u64,
+}
+
+impl FullAdderGate {
+    pub fn new(id: String, delay: u64) -> Self {
+        Self {
+            id,
+            inputs: vec![StateType::Unknown; 3],
+            outputs: vec![StateType::Unknown; 2],
+            delay,
+        }
+    }
+}
+
+impl Gate for FullAdderGate {
+    fn id(&self) -> &str { &self.id }
+    fn gate_type(&self) -> &str { "FULL_ADDER" }
+    fn input_count(&self) -> usize { 3 }
+    fn output_count(&self) -> usize { 2 }
+    fn get_inputs(&self) -> &[StateType] { &self.inputs }
+    fn get_outputs(&self) -> &[StateType] { &self.outputs }
+
+    fn set_input(&mut self, index: usize, state: StateType) {
+        if index < self.inputs.len() { self.inputs[index] = state; }
+    }
+
+    fn set_output(&mut self, index: usize, state: StateType) {
+        if index < self.outputs.len() { self.outputs[index] = state; }
+    }
+
+    fn evaluate(&mut self) -> GateResult {
+        let (sum, carry_out) = full_add(self.inputs[0], self.inputs[1], self.inputs[2]);
+        self.outputs[0] = sum;
+        self.outputs[1] = carry_out;
+        GateResult { outputs: self.outputs.clone(), delay: self.delay }
+    }
+
+    fn reset(&mut self) {
+        self.inputs.fill(StateType::Unknown);
+        self.outputs.fill(StateType::Unknown);
+    }
+
+    fn delay(&self) -> u64 { self.delay }
+}
+
+/// Full Subtractor (inputs: [A, B, Bin], outputs: [Diff, Bout]) computing A - B - Bin
+pub struct FullSubtractorGate {
+    id: String,
+    inputs: Vec<StateType>,
+    outputs: Vec<StateType>,
+    delay: u64,
+}
+
+impl FullSubtractorGate {
+    pub fn new(id: String, delay: u64) -> Self {
+        Self {
+            id,
+            inputs: vec![StateType::Unknown; 3],
+            outputs: vec![StateType::Unknown; 2],
+            delay,
+        }
+    }
+}
+
+impl Gate for FullSubtractorGate {
+    fn id(&self) -> &str { &self.id }
+    fn gate_type(&self) -> &str { "FULL_SUB" }
+    fn input_count(&self) -> usize { 3 }
+    fn output_count(&self) -> usize { 2 }
+    fn get_inputs(&self) -> &[StateType] { &self.inputs }
+    fn get_outputs(&self) -> &[StateType] { &self.outputs }
+
+    fn set_input(&mut self, index: usize, state: StateType) {
+        if index < self.inputs.len() { self.inputs[index] = state; }
+    }
+
+    fn set_output(&mut self, index: usize, state: StateType) {
+        if index < self.outputs.len() { self.outputs[index] = state; }
+    }
+
+    fn evaluate(&mut self) -> GateResult {
+        let (a, b, borrow_in) = (self.inputs[0], self.inputs[1], self.inputs[2]);
+        let not_a = a.not();
+        self.outputs[0] = a.xor(b).xor(borrow_in);
+        self.outputs[1] = not_a.and(b).or(not_a.and(borrow_in)).or(b.and(borrow_in));
+        GateResult { outputs: self.outputs.clone(), delay: self.delay }
+    }
+
+    fn reset(&mut self) {
+        self.inputs.fill(StateType::Unknown);
+        self.outputs.fill(StateType::Unknown);
+    }
+
+    fn delay(&self) -> u64 { self.delay }
+}
+
+/// Ripple-Carry Adder (inputs: [a0..a(n-1), b0..b(n-1), Cin], outputs: [s0..s(n-1), Cout])
+///
+/// Bit 0 is the least significant; each bit is a chained full adder.
+pub struct RippleCarryAdderGate {
+    id: String,
+    inputs: Vec<StateType>,
+    outputs: Vec<StateType>,
+    width: usize,
+    delay: u64,
+}
+
+impl RippleCarryAdderGate {
+    pub fn new(id: String, width: usize, delay: u64) -> Self {
+        let width = width.max(1);
+        Self {
+            id,
+            inputs: vec![StateType::Unknown; 2 * width + 1],
+            outputs: vec![StateType::Unknown; width + 1],
+            width,
+            delay,
+        }
+    }
+}
+
+impl Gate for RippleCarryAdderGate {
+    fn id(&self) -> &str { &self.id }
+    fn gate_type(&self) -> &str { "ADDER" }
+    fn input_count(&self) -> usize { self.inputs.len() }
+    fn output_count(&self) -> usize { self.outputs.len() }
+    fn get_inputs(&self) -> &[StateType] { &self.inputs }
+    fn get_outputs(&self) -> &[StateType] { &self.outputs }
+
+    fn set_input(&mut self, index: usize, state: StateType) {
+        if index < self.inputs.len() { self.inputs[index] = state; }
+    }
+
+    fn set_output(&mut self, index: usize, state: StateType) {
+        if index < self.outputs.len() { self.outputs[index] = state; }
+    }
+
+    fn evaluate(&mut self) -> GateResult {
+        let mut carry = self.inputs[2 * self.width];
+        for bit in 0..self.width {
+            let (sum, carry_out) = full_add(self.inputs[bit], self.inputs[self.width + bit], carry);
+            self.outputs[bit] = sum;
+            carry = carry_out;
+        }
+        self.outputs[self.width] = carry;
+        GateResult { outputs: self.outputs.clone(), delay: self.delay }
+    }
+
+    fn reset(&mut self) {
+        self.inputs.fill(StateType::Unknown);
+        self.outputs.fill(StateType::Unknown);
+    }
+
+    fn delay(&self) -> u64 { self.delay }
+}
+
+/// Maximum binary input width of a BCD converter
+const MAX_BCD_INPUT_BITS: usize = 32;
+
+/// Convert the low `input_bits` of `value` to packed BCD by double dabble:
+/// shift the bits in MSB first, adding 3 to every digit of 5 or more before
+/// each shift
+fn double_dabble(value: u64, input_bits: usize, digits: usize) -> u64 {
+    let mut bcd: u64 = 0;
+    for i in (0..input_bits).rev() {
+        for digit in 0..digits {
+            if (bcd >> (4 * digit)) & 0xF >= 5 {
+                bcd += 3 << (4 * digit);
+            }
+        }
+        bcd = (bcd << 1) | ((value >> i) & 1);
+    }
+    bcd
+}
+
+/// Binary to BCD converter (inputs: [b0..b(n-1)], outputs: 4 bits per digit)
+///
+/// Outputs are packed BCD, ones digit first and each digit least significant
+/// bit first, with as many digits as the largest n-bit value needs (3 for
+/// 8 inputs). Any non-binary input makes every output Unknown.
+pub struct BcdConvertGate {
+    id: String,
+    inputs: Vec<StateType>,
+    outputs: Vec<StateType>,
+    delay: u64,
+}
+
+impl BcdConvertGate {
+    pub fn new(id: String, input_bits: usize, delay: u64) -> Self {
+        let input_bits = input_bits.clamp(1, MAX_BCD_INPUT_BITS);
+        let digits = ((1u64 << input_bits) - 1).to_string().len();
+        Self {
+            id,
+            inputs: vec![StateType::Unknown; input_bits],
+            outputs: vec![StateType::Unknown; 4 * digits],
+            delay,
+        }
+    }
+}
+
+impl Gate for BcdConvertGate {
+    fn id(&self) -> &str { &self.id }
+    fn gate_type(&self) -> &str { "BCD" }
+    fn input_count(&self) -> usize { self.inputs.len() }
+    fn output_count(&self) -> usize { self.outputs.len() }
+    fn get_inputs(&self) -> &[StateType] { &self.inputs }
+    fn get_outputs(&self) -> &[StateType] { &self.outputs }
+
+    fn set_input(&mut self, index: usize, state: StateType) {
+        if index < self.inputs.len() { self.inputs[index] = state; }
+    }
+
+    fn set_output(&mut self, index: usize, state: StateType) {
+        if index < self.outputs.len() { self.outputs[index] = state; }
+    }
+
+    fn evaluate(&mut self) -> GateResult {
+        match binary_value(&self.inputs) {
+            Some(value) => {
+                let bcd = double_dabble(value as u64, self.inputs.len(), self.outputs.len() / 4);
+                for (i, output) in self.outputs.iter_mut().enumerate() {
+                    *output = if bcd & (1 << i) != 0 { StateType::One } else { StateType::Zero };
+                }
+            }
+            None => self.outputs.fill(StateType::Unknown),
+        }
+        GateResult { outputs: self.outputs.clone(), delay: self.delay }
+    }
+
+    fn reset(&mut self) {
+        self.inputs.fill(StateType::Unknown);
+        self.outputs.fill(StateType::Unknown);
+    }
+
+    fn delay(&self) -> u64 { self.delay }
+}
+
+/// Segment patterns [a, b, c, d, e, f, g] for BCD digits 0-9
+const SEVEN_SEGMENT_DIGITS: [[bool; 7]; 10] = [
+    [true, true, true, true, true, true, false],
+    [false, true, true, false, false, false, false],
+    [true, true, false, true, true, false, true],
+    [true, true, true, true, false, false, true],
+    [false, true, true, false, false, true, true],
+    [true, false, true, true, false, true, true],
+    [true, false, true, true, true, true, true],
+    [true, true, true, false, false, false, false],
+    [true, true, true, true, true, true, true],
+    [true, true, true, true, false, true, true],
+];
+
+/// Segment pattern shown for invalid BCD values (a dash: only `g` lit)
+const SEVEN_SEGMENT_DASH: [bool; 7] = [false, false, false, false, false, false, true];
+
+/// Seven-Segment Display (no outputs)
+///
+/// With 4 inputs, `[b0, b1, b2, b3]` is a BCD digit (b0 least significant);
+/// values above 9 show a dash and unknown bits blank every segment to
+/// Unknown. With 7 inputs, `[a, b, c, d, e, f, g]` drive the segments
+/// directly. Decoded segments are reported through `segments()`.
+pub struct SevenSegmentGate {
+    id: String,
+    inputs: Vec<StateType>,
+}
+
+impl SevenSegmentGate {
+    pub fn new(id: String, input_count: usize) -> Self {
+        let input_count = if input_count == 7 { 7 } else { 4 };
+        Self {
+            id,
+            inputs: vec![StateType::Unknown; input_count],
+        }
+    }
+}
+
+impl Gate for SevenSegmentGate {
+    fn id(&self) -> &str { &self.id }
+    fn gate_type(&self) -> &str { "SEVEN_SEG" }
+    fn input_count(&self) -> usize { self.inputs.len() }
+    fn output_count(&self) -> usize { 0 }
+    fn get_inputs(&self) -> &[StateType] { &self.inputs }
+    fn get_outputs(&self) -> &[StateType] { &[] }
+
+    fn set_input(&mut self, index: usize, state: StateType) {
+        if index < self.inputs.len() { self.inputs[index] = state; }
+    }
+
+    fn evaluate(&mut self) -> GateResult {
+        GateResult { outputs: vec![], delay: 0 }
+    }
+
+    fn reset(&mut self) {
+        self.inputs.fill(StateType::Unknown);
+    }
+
+    fn delay(&self) -> u64 { 0 }
+
+    fn segments(&self) -> Option<Vec<StateType>> {
+        if self.inputs.len() == 7 {
+            return Some(self.inputs.clone());
+        }
+        let segments = match binary_value(&self.inputs) {
+            Some(digit) => {
+                let pattern = SEVEN_SEGMENT_DIGITS.get(digit).unwrap_or(&SEVEN_SEGMENT_DASH);
+                pattern.iter().map(|&lit| if lit { StateType::One } else { StateType::Zero }).collect()
+            }
+            None => vec![StateType::Unknown; 7],
+        };
+        Some(segments)
+    }
+}
+
+/// Magnitude comparator (inputs: [a0..a(n-1), b0..b(n-1)], outputs: [A==B, A>B, A<B])
+///
+/// Bit 0 is the least significant. Any non-binary bit makes all outputs Unknown.
+pub struct ComparatorGate {
+    id: String,
+    inputs: Vec<StateType>,
+    outputs: Vec<StateType>,
+    width: usize,
+    delay: u64,
+}
+
+impl ComparatorGate {
+    pub fn new(id: String, width: usize, delay: u64) -> Self {
+        let width = width.max(1);
+        Self {
+            id,
+            inputs: vec![StateType::Unknown; 2 * width],
+            outputs: vec![StateType::Unknown; 3],
+            width,
+            delay,
+        }
+    }
+}
+
+impl Gate for ComparatorGate {
+    fn id(&self) -> &str { &self.id }
+    fn gate_type(&self) -> &str { "COMPARATOR" }
+    fn input_count(&self) -> usize { self.inputs.len() }
+    fn output_count(&self) -> usize { 3 }
+    fn get_inputs(&self) -> &[StateType] { &self.inputs }
+    fn get_outputs(&self) -> &[StateType] { &self.outputs }
+
+    fn set_input(&mut self, index: usize, state: StateType) {
+        if index < self.inputs.len() { self.inputs[index] = state; }
+    }
+
+    fn set_output(&mut self, index: usize, state: StateType) {
+        if index < self.outputs.len() { self.outputs[index] = state; }
+    }
+
+    fn evaluate(&mut self) -> GateResult {
+        let binary = self.inputs.iter().all(|&s| s == StateType::Zero || s == StateType::One);
+        if !binary {
+            self.outputs.fill(StateType::Unknown);
+        } else {
+            // Compare from the most significant bit down
+            let (a, b) = self.inputs.split_at(self.width);
+            let ordering = a
+                .iter()
+                .rev()
+                .zip(b.iter().rev())
+                .map(|(&a, &b)| (a == StateType::One).cmp(&(b == StateType::One)))
+                .find(|ordering| ordering.is_ne())
+                .unwrap_or(std::cmp::Ordering::Equal);
+            let level = |condition: bool| if condition { StateType::One } else { StateType::Zero };
+            self.outputs[0] = level(ordering.is_eq());
+            self.outputs[1] = level(ordering.is_gt());
+            self.outputs[2] = level(ordering.is_lt());
+        }
+        GateResult { outputs: self.outputs.clone(), delay: self.delay }
+    }
+
+    fn reset(&mut self) {
+        self.inputs.fill(StateType::Unknown);
+        self.outputs.fill(StateType::Unknown);
+    }
+
+    fn delay(&self) -> u64 { self.delay }
+}
+
+/// Maximum width of a MATCH gate's constant pattern
+const MAX_MATCH_BITS: usize = 32;
+
+/// Equality-to-constant detector (inputs: [i0..i(n-1)], outputs: [match])
+///
+/// `init_data` is `[pattern, care_mask]`; bit k of the mask set means input
+/// k must equal bit k of the pattern, clear means don't care (the mask
+/// defaults to all bits). A mismatch on any relevant bit gives Zero even if
+/// other relevant bits are non-binary; otherwise a non-binary relevant bit
+/// gives Unknown.
+pub struct MatchGate {
+    id: String,
+    inputs: Vec<StateType>,
+    outputs: Vec<StateType>,
+    pattern: u32,
+    care_mask: u32,
+    delay: u64,
+}
+
+impl MatchGate {
+    pub fn new(id: String, input_count: usize, pattern: u32, care_mask: u32, delay: u64) -> Self {
+        Self {
+            id,
+            inputs: vec![StateType::Unknown; input_count.clamp(1, MAX_MATCH_BITS)],
+            outputs: vec![StateType::Unknown; 1],
+            pattern,
+            care_mask,
+            delay,
+        }
+    }
+}
+
+impl Gate for MatchGate {
+    fn id(&self) -> &str { &self.id }
+    fn gate_type(&self) -> &str { "MATCH" }
+    fn input_count(&self) -> usize { self.inputs.len() }
+    fn output_count(&self) -> usize { 1 }
+    fn get_inputs(&self) -> &[StateType] { &self.inputs }
+    fn get_outputs(&self) -> &[StateType] { &self.outputs }
+
+    fn set_input(&mut self, index: usize, state: StateType) {
+        if index < self.inputs.len() { self.inputs[index] = state; }
+    }
+
+    fn set_output(&mut self, index: usize, state: StateType) {
+        if index < self.outputs.len() { self.outputs[index] = state; }
+    }
+
+    fn evaluate(&mut self) -> GateResult {
+        let mut undetermined = false;
+        let mut mismatch = false;
+        for (i, &bit) in self.inputs.iter().enumerate() {
+            if self.care_mask & (1 << i) == 0 {
+                continue;
+            }
+            let expected = if self.pattern & (1 << i) != 0 { StateType::One } else { StateType::Zero };
+            match bit {
+                StateType::Zero | StateType::One => mismatch |= bit != expected,
+                _ => undetermined = true,
+            }
+        }
+        self.outputs[0] = if mismatch {
+            StateType::Zero
+        } else if undetermined {
+            StateType::Unknown
+        } else {
+            StateType::One
+        };
+        GateResult { outputs: self.outputs.clone(), delay: self.delay }
+    }
+
+    fn reset(&mut self) {
+        self.inputs.fill(StateType::Unknown);
+        self.outputs.fill(StateType::Unknown);
+    }
+
+    fn delay(&self) -> u64 { self.delay }
+}
+
+/// Maximum width of a RANGE gate's input value
+const MAX_RANGE_BITS: usize = 32;
+
+/// Window detector (inputs: [i0..i(n-1)], outputs: [in_range])
+///
+/// Bit 0 is the least significant. `init_data` is `[lo, hi]` (defaulting to
+/// the full range) and the output is ONE when lo <= value <= hi. Non-binary
+/// bits give Unknown only if the values they could take fall both inside
+/// and outside the window.
+pub struct RangeGate {
+    id: String,
+    inputs: Vec<StateType>,
+    outputs: Vec<StateType>,
+    lo: u32,
+    hi: u32,
+    delay: u64,
+}
+
+impl RangeGate {
+    pub fn new(id: String, input_count: usize, lo: u32, hi: u32, delay: u64) -> Self {
+        Self {
+            id,
+            inputs: vec![StateType::Unknown; input_count.clamp(1, MAX_RANGE_BITS)],
+            outputs: vec![StateType::Unknown; 1],
+            lo,
+            hi,
+            delay,
+        }
+    }
+}
+
+/// Smallest value at or above `lo` whose bits match `known` everywhere
+/// outside the `free` mask (bits below `width`), if any
+fn smallest_at_least(known: u64, free: u64, width: usize, lo: u64) -> Option<u64> {
+    if lo >= 1 << width {
+        return None;
+    }
+    // Best value found by setting a free bit above lo's, taking the lowest
+    // such position reached while still matching lo's prefix
+    let mut candidate = None;
+    let mut prefix = 0u64;
+    for i in (0..width).rev() {
+        let bit = 1u64 << i;
+        let lower_known = known & (bit - 1);
+        let lo_bit = lo & bit;
+        if free & bit != 0 {
+            if lo_bit == 0 {
+                candidate = Some(prefix | bit | lower_known);
+            }
+            prefix |= lo_bit;
+        } else if known & bit > lo_bit {
+            return Some(prefix | bit | lower_known);
+        } else if known & bit < lo_bit {
+            return candidate;
+        } else {
+            prefix |= lo_bit;
+        }
+    }
+    Some(prefix)
+}
+
+impl Gate for RangeGate {
+    fn id(&self) -> &str { &self.id }
+    fn gate_type(&self) -> &str { "RANGE" }
+    fn input_count(&self) -> usize { self.inputs.len() }
+    fn output_count(&self) -> usize { 1 }
+    fn get_inputs(&self) -> &[StateType] { &self.inputs }
+    fn get_outputs(&self) -> &[StateType] { &self.outputs }
+
+    fn set_input(&mut self, index: usize, state: StateType) {
+        if index < self.inputs.len() { self.inputs[index] = state; }
+    }
+
+    fn set_output(&mut self, index: usize, state: StateType) {
+        if index < self.outputs.len() { self.outputs[index] = state; }
+    }
+
+    fn evaluate(&mut self) -> GateResult {
+        let (mut known, mut free) = (0u64, 0u64);
+        for (i, &bit) in self.inputs.iter().enumerate() {
+            match bit {
+                StateType::One => known |= 1 << i,
+                StateType::Zero => {}
+                _ => free |= 1 << i,
+            }
+        }
+        let (lo, hi) = (self.lo as u64, self.hi as u64);
+        let any_inside = smallest_at_least(known, free, self.inputs.len(), lo).is_some_and(|value| value <= hi);
+        let any_outside = known < lo || known | free > hi;
+        self.outputs[0] = match (any_inside, any_outside) {
+            (true, false) => StateType::One,
+            (false, _) => StateType::Zero,
+            (true, true) => StateType::Unknown,
+        };
+        GateResult { outputs: self.outputs.clone(), delay: self.delay }
+    }
+
+    fn reset(&mut self) {
+        self.inputs.fill(StateType::Unknown);
+        self.outputs.fill(StateType::Unknown);
+    }
+
+    fn delay(&self) -> u64 { self.delay }
+}
+
+/// Majority / Voter Gate (N inputs, normally odd)
+///
+/// Outputs ONE when more than half the inputs are ONE. Unknown inputs only
+/// produce Unknown when they could swing the vote either way; a tie (even N)
+/// is not a majority.
+pub struct MajorityGate {
+    id: String,
+    inputs: Vec<StateType>,
+    outputs: Vec<StateType>,
+    delay: u64,
+}
+
+impl MajorityGate {
+    pub fn new(id: String, input_count: usize, delay: u64) -> Self {
+        Self {
+            id,
+            inputs: vec![StateType::Unknown; input_count.max(1)],
+            outputs: vec![StateType::Unknown; 1],
+            delay,
+        }
+    }
+}
+
+impl Gate for MajorityGate {
+    fn id(&self) -> &str { &self.id }
+    fn gate_type(&self) -> &str { "MAJORITY" }
+    fn input_count(&self) -> usize { self.inputs.len() }
+    fn output_count(&self) -> usize { 1 }
+    fn get_inputs(&self) -> &[StateType] { &self.inputs }
+    fn get_outputs(&self) -> &[StateType] { &self.outputs }
+
+    fn set_input(&mut self, index: usize, state: StateType) {
+        if index < self.inputs.len() { self.inputs[index] = state; }
+    }
+
+    fn set_output(&mut self, index: usize, state: StateType) {
+        if index < self.outputs.len() { self.outputs[index] = state; }
+    }
+
+    fn evaluate(&mut self) -> GateResult {
+        let total = self.inputs.len();
+        let ones = self.inputs.iter().filter(|&&s| s == StateType::One).count();
+        let zeros = self.inputs.iter().filter(|&&s| s == StateType::Zero).count();
+        self.outputs[0] = if ones * 2 > total {
+            StateType::One
+        } else if (total - zeros) * 2 <= total {
+            StateType::Zero
+        } else {
+            StateType::Unknown
+        };
+        GateResult { outputs: self.outputs.clone(), delay: self.delay }
+    }
+
+    fn reset(&mut self) {
+        self.inputs.fill(StateType::Unknown);
+        self.outputs.fill(StateType::Unknown);
+    }
+
+    fn delay(&self) -> u64 { self.delay }
+}
+
+/// Explicit bus node resolving N tri-state drivers into one net value
+pub struct BusNodeGate {
+    id: String,
+    inputs: Vec<StateType>,
+    outputs: Vec<StateType>,
+    delay: u64,
+}
+
+impl BusNodeGate {
+    pub fn new(id: String, input_count: usize, delay: u64) -> Self {
+        Self {
+            id,
+            inputs: vec![StateType::HiZ; input_count.max(1)],
+            outputs: vec![StateType::HiZ; 1],
+            delay,
+        }
+    }
+}
+
+impl Gate for BusNodeGate {
+    fn id(&self) -> &str { &self.id }
+    fn gate_type(&self) -> &str { "BUS" }
+    fn input_count(&self) -> usize { self.inputs.len() }
+    fn output_count(&self) -> usize { 1 }
+    fn get_inputs(&self) -> &[StateType] { &self.inputs }
+    fn get_outputs(&self) -> &[StateType] { &self.outputs }
+
+    fn set_input(&mut self, index: usize, state: StateType) {
+        if index < self.inputs.len() { self.inputs[index] = state; }
+    }
+
+    fn set_output(&mut self, index: usize, state: StateType) {
+        if index < self.outputs.len() { self.outputs[index] = state; }
+    }
+
+    fn evaluate(&mut self) -> GateResult {
+        self.outputs[0] = resolve_wire_state(&self.inputs);
+        GateResult { outputs: self.outputs.clone(), delay: self.delay }
+    }
+
+    fn reset(&mut self) {
+        self.inputs.fill(StateType::HiZ);
+        self.outputs.fill(StateType::HiZ);
+    }
+
+    fn delay(&self) -> u64 { self.delay }
+
+    fn passes_high_impedance(&self) -> bool { true }
+}
+
+/// N-bit register (inputs: [d0..dn-1, clock, enable, reset], outputs: [q0..qn-1])
+///
+/// On the rising edge of `clock` with `enable` high all data bits latch
+/// together. `reset` high clears every bit asynchronously.
+pub struct RegisterGate {
+    id: String,
+    width: usize,
+    inputs: Vec<StateType>,
+    previous_inputs: Vec<StateType>,
+    outputs: Vec<StateType>,
+    delay: u64,
+}
+
+impl RegisterGate {
+    pub fn new(id: String, width: usize, delay: u64) -> Self {
+        let width = width.max(1);
+        Self {
+            id,
+            width,
+            inputs: vec![StateType::Unknown; width + 3],
+            previous_inputs: vec![StateType::Unknown; width + 3],
+            outputs: vec![StateType::Zero; width],
+            delay,
+        }
+    }
+}
+
+impl Gate for RegisterGate {
+    fn id(&self) -> &str { &self.id }
+    fn gate_type(&self) -> &str { "REGISTER" }
+    fn input_count(&self) -> usize { self.inputs.len() }
+    fn output_count(&self) -> usize { self.width }
+    fn get_inputs(&self) -> &[StateType] { &self.inputs }
+    fn get_outputs(&self) -> &[StateType] { &self.outputs }
+
+    fn set_input(&mut self, index: usize, state: StateType) {
+        if index < self.inputs.len() { self.inputs[index] = state; }
+    }
+
+    fn set_output(&mut self, index: usize, state: StateType) {
+        if index < self.outputs.len() { self.outputs[index] = state; }
+    }
+
+    fn evaluate(&mut self) -> GateResult {
+        let clock = self.width;
+        let enable = self.inputs[self.width + 1];
+        let reset = self.inputs[self.width + 2];
+
+        if reset == StateType::One {
+            self.outputs.fill(StateType::Zero);
+        } else if self.is_rising_edge(clock) && enable == StateType::One {
+            self.outputs.copy_from_slice(&self.inputs[..self.width]);
+        }
+        self.update_previous_inputs();
+        GateResult { outputs: self.outputs.clone(), delay: self.delay }
+    }
+
+    fn reset(&mut self) {
+        self.inputs.fill(StateType::Unknown);
+        self.previous_inputs.fill(StateType::Unknown);
+        self.outputs.fill(StateType::Zero);
+    }
+
+    fn delay(&self) -> u64 { self.delay }
+    fn is_sequential(&self) -> bool { true }
+
+    fn save_state(&self) -> Vec<u64> {
+        self.previous_inputs.iter().map(|s| s.to_u8() as u64).collect()
+    }
+
+    fn load_state(&mut self, state: &[u64]) {
+        for (previous, &value) in self.previous_inputs.iter_mut().zip(state) {
+            *previous = StateType::from_u8(value as u8);
+        }
+    }
+
+    fn is_rising_edge(&self, index: usize) -> bool {
+        self.previous_inputs.get(index) == Some(&StateType::Zero)
+            && self.inputs.get(index) == Some(&StateType::One)
+    }
+
+    fn update_previous_inputs(&mut self) {
+        self.previous_inputs.copy_from_slice(&self.inputs);
+    }
+}
+
+/// Binary counter (inputs: [clock, reset, enable], outputs: [q0..q(n-1)])
+///
+/// Increments on each rising edge of `clock` while `enable` is high, wrapping
+/// at `modulus` (2^n by default). `reset` high clears the count asynchronously.
+pub struct CounterGate {
+    id: String,
+    inputs: Vec<StateType>,
+    previous_inputs: Vec<StateType>,
+    outputs: Vec<StateType>,
+    count: u64,
+    modulus: u64,
+    delay: u64,
+}
+
+impl CounterGate {
+    pub fn new(id: String, width: usize, modulus: Option<u64>, delay: u64) -> Self {
+        let width = width.clamp(1, 63);
+        let modulus = modulus.unwrap_or(1 << width).clamp(1, 1 << width);
+        Self {
+            id,
+            inputs: vec![StateType::Unknown; 3],
+            previous_inputs: vec![StateType::Unknown; 3],
+            outputs: vec![StateType::Zero; width],
+            count: 0,
+            modulus,
+            delay,
+        }
+    }
+
+    fn write_count(&mut self) {
+        for (i, output) in self.outputs.iter_mut().enumerate() {
+            *output = if self.count & (1 << i) != 0 { StateType::One } else { StateType::Zero };
+        }
+    }
+}
+
+impl Gate for CounterGate {
+    fn id(&self) -> &str { &self.id }
+    fn gate_type(&self) -> &str { "COUNTER" }
+    fn input_count(&self) -> usize { 3 }
+    fn output_count(&self) -> usize { self.outputs.len() }
+    fn get_inputs(&self) -> &[StateType] { &self.inputs }
+    fn get_outputs(&self) -> &[StateType] { &self.outputs }
+
+    fn set_input(&mut self, index: usize, state: StateType) {
+        if index < self.inputs.len() { self.inputs[index] = state; }
+    }
+
+    fn set_output(&mut self, index: usize, state: StateType) {
+        if index < self.outputs.len() { self.outputs[index] = state; }
+    }
+
+    fn evaluate(&mut self) -> GateResult {
+        if self.inputs[1] == StateType::One {
+            self.count = 0;
+        } else if self.is_rising_edge(0) && self.inputs[2] == StateType::One {
+            self.count = (self.count + 1) % self.modulus;
+        }
+        self.write_count();
+        self.update_previous_inputs();
+        GateResult { outputs: self.outputs.clone(), delay: self.delay }
+    }
+
+    fn reset(&mut self) {
+        self.inputs.fill(StateType::Unknown);
+        self.previous_inputs.fill(StateType::Unknown);
+        self.count = 0;
+        self.write_count();
+    }
+
+    fn delay(&self) -> u64 { self.delay }
+    fn is_sequential(&self) -> bool { true }
+
+    fn save_state(&self) -> Vec<u64> {
+        let mut state = vec![self.count];
+        state.extend(self.previous_inputs.iter().map(|s| s.to_u8() as u64));
+        state
+    }
+
+    fn load_state(&mut self, state: &[u64]) {
+        if let Some((&count, previous)) = state.split_first() {
+            self.count = count % self.modulus;
+            for (previous_input, &value) in self.previous_inputs.iter_mut().zip(previous) {
+                *previous_input = StateType::from_u8(value as u8);
+            }
+        }
+    }
+
+    fn is_rising_edge(&self, index: usize) -> bool {
+        self.previous_inputs.get(index) == Some(&StateType::Zero)
+            && self.inputs.get(index) == Some(&StateType::One)
+    }
+
+    fn update_previous_inputs(&mut self) {
+        self.previous_inputs.copy_from_slice(&self.inputs);
+    }
+}
+
+/// Frequency divider (inputs: [clock, reset], outputs: [out])
+///
+/// The output toggles on every `divisor`-th rising edge of `clock`, giving a
+/// clock divided by 2 * `divisor`. `reset` high clears the edge count and
+/// drives the output low asynchronously.
+pub struct DividerGate {
+    id: String,
+    inputs: Vec<StateType>,
+    previous_inputs: Vec<StateType>,
+    outputs: Vec<StateType>,
+    divisor: u64,
+    /// Rising edges since the output last toggled
+    edges: u64,
+    delay: u64,
+}
+
+impl DividerGate {
+    pub fn new(id: String, divisor: u64, delay: u64) -> Self {
+        Self {
+            id,
+            inputs: vec![StateType::Unknown; 2],
+            previous_inputs: vec![StateType::Unknown; 2],
+            outputs: vec![StateType::Zero; 1],
+            divisor: divisor.max(1),
+            edges: 0,
+            delay,
+        }
+    }
+}
+
+impl Gate for DividerGate {
+    fn id(&self) -> &str { &self.id }
+    fn gate_type(&self) -> &str { "DIVIDER" }
+    fn input_count(&self) -> usize { 2 }
+    fn output_count(&self) -> usize { 1 }
+    fn get_inputs(&self) -> &[StateType] { &self.inputs }
+    fn get_outputs(&self) -> &[StateType] { &self.outputs }
+
+    fn set_input(&mut self, index: usize, state: StateType) {
+        if index < self.inputs.len() { self.inputs[index] = state; }
+    }
+
+    fn set_output(&mut self, index: usize, state: StateType) {
+        if index < self.outputs.len() { self.outputs[index] = state; }
+    }
+
+    fn evaluate(&mut self) -> GateResult {
+        if self.inputs[1] == StateType::One {
+            self.edges = 0;
+            self.outputs[0] = StateType::Zero;
+        } else if self.is_rising_edge(0) {
+            self.edges += 1;
+            if self.edges == self.divisor {
+                self.edges = 0;
+                self.outputs[0] = self.outputs[0].not();
+            }
+        }
+        self.update_previous_inputs();
+        GateResult { outputs: self.outputs.clone(), delay: self.delay }
+    }
+
+    fn reset(&mut self) {
+        self.inputs.fill(StateType::Unknown);
+        self.previous_inputs.fill(StateType::Unknown);
+        self.outputs.fill(StateType::Zero);
+        self.edges = 0;
+    }
+
+    fn delay(&self) -> u64 { self.delay }
+    fn is_sequential(&self) -> bool { true }
+
+    fn save_state(&self) -> Vec<u64> {
+        let mut state = vec![self.edges];
+        state.extend(self.previous_inputs.iter().map(|s| s.to_u8() as u64));
+        state
+    }
+
+    fn load_state(&mut self, state: &[u64]) {
+        if let Some((&edges, previous)) = state.split_first() {
+            self.edges = edges % self.divisor;
+            for (previous_input, &value) in self.previous_inputs.iter_mut().zip(previous) {
+                *previous_input = StateType::from_u8(value as u8);
+            }
+        }
+    }
+
+    fn is_rising_edge(&self, index: usize) -> bool {
+        self.previous_inputs.get(index) == Some(&StateType::Zero)
+            && self.inputs.get(index) == Some(&StateType::One)
+    }
+
+    fn update_previous_inputs(&mut self) {
+        self.previous_inputs.copy_from_slice(&self.inputs);
+    }
+}
+
+/// Serial-in shift register (inputs: [serial_in, clock, reset],
+/// outputs: [q0..q(n-1), serial_out])
+///
+/// Each rising edge of `clock` shifts every stage one place toward
+/// `serial_out` and loads `serial_in` into `q0`; `serial_out` mirrors the last
+/// stage. `reset` high clears all stages asynchronously.
+pub struct ShiftRegisterGate {
+    id: String,
+    inputs: Vec<StateType>,
+    previous_inputs: Vec<StateType>,
+    outputs: Vec<StateType>,
+    delay: u64,
+}
+
+impl ShiftRegisterGate {
+    pub fn new(id: String, width: usize, delay: u64) -> Self {
+        let width = width.clamp(1, MAX_DATA_BITS);
+        Self {
+            id,
+            inputs: vec![StateType::Unknown; 3],
+            previous_inputs: vec![StateType::Unknown; 3],
+            outputs: vec![StateType::Zero; width + 1],
+            delay,
+        }
+    }
+
+    fn width(&self) -> usize {
+        self.outputs.len() - 1
+    }
+}
+
+impl Gate for ShiftRegisterGate {
+    fn id(&self) -> &str { &self.id }
+    fn gate_type(&self) -> &str { "SHIFT_REG" }
+    fn input_count(&self) -> usize { 3 }
+    fn output_count(&self) -> usize { self.outputs.len() }
+    fn get_inputs(&self) -> &[StateType] { &self.inputs }
+    fn get_outputs(&self) -> &[StateType] { &self.outputs }
+
+    fn set_input(&mut self, index: usize, state: StateType) {
+        if index < self.inputs.len() { self.inputs[index] = state; }
+    }
+
+    fn set_output(&mut self, index: usize, state: StateType) {
+        if index < self.outputs.len() { self.outputs[index] = state; }
+    }
+
+    fn evaluate(&mut self) -> GateResult {
+        let width = self.width();
+        if self.inputs[2] == StateType::One {
+            self.outputs[..width].fill(StateType::Zero);
+        } else if self.is_rising_edge(1) {
+            self.outputs.copy_within(0..width - 1, 1);
+            self.outputs[0] = self.inputs[0];
+        }
+        self.outputs[width] = self.outputs[width - 1];
+        self.update_previous_inputs();
+        GateResult { outputs: self.outputs.clone(), delay: self.delay }
+    }
+
+    fn reset(&mut self) {
+        self.inputs.fill(StateType::Unknown);
+        self.previous_inputs.fill(StateType::Unknown);
+        self.outputs.fill(StateType::Zero);
+    }
+
+    fn delay(&self) -> u64 { self.delay }
+    fn is_sequential(&self) -> bool { true }
+
+    fn save_state(&self) -> Vec<u64> {
+        self.previous_inputs
+            .iter()
+            .chain(&self.outputs)
+            .map(|s| s.to_u8() as u64)
+            .collect()
+    }
+
+    fn load_state(&mut self, state: &[u64]) {
+        let (previous, stages) = state.split_at(state.len().min(self.previous_inputs.len()));
+        for (previous_input, &value) in self.previous_inputs.iter_mut().zip(previous) {
+            *previous_input = StateType::from_u8(value as u8);
+        }
+        for (output, &value) in self.outputs.iter_mut().zip(stages) {
+            *output = StateType::from_u8(value as u8);
+        }
+    }
+
+    fn is_rising_edge(&self, index: usize) -> bool {
+        self.previous_inputs.get(index) == Some(&StateType::Zero)
+            && self.inputs.get(index) == Some(&StateType::One)
+    }
+
+    fn update_previous_inputs(&mut self) {
+        self.previous_inputs.copy_from_slice(&self.inputs);
+    }
+}
+
+/// Maximum address width of memory gates (64K words)
+const MAX_ADDRESS_BITS: usize = 16;
+
+/// Maximum word width of memory gates
+const MAX_DATA_BITS: usize = 32;
+
+/// Drive `outputs` with the bits of `word`, least significant first
+fn write_word(outputs: &mut [StateType], word: u32) {
+    for (i, output) in outputs.iter_mut().enumerate() {
+        *output = if word & (1 << i) != 0 { StateType::One } else { StateType::Zero };
+    }
+}
+
+/// Read-only memory (inputs: [a0..a(n-1)], outputs: [d0..d(m-1)])
+///
+/// Contents come from `init_data`, one word per address; addresses past the
+/// end read as zero. A non-binary address bit makes every output Unknown.
+pub struct RomGate {
+    id: String,
+    inputs: Vec<StateType>,
+    outputs: Vec<StateType>,
+    contents: Vec<u32>,
+    delay: u64,
+}
+
+impl RomGate {
+    pub fn new(id: String, addr_bits: usize, data_bits: usize, contents: Vec<u32>, delay: u64) -> Self {
+        Self {
+            id,
+            inputs: vec![StateType::Unknown; addr_bits.clamp(1, MAX_ADDRESS_BITS)],
+            outputs: vec![StateType::Unknown; data_bits.clamp(1, MAX_DATA_BITS)],
+            contents,
+            delay,
+        }
+    }
+}
+
+impl Gate for RomGate {
+    fn id(&self) -> &str { &self.id }
+    fn gate_type(&self) -> &str { "ROM" }
+    fn input_count(&self) -> usize { self.inputs.len() }
+    fn output_count(&self) -> usize { self.outputs.len() }
+    fn get_inputs(&self) -> &[StateType] { &self.inputs }
+    fn get_outputs(&self) -> &[StateType] { &self.outputs }
+
+    fn set_input(&mut self, index: usize, state: StateType) {
+        if index < self.inputs.len() { self.inputs[index] = state; }
+    }
+
+    fn set_output(&mut self, index: usize, state: StateType) {
+        if index < self.outputs.len() { self.outputs[index] = state; }
+    }
+
+    fn evaluate(&mut self) -> GateResult {
+        match binary_value(&self.inputs) {
+            Some(address) => {
+                let word = self.contents.get(address).copied().unwrap_or(0);
+                write_word(&mut self.outputs, word);
+            }
+            None => self.outputs.fill(StateType::Unknown),
+        }
+        GateResult { outputs: self.outputs.clone(), delay: self.delay }
+    }
+
+    fn reset(&mut self) {
+        self.inputs.fill(StateType::Unknown);
+        self.outputs.fill(StateType::Unknown);
+    }
+
+    fn delay(&self) -> u64 { self.delay }
+}
+
+/// Lookup table logic function (inputs: [i0..i(n-1)], outputs: [y0..y(m-1)])
+///
+/// `init_data` is the truth table, one entry per input combination indexed
+/// by the inputs' binary value; bit k of an entry is output k (a single
+/// output LUT uses entries of 0 and 1). Missing entries read as zero. With
+/// non-binary inputs every matching row is checked: an output is driven if
+/// all of them agree and Unknown otherwise.
+pub struct LookupGate {
+    id: String,
+    inputs: Vec<StateType>,
+    outputs: Vec<StateType>,
+    table: Vec<u32>,
+    delay: u64,
+}
+
+impl LookupGate {
+    pub fn new(id: String, input_count: usize, output_count: usize, table: Vec<u32>, delay: u64) -> Self {
+        Self {
+            id,
+            inputs: vec![StateType::Unknown; input_count.clamp(1, MAX_ADDRESS_BITS)],
+            outputs: vec![StateType::Unknown; output_count.clamp(1, MAX_DATA_BITS)],
+            table,
+            delay,
+        }
+    }
+}
+
+impl Gate for LookupGate {
+    fn id(&self) -> &str { &self.id }
+    fn gate_type(&self) -> &str { "LUT" }
+    fn input_count(&self) -> usize { self.inputs.len() }
+    fn output_count(&self) -> usize { self.outputs.len() }
+    fn get_inputs(&self) -> &[StateType] { &self.inputs }
+    fn get_outputs(&self) -> &[StateType] { &self.outputs }
+
+    fn set_input(&mut self, index: usize, state: StateType) {
+        if index < self.inputs.len() { self.inputs[index] = state; }
+    }
+
+    fn set_output(&mut self, index: usize, state: StateType) {
+        if index < self.outputs.len() { self.outputs[index] = state; }
+    }
+
+    fn evaluate(&mut self) -> GateResult {
+        let mut known = 0usize;
+        let mut unknown = 0usize;
+        for (i, &bit) in self.inputs.iter().enumerate() {
+            match bit {
+                StateType::One => known |= 1 << i,
+                StateType::Zero => {}
+                _ => unknown |= 1 << i,
+            }
+        }
+
+        // OR of every matching row's entry, and of its complement
+        let (mut ones, mut zeros) = (0u32, 0u32);
+        let mut subset = unknown;
+        loop {
+            let entry = self.table.get(known | subset).copied().unwrap_or(0);
+            ones |= entry;
+            zeros |= !entry;
+            if subset == 0 {
+                break;
+            }
+            subset = (subset - 1) & unknown;
+        }
+
+        for (i, output) in self.outputs.iter_mut().enumerate() {
+            *output = match (ones & (1 << i) != 0, zeros & (1 << i) != 0) {
+                (true, false) => StateType::One,
+                (false, true) => StateType::Zero,
+                _ => StateType::Unknown,
+            };
+        }
+        GateResult { outputs: self.outputs.clone(), delay: self.delay }
+    }
+
+    fn reset(&mut self) {
+        self.inputs.fill(StateType::Unknown);
+        self.outputs.fill(StateType::Unknown);
+    }
+
+    fn delay(&self) -> u64 { self.delay }
+}
+
+/// Random-access memory (inputs: [a0..a(n-1), d0..d(m-1), write_enable, clock],
+/// outputs: [q0..q(m-1)])
+///
+/// On the rising edge of `clock` with `write_enable` high the data word is
+/// stored at the address; writes with a non-binary address or data are
+/// ignored. The outputs always show the addressed word (Unknown for a
+/// non-binary address).
+pub struct RamGate {
+    id: String,
+    addr_bits: usize,
+    inputs: Vec<StateType>,
+    previous_inputs: Vec<StateType>,
+    outputs: Vec<StateType>,
+    memory: Vec<u32>,
+    delay: u64,
+}
+
+impl RamGate {
+    pub fn new(id: String, addr_bits: usize, data_bits: usize, delay: u64) -> Self {
+        let addr_bits = addr_bits.clamp(1, MAX_ADDRESS_BITS);
+        let data_bits = data_bits.clamp(1, MAX_DATA_BITS);
+        Self {
+            id,
+            addr_bits,
+            inputs: vec![StateType::Unknown; addr_bits + data_bits + 2],
+            previous_inputs: vec![StateType::Unknown; addr_bits + data_bits + 2],
+            outputs: vec![StateType::Unknown; data_bits],
+            memory: vec![0; 1 << addr_bits],
+            delay,
+        }
+    }
+
+    fn data_bits(&self) -> usize {
+        self.outputs.len()
+    }
+}
+
+impl Gate for RamGate {
+    fn id(&self) -> &str { &self.id }
+    fn gate_type(&self) -> &str { "RAM" }
+    fn input_count(&self) -> usize { self.inputs.len() }
+    fn output_count(&self) -> usize { self.outputs.len() }
+    fn get_inputs(&self) -> &[StateType] { &self.inputs }
+    fn get_outputs(&self) -> &[StateType] { &self.outputs }
+
+    fn set_input(&mut self, index: usize, state: StateType) {
+        if index < self.inputs.len() { self.inputs[index] = state; }
+    }
+
+    fn set_output(&mut self, index: usize, state: StateType) {
+        if index < self.outputs.len() { self.outputs[index] = state; }
+    }
+
+    fn evaluate(&mut self) -> GateResult {
+        let data_start = self.addr_bits;
+        let write_enable = data_start + self.data_bits();
+        let clock = write_enable + 1;
+        let address = binary_value(&self.inputs[..data_start]);
+
+        if self.is_rising_edge(clock) && self.inputs[write_enable] == StateType::One {
+            if let (Some(address), Some(data)) = (address, binary_value(&self.inputs[data_start..write_enable])) {
+                self.memory[address] = data as u32;
+            }
+        }
+
+        match address {
+            Some(address) => write_word(&mut self.outputs, self.memory[address]),
+            None => self.outputs.fill(StateType::Unknown),
+        }
+        self.update_previous_inputs();
+        GateResult { outputs: self.outputs.clone(), delay: self.delay }
+    }
+
+    fn reset(&mut self) {
+        self.reset_mode(ResetMode::Full);
+    }
+
+    fn reset_mode(&mut self, mode: ResetMode) {
+        self.inputs.fill(StateType::Unknown);
+        self.previous_inputs.fill(StateType::Unknown);
+        self.outputs.fill(StateType::Unknown);
+        if mode == ResetMode::Full {
+            self.memory.fill(0);
+        }
+    }
+
+    fn delay(&self) -> u64 { self.delay }
+    fn is_sequential(&self) -> bool { true }
+
+    fn save_state(&self) -> Vec<u64> {
+        self.previous_inputs
+            .iter()
+            .map(|s| s.to_u8() as u64)
+            .chain(self.memory.iter().map(|&word| word as u64))
+            .collect()
+    }
+
+    fn load_state(&mut self, state: &[u64]) {
+        let (previous, memory) = state.split_at(state.len().min(self.previous_inputs.len()));
+        for (previous_input, &value) in self.previous_inputs.iter_mut().zip(previous) {
+            *previous_input = StateType::from_u8(value as u8);
+        }
+        for (word, &value) in self.memory.iter_mut().zip(memory) {
+            *word = value as u32;
+        }
+    }
+
+    fn is_rising_edge(&self, index: usize) -> bool {
+        self.previous_inputs.get(index) == Some(&StateType::Zero)
+            && self.inputs.get(index) == Some(&StateType::One)
+    }
+
+    fn update_previous_inputs(&mut self) {
+        self.previous_inputs.copy_from_slice(&self.inputs);
+    }
+}
+
+/// Inversion bubbles on selected inputs of any gate
+///
+/// States arriving on inverted ports are negated before they reach the
+/// wrapped gate, so the stored inputs are what the gate logic sees.
+pub struct InvertedInputsGate {
+    inner: Box<dyn Gate>,
+    inverted: Vec<bool>,
+}
+
+impl InvertedInputsGate {
+    pub fn new(inner: Box<dyn Gate>, mut inverted: Vec<bool>) -> Self {
+        inverted.resize(inner.input_count(), false);
+        Self { inner, inverted }
+    }
+}
+
+impl Gate for InvertedInputsGate {
+    fn id(&self) -> &str { self.inner.id() }
+    fn gate_type(&self) -> &str { self.inner.gate_type() }
+    fn input_count(&self) -> usize { self.inner.input_count() }
+    fn output_count(&self) -> usize { self.inner.output_count() }
+    fn get_inputs(&self) -> &[StateType] { self.inner.get_inputs() }
+    fn get_outputs(&self) -> &[StateType] { self.inner.get_outputs() }
+
+    fn set_input(&mut self, index: usize, state: StateType) {
+        let state = if self.inverted.get(index).copied().unwrap_or(false) { state.not() } else { state };
+        self.inner.set_input(index, state);
+    }
+
+    fn set_output(&mut self, index: usize, state: StateType) { self.inner.set_output(index, state); }
+    fn evaluate(&mut self) -> GateResult { self.inner.evaluate() }
+    fn reset(&mut self) { self.inner.reset(); }
+    fn reset_mode(&mut self, mode: ResetMode) { self.inner.reset_mode(mode); }
+    fn delay(&self) -> u64 { self.inner.delay() }
+    fn rise_delay(&self) -> u64 { self.inner.rise_delay() }
+    fn fall_delay(&self) -> u64 { self.inner.fall_delay() }
+    fn corner_delay(&self, corner: DelayCorner) -> Option<u64> { self.inner.corner_delay(corner) }
+    fn is_rising_edge(&self, index: usize) -> bool { self.inner.is_rising_edge(index) }
+    fn is_sequential(&self) -> bool { self.inner.is_sequential() }
+    fn update_previous_inputs(&mut self) { self.inner.update_previous_inputs(); }
+    fn toggle(&mut self) { self.inner.toggle(); }
+    fn set_level(&mut self, state: StateType) { self.inner.set_level(state); }
+    fn tick(&mut self, time: u64) { self.inner.tick(time); }
+    fn next_wakeup(&self, time: u64) -> Option<u64> { self.inner.next_wakeup(time) }
+    fn set_period(&mut self, period: u64) { self.inner.set_period(period); }
+    fn set_phase(&mut self, offset: u64) { self.inner.set_phase(offset); }
+    fn fire_pulse(&mut self, end_time: u64) { self.inner.fire_pulse(end_time); }
+    fn segments(&self) -> Option<Vec<StateType>> { self.inner.segments() }
+    fn save_state(&self) -> Vec<u64> { self.inner.save_state() }
+    fn load_state(&mut self, state: &[u64]) { self.inner.load_state(state); }
+    fn passes_high_impedance(&self) -> bool { self.inner.passes_high_impedance() }
+    fn inverted_inputs(&self) -> Option<&[bool]> { Some(&self.inverted) }
+    fn inout_input(&self, output: usize) -> Option<usize> { self.inner.inout_input(output) }
+    fn set_unknown_policy(&mut self, policy: UnknownPolicy) { self.inner.set_unknown_policy(policy); }
+}
+
+/// Delays that override a gate's own `delay`, per output direction or
+/// per delay corner
+///
+/// Unset edge delays keep the gate's own delay; unset corner delays fall
+/// back to the typical (edge) delays.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct GateTiming {
+    /// Delay for outputs rising to ONE
+    pub rise_delay: Option<u64>,
+    /// Delay for outputs falling to ZERO
+    pub fall_delay: Option<u64>,
+    /// Delay for every output at the fast corner
+    pub min_delay: Option<u64>,
+    /// Delay for every output at the slow corner
+    pub max_delay: Option<u64>,
+}
+
+impl GateTiming {
+    fn is_default(&self) -> bool {
+        *self == GateTiming::default()
+    }
+}
+
+/// Timing overrides (see `GateTiming`) for any gate
+pub struct TimingGate {
+    inner: Box<dyn Gate>,
+    timing: GateTiming,
+}
+
+impl TimingGate {
+    pub fn new(inner: Box<dyn Gate>, timing: GateTiming) -> Self {
+        Self { inner, timing }
+    }
+}
+
+impl Gate for TimingGate {
+    fn id(&self) -> &str { self.inner.id() }
+    fn gate_type(&self) -> &str { self.inner.gate_type() }
+    fn input_count(&self) -> usize { self.inner.input_count() }
+    fn output_count(&self) -> usize { self.inner.output_count() }
+    fn get_inputs(&self) -> &[StateType] { self.inner.get_inputs() }
+    fn get_outputs(&self) -> &[StateType] { self.inner.get_outputs() }
+    fn set_input(&mut self, index: usize, state: StateType) { self.inner.set_input(index, state); }
+    fn set_output(&mut self, index: usize, state: StateType) { self.inner.set_output(index, state); }
+    fn evaluate(&mut self) -> GateResult { self.inner.evaluate() }
+    fn reset(&mut self) { self.inner.reset(); }
+    fn reset_mode(&mut self, mode: ResetMode) { self.inner.reset_mode(mode); }
+    fn delay(&self) -> u64 { self.inner.delay() }
+    fn rise_delay(&self) -> u64 { self.timing.rise_delay.unwrap_or_else(|| self.inner.rise_delay()) }
+    fn fall_delay(&self) -> u64 { self.timing.fall_delay.unwrap_or_else(|| self.inner.fall_delay()) }
+
+    fn corner_delay(&self, corner: DelayCorner) -> Option<u64> {
+        match corner {
+            DelayCorner::Typical => None,
+            DelayCorner::Min => self.timing.min_delay,
+            DelayCorner::Max => self.timing.max_delay,
+        }
+    }
+
+    fn is_rising_edge(&self, index: usize) -> bool { self.inner.is_rising_edge(index) }
+    fn is_sequential(&self) -> bool { self.inner.is_sequential() }
+    fn update_previous_inputs(&mut self) { self.inner.update_previous_inputs(); }
+    fn toggle(&mut self) { self.inner.toggle(); }
+    fn set_level(&mut self, state: StateType) { self.inner.set_level(state); }
+    fn tick(&mut self, time: u64) { self.inner.tick(time); }
+    fn next_wakeup(&self, time: u64) -> Option<u64> { self.inner.next_wakeup(time) }
+    fn set_period(&mut self, period: u64) { self.inner.set_period(period); }
+    fn set_phase(&mut self, offset: u64) { self.inner.set_phase(offset); }
+    fn fire_pulse(&mut self, end_time: u64) { self.inner.fire_pulse(end_time); }
+    fn segments(&self) -> Option<Vec<StateType>> { self.inner.segments() }
+    fn save_state(&self) -> Vec<u64> { self.inner.save_state() }
+    fn load_state(&mut self, state: &[u64]) { self.inner.load_state(state); }
+    fn passes_high_impedance(&self) -> bool { self.inner.passes_high_impedance() }
+    fn inverted_inputs(&self) -> Option<&[bool]> { self.inner.inverted_inputs() }
+    fn inout_input(&self, output: usize) -> Option<usize> { self.inner.inout_input(output) }
+    fn set_unknown_policy(&mut self, policy: UnknownPolicy) { self.inner.set_unknown_policy(policy); }
+}
+
+/// Optional construction parameters for `create_gate`
+///
+/// Unset fields fall back to per-type defaults.
+#[derive(Clone, Debug, Default)]
+pub struct GateParams {
+    pub input_count: Option<usize>,
+    pub output_count: Option<usize>,
+    pub delay: Option<u64>,
+    /// Delay for outputs rising to ONE (defaults to `delay`)
+    pub rise_delay: Option<u64>,
+    /// Delay for outputs falling to ZERO (defaults to `delay`)
+    pub fall_delay: Option<u64>,
+    /// Delay at the fast corner (defaults to the typical delays)
+    pub min_delay: Option<u64>,
+    /// Delay at the slow corner (defaults to the typical delays)
+    pub max_delay: Option<u64>,
+    /// Per-input inversion bubbles
+    pub inverted_inputs: Option<Vec<bool>>,
+    /// Count modulus (COUNTER)
+    pub modulus: Option<u64>,
+    /// Initial contents (ROM words) or configuration (RANGE, DIVIDER,
+    /// SCHMITT, DEBOUNCE)
+    pub init_data: Option<Vec<u32>>,
+    /// Output pulse length (ONESHOT)
+    pub pulse_width: Option<u64>,
+}
+
+impl GateParams {
+    /// Delay overrides applied by `TimingGate`
+    pub fn timing(&self) -> GateTiming {
+        GateTiming {
+            rise_delay: self.rise_delay,
+            fall_delay: self.fall_delay,
+            min_delay: self.min_delay,
+            max_delay: self.max_delay,
+        }
+    }
+}
+
+/// Factory function to create gates by type
+///
+/// A mask with any inverted port wraps the gate in `InvertedInputsGate`, and
+/// rise/fall or corner delays wrap it in `TimingGate`.
+pub fn create_gate(gate_type: &str, id: String, params: &GateParams) -> Box<dyn Gate> {
+    let gate = with_inverted_inputs(build_basic_gate(gate_type, id, params), params.inverted_inputs.clone());
+    with_timing(gate, params.timing())
+}
+
+/// Wrap a gate in `TimingGate` if the timing overrides any delay
+pub fn with_timing(gate: Box<dyn Gate>, timing: GateTiming) -> Box<dyn Gate> {
+    if timing.is_default() {
+        return gate;
+    }
+    Box::new(TimingGate::new(gate, timing))
+}
+
+/// Wrap a gate in `InvertedInputsGate` if the mask inverts any port
+pub fn with_inverted_inputs(gate: Box<dyn Gate>, inverted_inputs: Option<Vec<bool>>) -> Box<dyn Gate> {
+    match inverted_inputs {
+        Some(mask) if mask.iter().any(|&inverted| inverted) && gate.input_count() > 0 => {
+            Box::new(InvertedInputsGate::new(gate, mask))
+        }
+        _ => gate,
+    }
+}
+
+fn build_basic_gate(gate_type: &str, id: String, params: &GateParams) -> Box<dyn Gate> {
+    let input_count = params.input_count;
+    let delay = params.delay.unwrap_or(1);
+    match gate_type {
+        "AND" => Box::new(AndGate::new(id, input_count.unwrap_or(2), delay)),
+        "OR" => Box::new(OrGate::new(id, input_count.unwrap_or(2), delay)),
+        "NOT" => Box::new(NotGate::new(id, delay)),
+        "XOR" => Box::new(XorGate::new(id, input_count.unwrap_or(2), delay)),
+        "NAND" => Box::new(NandGate::new(id, input_count.unwrap_or(2), delay)),
+        "NOR" => Box::new(NorGate::new(id, input_count.unwrap_or(2), delay)),
+        "XNOR" => Box::new(XnorGate::new(id, input_count.unwrap_or(2), delay)),
+        "BUFFER" => Box::new(BufferGate::new(id, delay)),
+        "TRI_BUFFER" => Box::new(TriBufferGate::new(id, delay)),
+        "TGATE" => Box::new(TransmissionGate::new(id, delay)),
+        "TRANSCEIVER" => Box::new(TransceiverGate::new(id, delay)),
+        "TOGGLE" => Box::new(ToggleGate::new(id)),
+        "CLOCK" => Box::new(ClockGate::new(id)),
+        "PULSE" => Box::new(PulseGate::new(id)),
+        "CONST_HIGH" => Box::new(ConstantGate::new(id, StateType::One)),
+        "CONST_LOW" => Box::new(ConstantGate::new(id, StateType::Zero)),
+        "PULLUP" => Box::new(PullUpGate::new(id)),
+        "PULLDOWN" => Box::new(PullDownGate::new(id)),
+        "LED" => Box::new(LedGate::new(id)),
+        "SEVEN_SEG" => Box::new(SevenSegmentGate::new(id, input_count.unwrap_or(4))),
+        "BCD" => Box::new(BcdConvertGate::new(id, input_count.unwrap_or(8), delay)),
+        "DFF" => Box::new(DFlipFlopGate::new(id, input_count.is_some_and(|n| n >= 4), delay)),
+        "JKFF" => Box::new(JkFlipFlopGate::new(id, input_count.is_some_and(|n| n >= 5), delay)),
+        "TFF" => Box::new(TFlipFlopGate::new(id, delay)),
+        "SR_LATCH" => Box::new(SrLatchGate::new(id, delay)),
+        "DECODER" => Box::new(DecoderGate::new(id, input_count.unwrap_or(2), false, delay)),
+        "MAJORITY" => Box::new(MajorityGate::new(id, input_count.unwrap_or(3), delay)),
+        "COMPARATOR" => Box::new(ComparatorGate::new(id, input_count.unwrap_or(8) / 2, delay)),
+        "COUNTER" => Box::new(CounterGate::new(id, params.output_count.unwrap_or(4), params.modulus, delay)),
+        "DIVIDER" => {
+            let divisor = params.init_data.as_deref().and_then(|data| data.first()).copied().unwrap_or(2);
+            Box::new(DividerGate::new(id, divisor as u64, delay))
+        }
+        "MATCH" => {
+            let data = params.init_data.as_deref().unwrap_or_default();
+            Box::new(MatchGate::new(
+                id,
+                input_count.unwrap_or(4),
+                data.first().copied().unwrap_or(0),
+                data.get(1).copied().unwrap_or(u32::MAX),
+                delay,
+            ))
+        }
+        "RANGE" => {
+            let data = params.init_data.as_deref().unwrap_or_default();
+            Box::new(RangeGate::new(
+                id,
+                input_count.unwrap_or(8),
+                data.first().copied().unwrap_or(0),
+                data.get(1).copied().unwrap_or(u32::MAX),
+                delay,
+            ))
+        }
+        "LUT" => Box::new(LookupGate::new(
+            id,
+            input_count.unwrap_or(2),
+            params.output_count.unwrap_or(1),
+            params.init_data.clone().unwrap_or_default(),
+            delay,
+        )),
+        "ROM" => Box::new(RomGate::new(
+            id,
+            input_count.unwrap_or(4),
+            params.output_count.unwrap_or(8),
+            params.init_data.clone().unwrap_or_default(),
+            delay,
+        )),
+        "RAM" => {
+            let data_bits = params.output_count.unwrap_or(8);
+            let addr_bits = input_count.map_or(4, |count| count.saturating_sub(data_bits + 2));
+            Box::new(RamGate::new(id, addr_bits, data_bits, delay))
+        }
+        "BUS" => Box::new(BusNodeGate::new(id, input_count.unwrap_or(2), delay)),
+        "SHIFT_REG" => Box::new(ShiftRegisterGate::new(
+            id,
+            params.output_count.map_or(4, |n| n.saturating_sub(1)),
+            delay,
+        )),
+        "REGISTER" => Box::new(RegisterGate::new(id, input_count.unwrap_or(11).saturating_sub(3), delay)),
+        "HALF_ADDER" => Box::new(HalfAdderGate::new(id, delay)),
+        "FULL_ADDER" => Box::new(FullAdderGate::new(id, delay)),
+        "FULL_SUB" => Box::new(FullSubtractorGate::new(id, delay)),
+        "ADDER" => Box::new(RippleCarryAdderGate::new(id, input_count.unwrap_or(9).saturating_sub(1) / 2, delay)),
+        "DEMUX2" => Box::new(DemuxGate::new(id, 1, false, delay)),
+        "ONESHOT" => Box::new(MonostableGate::new(id, params.pulse_width.unwrap_or(10), delay)),
+        "SCHMITT" => {
+            let hold = params.init_data.as_deref().and_then(|data| data.first()).copied().unwrap_or(2);
+            Box::new(SchmittGate::new(id, hold, delay))
+        }
+        "DEBOUNCE" => {
+            let window = params.init_data.as_deref().and_then(|data| data.first()).copied().unwrap_or(5);
+            Box::new(DebounceGate::new(id, window as u64, delay))
+        }
+        "PRIORITY_ENC" => Box::new(PriorityEncoderGate::new(
+            id,
+            input_count.map_or(2, |count| count.next_power_of_two().trailing_zeros() as usize),
+            delay,
+        )),
+        "DEMUX2_TRI" => Box::new(DemuxGate::new(id, 1, true, delay)),
+        "DECODER_EN" => Box::new(DecoderGate::new(id, input_count.unwrap_or(3).saturating_sub(1), true, delay)),
+        _ => Box::new(BufferGate::new(id, delay)), // Default fallback
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dff_latches_on_rising_edge() {
+        let mut dff = DFlipFlopGate::new("dff".to_string(), false, 1);
+        dff.set_input(0, StateType::One);
+        dff.set_input(1, StateType::Zero);
+        dff.evaluate();
+        assert_eq!(dff.get_outputs(), &[StateType::Zero, StateType::One]);
+
+        dff.set_input(1, StateType::One);
+        dff.evaluate();
+        assert_eq!(dff.get_outputs(), &[StateType::One, StateType::Zero]);
+
+        // Holding clock high must not re-latch
+        dff.set_input(0, StateType::Zero);
+        dff.evaluate();
+        assert_eq!(dff.get_outputs(), &[StateType::One, StateType::Zero]);
+
+        dff.reset();
+        assert_eq!(dff.get_outputs(), &[StateType::Zero, StateType::One]);
+    }
+
+    #[test]
+    fn test_flip_flop_async_preset_clear() {
+        let (zero, one) = (StateType::Zero, StateType::One);
+        let mut dff = DFlipFlopGate::new("dff".to_string(), true, 1);
+        assert_eq!(dff.input_count(), 4);
+        for (port, state) in [(0, zero), (1, zero), (2, one), (3, one)] {
+            dff.set_input(port, state);
+        }
+
+        // PRE_n overrides without a clock edge
+        dff.set_input(2, zero);
+        assert_eq!(dff.evaluate().outputs, vec![one, zero]);
+
+        // Clock edges are ignored while CLR_n holds Q low
+        dff.set_input(2, one);
+        dff.set_input(3, zero);
+        dff.evaluate();
+        dff.set_input(1, one);
+        assert_eq!(dff.evaluate().outputs, vec![zero, one]);
+
+        dff.set_input(2, zero);
+        assert_eq!(dff.evaluate().outputs[0], StateType::Conflict);
+
+        let mut jk = JkFlipFlopGate::new("jk".to_string(), true, 1);
+        assert_eq!(jk.input_count(), 5);
+        jk.set_input(3, zero);
+        jk.set_input(4, one);
+        assert_eq!(jk.evaluate().outputs, vec![one, zero]);
+    }
+
+    fn clock_jk(jk: &mut JkFlipFlopGate, j: StateType, k: StateType) -> StateType {
+        jk.set_input(0, j);
+        jk.set_input(1, k);
+        jk.set_input(2, StateType::Zero);
+        jk.evaluate();
+        jk.set_input(2, StateType::One);
+        jk.evaluate();
+        jk.get_outputs()[0]
+    }
+
+    #[test]
+    fn test_jkff_truth_table() {
+        let mut jk = JkFlipFlopGate::new("jk".to_string(), false, 1);
+        assert_eq!(clock_jk(&mut jk, StateType::One, StateType::Zero), StateType::One);
+        assert_eq!(clock_jk(&mut jk, StateType::Zero, StateType::Zero), StateType::One);
+        assert_eq!(clock_jk(&mut jk, StateType::Zero, StateType::One), StateType::Zero);
+        assert_eq!(clock_jk(&mut jk, StateType::One, StateType::One), StateType::One);
+        assert_eq!(clock_jk(&mut jk, StateType::One, StateType::One), StateType::Zero);
+        assert_eq!(clock_jk(&mut jk, StateType::Unknown, StateType::Zero), StateType::Unknown);
+        assert_eq!(jk.get_outputs()[1], StateType::Unknown);
+    }
+
+    #[test]
+    fn test_tff_toggles_and_holds() {
+        let mut tff = TFlipFlopGate::new("tff".to_string(), 1);
+        let clock = |tff: &mut TFlipFlopGate, t: StateType| {
+            tff.set_input(0, t);
+            tff.set_input(1, StateType::Zero);
+            tff.evaluate();
+            tff.set_input(1, StateType::One);
+            tff.evaluate();
+            tff.get_outputs()[0]
+        };
+        assert_eq!(clock(&mut tff, StateType::One), StateType::One);
+        assert_eq!(clock(&mut tff, StateType::Zero), StateType::One);
+        assert_eq!(clock(&mut tff, StateType::One), StateType::Zero);
+        assert_eq!(clock(&mut tff, StateType::Unknown), StateType::Unknown);
+    }
+
+    fn drive_sr(latch: &mut SrLatchGate, s: StateType, r: StateType) -> Vec<StateType> {
+        latch.set_input(0, s);
+        latch.set_input(1, r);
+        latch.evaluate().outputs
+    }
+
+    #[test]
+    fn test_sr_latch_set_reset_hold() {
+        let mut latch = SrLatchGate::new("sr".to_string(), 1);
+        assert_eq!(drive_sr(&mut latch, StateType::One, StateType::Zero), vec![StateType::One, StateType::Zero]);
+        assert_eq!(drive_sr(&mut latch, StateType::Zero, StateType::Zero), vec![StateType::One, StateType::Zero]);
+        assert_eq!(drive_sr(&mut latch, StateType::Zero, StateType::One), vec![StateType::Zero, StateType::One]);
+        assert_eq!(drive_sr(&mut latch, StateType::Zero, StateType::Zero), vec![StateType::Zero, StateType::One]);
+    }
+
+    #[test]
+    fn test_sr_latch_forbidden_state() {
+        let mut latch = SrLatchGate::new("sr".to_string(), 1);
+        assert_eq!(drive_sr(&mut latch, StateType::One, StateType::One), vec![StateType::Conflict, StateType::Conflict]);
+        assert_eq!(drive_sr(&mut latch, StateType::One, StateType::Zero), vec![StateType::One, StateType::Zero]);
+    }
+
+    #[test]
+    fn test_decoder_one_hot() {
+        let mut decoder = DecoderGate::new("dec".to_string(), 2, false, 1);
+        decoder.set_input(0, StateType::Zero);
+        decoder.set_input(1, StateType::One);
+        assert_eq!(
+            decoder.evaluate().outputs,
+            vec![StateType::Zero, StateType::Zero, StateType::One, StateType::Zero]
+        );
+
+        decoder.set_input(0, StateType::Unknown);
+        assert_eq!(decoder.evaluate().outputs, vec![StateType::Unknown; 4]);
+    }
+
+    #[test]
+    fn test_decoder_enable() {
+        let mut decoder = DecoderGate::new("dec".to_string(), 3, true, 1);
+        assert_eq!(decoder.output_count(), 8);
+        for i in 0..3 {
+            decoder.set_input(i, StateType::One);
+        }
+        decoder.set_input(3, StateType::Zero);
+        assert_eq!(decoder.evaluate().outputs, vec![StateType::Zero; 8]);
+
+        decoder.set_input(3, StateType::One);
+        assert_eq!(decoder.evaluate().outputs[7], StateType::One);
+    }
+
+    fn bits(value: usize, width: usize) -> Vec<StateType> {
+        (0..width)
+            .map(|i| if value & (1 << i) != 0 { StateType::One } else { StateType::Zero })
+            .collect()
+    }
+
+    #[test]
+    fn test_full_adder_truth_table() {
+        let mut adder = FullAdderGate::new("fa".to_string(), 1);
+        for value in 0..8 {
+            for (i, bit) in bits(value, 3).into_iter().enumerate() {
+                adder.set_input(i, bit);
+            }
+            let total = (value & 1) + ((value >> 1) & 1) + ((value >> 2) & 1);
+            assert_eq!(adder.evaluate().outputs, bits(total, 2));
+        }
+
+        adder.set_input(0, StateType::Unknown);
+        adder.set_input(1, StateType::Zero);
+        adder.set_input(2, StateType::Zero);
+        assert_eq!(adder.evaluate().outputs, vec![StateType::Unknown, StateType::Zero]);
+    }
+
+    #[test]
+    fn test_half_adder_truth_table() {
+        let mut adder = HalfAdderGate::new("ha".to_string(), 1);
+        for value in 0..4 {
+            for (i, bit) in bits(value, 2).into_iter().enumerate() {
+                adder.set_input(i, bit);
+            }
+            let total = (value & 1) + ((value >> 1) & 1);
+            assert_eq!(adder.evaluate().outputs, bits(total, 2));
+        }
+
+        adder.set_input(0, StateType::Unknown);
+        adder.set_input(1, StateType::Zero);
+        assert_eq!(adder.evaluate().outputs, vec![StateType::Unknown, StateType::Zero]);
+    }
+
+    #[test]
+    fn test_full_subtractor_truth_table() {
+        let mut subtractor = FullSubtractorGate::new("fs".to_string(), 1);
+        // (A, B, Bin) -> (Diff, Bout)
+        let table = [
+            ((0, 0, 0), (0, 0)),
+            ((0, 0, 1), (1, 1)),
+            ((0, 1, 0), (1, 1)),
+            ((0, 1, 1), (0, 1)),
+            ((1, 0, 0), (1, 0)),
+            ((1, 0, 1), (0, 0)),
+            ((1, 1, 0), (0, 0)),
+            ((1, 1, 1), (1, 1)),
+        ];
+        for ((a, b, borrow_in), (difference, borrow_out)) in table {
+            subtractor.set_input(0, bits(a, 1)[0]);
+            subtractor.set_input(1, bits(b, 1)[0]);
+            subtractor.set_input(2, bits(borrow_in, 1)[0]);
+            assert_eq!(subtractor.evaluate().outputs, bits(difference | (borrow_out << 1), 2));
+        }
+
+        // A borrow is certain whatever A is when B and Bin are both ONE
+        subtractor.set_input(0, StateType::Unknown);
+        subtractor.set_input(1, StateType::One);
+        subtractor.set_input(2, StateType::One);
+        assert_eq!(subtractor.evaluate().outputs, vec![StateType::Unknown, StateType::One]);
+    }
+
+    #[test]
+    fn test_ripple_carry_adder() {
+        let mut adder = RippleCarryAdderGate::new("add".to_string(), 4, 1);
+        let mut inputs = bits(11, 4);
+        inputs.extend(bits(6, 4));
+        inputs.push(StateType::One);
+        for (i, bit) in inputs.into_iter().enumerate() {
+            adder.set_input(i, bit);
+        }
+        assert_eq!(adder.evaluate().outputs, bits(18, 5));
+    }
+
+    #[test]
+    fn test_seven_segment_decoding() {
+        let mut display = SevenSegmentGate::new("seg".to_string(), 4);
+        let lit = |segments: Vec<StateType>| -> Vec<bool> {
+            segments.into_iter().map(|s| s == StateType::One).collect()
+        };
+
+        for (i, bit) in bits(8, 4).into_iter().enumerate() {
+            display.set_input(i, bit);
+        }
+        assert_eq!(lit(display.segments().unwrap()), vec![true; 7]);
+
+        for (i, bit) in bits(12, 4).into_iter().enumerate() {
+            display.set_input(i, bit);
+        }
+        assert_eq!(lit(display.segments().unwrap()), SEVEN_SEGMENT_DASH.to_vec());
+
+        display.set_input(0, StateType::Unknown);
+        assert_eq!(display.segments().unwrap(), vec![StateType::Unknown; 7]);
+    }
+
+    #[test]
+    fn test_majority_with_unknowns() {
+        let mut voter = MajorityGate::new("maj".to_string(), 3, 1);
+        let mut vote = |states: [StateType; 3]| {
+            for (i, state) in states.into_iter().enumerate() {
+                voter.set_input(i, state);
+            }
+            voter.evaluate().outputs[0]
+        };
+        assert_eq!(vote([StateType::One, StateType::One, StateType::Zero]), StateType::One);
+        assert_eq!(vote([StateType::One, StateType::Zero, StateType::Zero]), StateType::Zero);
+        assert_eq!(vote([StateType::One, StateType::One, StateType::Unknown]), StateType::One);
+        assert_eq!(vote([StateType::Zero, StateType::Zero, StateType::Unknown]), StateType::Zero);
+        assert_eq!(vote([StateType::One, StateType::Zero, StateType::Unknown]), StateType::Unknown);
+    }
+
+    #[test]
+    fn test_constant_gate_holds_level() {
+        let mut high = ConstantGate::new("hi".to_string(), StateType::One);
+        let mut low = ConstantGate::new("lo".to_string(), StateType::Zero);
+        assert_eq!(high.evaluate().outputs, vec![StateType::One]);
+        assert_eq!(low.evaluate().delay, 0);
+
+        high.reset();
+        low.reset();
+        assert_eq!(high.get_outputs(), &[StateType::One]);
+        assert_eq!(low.get_outputs(), &[StateType::Zero]);
+        assert_eq!(high.gate_type(), "CONST_HIGH");
+    }
+
+    #[test]
+    fn test_bus_node_resolves_drivers() {
+        let mut bus = BusNodeGate::new("bus".to_string(), 3, 1);
+        assert_eq!(bus.evaluate().outputs, vec![StateType::HiZ]);
+
+        bus.set_input(1, StateType::One);
+        assert_eq!(bus.evaluate().outputs, vec![StateType::One]);
+
+        bus.set_input(2, StateType::One);
+        assert_eq!(bus.evaluate().outputs, vec![StateType::One]);
+
+        bus.set_input(0, StateType::Zero);
+        assert_eq!(bus.evaluate().outputs, vec![StateType::Conflict]);
+    }
+
+    #[test]
+    fn test_inverted_input_bubble() {
+        // AND with an inverted second input computes a AND NOT b
+        let mut gate = create_gate(
+            "AND",
+            "g".to_string(),
+            &GateParams { inverted_inputs: Some(vec![false, true]), ..Default::default() },
+        );
+        assert_eq!(gate.inverted_inputs(), Some(&[false, true][..]));
+
+        gate.set_input(0, StateType::One);
+        gate.set_input(1, StateType::Zero);
+        assert_eq!(gate.evaluate().outputs, vec![StateType::One]);
+
+        gate.set_input(1, StateType::One);
+        assert_eq!(gate.evaluate().outputs, vec![StateType::Zero]);
+
+        let plain = create_gate(
+            "AND",
+            "g".to_string(),
+            &GateParams { inverted_inputs: Some(vec![false, false]), ..Default::default() },
+        );
+        assert_eq!(plain.inverted_inputs(), None);
+    }
+
+    #[test]
+    fn test_register_latches_all_bits_on_enabled_edge() {
+        let mut reg = RegisterGate::new("reg".to_string(), 4, 1);
+        assert_eq!(reg.input_count(), 7);
+        for (i, &bit) in bits(0b1010, 4).iter().enumerate() {
+            reg.set_input(i, bit);
+        }
+        reg.set_input(6, StateType::Zero);
+
+        // Rising edge with enable low holds
+        reg.set_input(5, StateType::Zero);
+        reg.set_input(4, StateType::Zero);
+        reg.evaluate();
+        reg.set_input(4, StateType::One);
+        assert_eq!(reg.evaluate().outputs, bits(0, 4));
+
+        // Rising edge with enable high latches every bit
+        reg.set_input(5, StateType::One);
+        reg.set_input(4, StateType::Zero);
+        reg.evaluate();
+        reg.set_input(4, StateType::One);
+        assert_eq!(reg.evaluate().outputs, bits(0b1010, 4));
+
+        // Reset clears without a clock edge
+        reg.set_input(6, StateType::One);
+        assert_eq!(reg.evaluate().outputs, bits(0, 4));
+    }
+
+    #[test]
+    fn test_range_bounds() {
+        let mut range = RangeGate::new("cs".to_string(), 8, 0x20, 0x3F, 1);
+        let mut check = |value: usize| {
+            for (i, bit) in bits(value, 8).into_iter().enumerate() {
+                range.set_input(i, bit);
+            }
+            range.evaluate().outputs[0]
+        };
+        assert_eq!(check(0x2A), StateType::One);
+        assert_eq!(check(0x1F), StateType::Zero);
+        assert_eq!(check(0x40), StateType::Zero);
+        assert_eq!(check(0x20), StateType::One);
+        assert_eq!(check(0x3F), StateType::One);
+    }
+
+    #[test]
+    fn test_range_unknown_bits() {
+        let mut range = RangeGate::new("cs".to_string(), 8, 0x20, 0x3F, 1);
+        let mut check = |value: usize, unknown_bit: usize| {
+            for (i, bit) in bits(value, 8).into_iter().enumerate() {
+                range.set_input(i, bit);
+            }
+            range.set_input(unknown_bit, StateType::Unknown);
+            range.evaluate().outputs[0]
+        };
+        // Low bits cannot leave the window, high bits can
+        assert_eq!(check(0x2A, 0), StateType::One);
+        assert_eq!(check(0x2A, 6), StateType::Unknown);
+        // 0x00 or 0x80: both outside on either side
+        assert_eq!(check(0x00, 7), StateType::Zero);
+        // 0x1F or 0x3F straddles the lower bound
+        assert_eq!(check(0x1F, 5), StateType::Unknown);
+    }
+
+    #[test]
+    fn test_match_exact_pattern() {
+        let mut gate = MatchGate::new("m".to_string(), 4, 0b1010, u32::MAX, 1);
+        for value in 0..16 {
+            for (i, bit) in bits(value, 4).into_iter().enumerate() {
+                gate.set_input(i, bit);
+            }
+            let expected = if value == 0b1010 { StateType::One } else { StateType::Zero };
+            assert_eq!(gate.evaluate().outputs, vec![expected]);
+        }
+    }
+
+    #[test]
+    fn test_match_mismatch_and_unknown() {
+        let mut gate = MatchGate::new("m".to_string(), 4, 0b1010, u32::MAX, 1);
+        for (i, bit) in bits(0b1010, 4).into_iter().enumerate() {
+            gate.set_input(i, bit);
+        }
+        gate.set_input(0, StateType::Unknown);
+        assert_eq!(gate.evaluate().outputs, vec![StateType::Unknown]);
+
+        // A known mismatch decides the result regardless
+        gate.set_input(3, StateType::Zero);
+        assert_eq!(gate.evaluate().outputs, vec![StateType::Zero]);
+    }
+
+    #[test]
+    fn test_match_dont_care_bit() {
+        // Address decode 1x01: bit 2 is don't care
+        let mut gate = MatchGate::new("m".to_string(), 4, 0b1001, 0b1011, 1);
+        for value in [0b1001, 0b1101] {
+            for (i, bit) in bits(value, 4).into_iter().enumerate() {
+                gate.set_input(i, bit);
+            }
+            assert_eq!(gate.evaluate().outputs, vec![StateType::One]);
+        }
+        gate.set_input(2, StateType::Unknown);
+        assert_eq!(gate.evaluate().outputs, vec![StateType::One]);
+        gate.set_input(1, StateType::One);
+        assert_eq!(gate.evaluate().outputs, vec![StateType::Zero]);
+    }
+
+    #[test]
+    fn test_bcd_converts_255() {
+        let mut bcd = BcdConvertGate::new("bcd".to_string(), 8, 1);
+        assert_eq!(bcd.output_count(), 12);
+        for (i, bit) in bits(255, 8).into_iter().enumerate() {
+            bcd.set_input(i, bit);
+        }
+        // Digits 2, 5, 5 packed ones digit first
+        assert_eq!(bcd.evaluate().outputs, bits(0x255, 12));
+
+        bcd.set_input(3, StateType::Unknown);
+        assert_eq!(bcd.evaluate().outputs, vec![StateType::Unknown; 12]);
+    }
+
+    #[test]
+    fn test_comparator() {
+        let mut cmp = ComparatorGate::new("cmp".to_string(), 3, 1);
+        let compare = |cmp: &mut ComparatorGate, a: usize, b: usize| {
+            for (i, bit) in bits(a, 3).into_iter().chain(bits(b, 3)).enumerate() {
+                cmp.set_input(i, bit);
+            }
+            cmp.evaluate().outputs
+        };
+
+        let (zero, one) = (StateType::Zero, StateType::One);
+        assert_eq!(compare(&mut cmp, 0b101, 0b011), vec![zero, one, zero]);
+        assert_eq!(compare(&mut cmp, 0b011, 0b101), vec![zero, zero, one]);
+        assert_eq!(compare(&mut cmp, 0b110, 0b110), vec![one, zero, zero]);
+
+        cmp.set_input(4, StateType::Unknown);
+        assert_eq!(cmp.evaluate().outputs, vec![StateType::Unknown; 3]);
+    }
+
+    fn clock_counter(counter: &mut CounterGate) -> Vec<StateType> {
+        counter.set_input(0, StateType::Zero);
+        counter.evaluate();
+        counter.set_input(0, StateType::One);
+        counter.evaluate().outputs
+    }
+
+    #[test]
+    fn test_counter_wraps_around() {
+        let mut counter = CounterGate::new("cnt".to_string(), 2, None, 1);
+        counter.set_input(1, StateType::Zero);
+        counter.set_input(2, StateType::One);
+
+        for expected in [1, 2, 3, 0, 1] {
+            assert_eq!(clock_counter(&mut counter), bits(expected, 2));
+        }
+
+        // Disabled counter holds, reset clears without a clock edge
+        counter.set_input(2, StateType::Zero);
+        assert_eq!(clock_counter(&mut counter), bits(1, 2));
+        counter.set_input(1, StateType::One);
+        assert_eq!(counter.evaluate().outputs, bits(0, 2));
+    }
+
+    #[test]
+    fn test_counter_custom_modulus() {
+        let mut decade = CounterGate::new("cnt".to_string(), 4, Some(10), 1);
+        decade.set_input(1, StateType::Zero);
+        decade.set_input(2, StateType::One);
+
+        for _ in 0..9 {
+            clock_counter(&mut decade);
+        }
+        assert_eq!(decade.get_outputs(), &bits(9, 4)[..]);
+        assert_eq!(clock_counter(&mut decade), bits(0, 4));
+    }
+
+    #[test]
+    fn test_divider_period() {
+        let mut divider = create_gate(
+            "DIVIDER",
+            "div".to_string(),
+            &GateParams { init_data: Some(vec![3]), ..Default::default() },
+        );
+        divider.set_input(1, StateType::Zero);
+
+        let mut levels = Vec::new();
+        for _ in 0..12 {
+            divider.set_input(0, StateType::Zero);
+            divider.evaluate();
+            divider.set_input(0, StateType::One);
+            levels.push(divider.evaluate().outputs[0]);
+        }
+        // Three edges low, three edges high: one period every 6 clocks
+        let (zero, one) = (StateType::Zero, StateType::One);
+        assert_eq!(levels, vec![zero, zero, one, one, one, zero, zero, zero, one, one, one, zero]);
+
+        divider.set_input(1, StateType::One);
+        assert_eq!(divider.evaluate().outputs, vec![StateType::Zero]);
+    }
+
+    #[test]
+    fn test_toggle_set_level() {
+        let mut toggle = ToggleGate::new("t".to_string());
+        toggle.set_level(StateType::One);
+        assert_eq!(toggle.evaluate().outputs, vec![StateType::One]);
+        toggle.set_level(StateType::One);
+        assert_eq!(toggle.evaluate().outputs, vec![StateType::One]);
+        toggle.toggle();
+        assert_eq!(toggle.evaluate().outputs, vec![StateType::Zero]);
+    }
+
+    #[test]
+    fn test_rom_reads_addressed_word() {
+        let mut rom = RomGate::new("rom".to_string(), 2, 4, vec![0x3, 0xA, 0xF], 1);
+        let mut read = |address: usize| {
+            for (i, bit) in bits(address, 2).into_iter().enumerate() {
+                rom.set_input(i, bit);
+            }
+            rom.evaluate().outputs
+        };
+
+        assert_eq!(read(0), bits(0x3, 4));
+        assert_eq!(read(1), bits(0xA, 4));
+        assert_eq!(read(2), bits(0xF, 4));
+        assert_eq!(read(3), bits(0, 4)); // past the end of the contents
+
+        rom.set_input(1, StateType::Unknown);
+        assert_eq!(rom.evaluate().outputs, vec![StateType::Unknown; 4]);
+    }
+
+    #[test]
+    fn test_lut_truth_table_and_unknown_inputs() {
+        // y = a AND b, plus a second output that ignores b: z = a
+        let mut lut = LookupGate::new("lut".to_string(), 2, 2, vec![0b00, 0b10, 0b00, 0b11], 1);
+        for row in 0..4 {
+            for (i, bit) in bits(row, 2).into_iter().enumerate() {
+                lut.set_input(i, bit);
+            }
+            assert_eq!(lut.evaluate().outputs, bits([0b00, 0b10, 0b00, 0b11][row], 2));
+        }
+
+        // a = 1, b unknown: y depends on b, z does not
+        lut.set_input(0, StateType::One);
+        lut.set_input(1, StateType::Unknown);
+        assert_eq!(lut.evaluate().outputs, vec![StateType::Unknown, StateType::One]);
+
+        // a = 0 decides both outputs
+        lut.set_input(0, StateType::Zero);
+        assert_eq!(lut.evaluate().outputs, vec![StateType::Zero, StateType::Zero]);
+    }
+
+    #[test]
+    fn test_ram_write_then_read() {
+        // 2 address bits, 4 data bits: inputs [a0, a1, d0..d3, we, clk]
+        let mut ram = RamGate::new("ram".to_string(), 2, 4, 1);
+        let cycle = |ram: &mut RamGate, address: usize, data: usize, write: bool| {
+            for (i, bit) in bits(address, 2).into_iter().chain(bits(data, 4)).enumerate() {
+                ram.set_input(i, bit);
+            }
+            ram.set_input(6, if write { StateType::One } else { StateType::Zero });
+            ram.set_input(7, StateType::Zero);
+            ram.evaluate();
+            ram.set_input(7, StateType::One);
+            ram.evaluate().outputs
+        };
+
+        assert_eq!(cycle(&mut ram, 2, 0b1001, true), bits(0b1001, 4));
+        assert_eq!(cycle(&mut ram, 1, 0b0110, true), bits(0b0110, 4));
+        assert_eq!(cycle(&mut ram, 2, 0b1111, false), bits(0b1001, 4));
+        assert_eq!(cycle(&mut ram, 3, 0, false), bits(0, 4));
+
+        let saved = ram.save_state();
+        ram.reset();
+        assert_eq!(cycle(&mut ram, 2, 0, false), bits(0, 4));
+        ram.load_state(&saved);
+        assert_eq!(cycle(&mut ram, 1, 0, false), bits(0b0110, 4));
+    }
+
+    #[test]
+    fn test_demux_routes_data_to_selected_output() {
+        let mut demux = DemuxGate::new("demux".to_string(), 1, false, 1);
+        demux.set_input(0, StateType::One);
+        demux.set_input(1, StateType::Zero);
+        assert_eq!(demux.evaluate().outputs, vec![StateType::One, StateType::Zero]);
+        demux.set_input(1, StateType::One);
+        assert_eq!(demux.evaluate().outputs, vec![StateType::Zero, StateType::One]);
+        demux.set_input(1, StateType::Unknown);
+        assert_eq!(demux.evaluate().outputs, vec![StateType::Unknown; 2]);
+
+        let mut tri = DemuxGate::new("demux".to_string(), 1, true, 1);
+        tri.set_input(0, StateType::Zero);
+        tri.set_input(1, StateType::One);
+        assert_eq!(tri.evaluate().outputs, vec![StateType::HiZ, StateType::Zero]);
+        assert_eq!(tri.gate_type(), "DEMUX2_TRI");
+    }
+
+    #[test]
+    fn test_priority_encoder_picks_highest_input() {
+        let mut enc = PriorityEncoderGate::new("enc".to_string(), 2, 1);
+        assert_eq!(enc.input_count(), 4);
+        let mut drive = |value: usize| {
+            for (i, state) in bits(value, 4).into_iter().enumerate() {
+                enc.set_input(i, state);
+            }
+            enc.evaluate().outputs
+        };
+        assert_eq!(drive(0b0000), bits(0b000, 3));
+        assert_eq!(drive(0b0001), bits(0b100, 3));
+        assert_eq!(drive(0b0110), bits(0b110, 3));
+        assert_eq!(drive(0b1011), bits(0b111, 3));
+
+        // Unknown below the highest ONE is irrelevant, above it is not
+        enc.set_input(0, StateType::Unknown);
+        assert_eq!(enc.evaluate().outputs, bits(0b111, 3));
+        enc.set_input(3, StateType::Unknown);
+        assert_eq!(enc.evaluate().outputs, vec![StateType::Unknown; 3]);
+    }
+
+    #[test]
+    fn test_oneshot_pulse_width() {
+        let mut oneshot = MonostableGate::new("os".to_string(), 5, 1);
+        oneshot.set_input(0, StateType::Zero);
+        oneshot.tick(0);
+        oneshot.evaluate();
+
+        oneshot.set_input(0, StateType::One);
+        oneshot.tick(3);
+        assert_eq!(oneshot.evaluate().outputs, vec![StateType::One]);
+        assert_eq!(oneshot.next_wakeup(3), Some(8));
+
+        // A new edge mid-pulse does not extend it
+        oneshot.set_input(0, StateType::Zero);
+        oneshot.tick(4);
+        oneshot.evaluate();
+        oneshot.set_input(0, StateType::One);
+        oneshot.tick(5);
+        oneshot.evaluate();
+
+        oneshot.tick(8);
+        assert_eq!(oneshot.evaluate().outputs, vec![StateType::Zero]);
+        assert_eq!(oneshot.next_wakeup(8), None);
+    }
+
+    #[test]
+    fn test_tgate_passes_signal_when_closed() {
+        let mut tgate = TransmissionGate::new("tg".to_string(), 1);
+        tgate.set_input(1, StateType::One);
+        for signal in [StateType::Zero, StateType::One, StateType::HiZ, StateType::WeakOne] {
+            tgate.set_input(0, signal);
+            assert_eq!(tgate.evaluate().outputs, vec![signal]);
+        }
+
+        tgate.set_input(1, StateType::Zero);
+        tgate.set_input(0, StateType::One);
+        assert_eq!(tgate.evaluate().outputs, vec![StateType::HiZ]);
+
+        tgate.set_input(1, StateType::Unknown);
+        assert_eq!(tgate.evaluate().outputs, vec![StateType::Unknown]);
+    }
+
+    #[test]
+    fn test_schmitt_ignores_single_step_glitch() {
+        let mut schmitt = SchmittGate::new("st".to_string(), 2, 1);
+        schmitt.set_input(0, StateType::Zero);
+        schmitt.evaluate();
+        assert_eq!(schmitt.evaluate().outputs, vec![StateType::Zero]);
+
+        // A one-step glitch never reaches the output
+        schmitt.set_input(0, StateType::One);
+        assert_eq!(schmitt.evaluate().outputs, vec![StateType::Zero]);
+        assert_eq!(schmitt.next_wakeup(5), Some(6));
+        schmitt.set_input(0, StateType::Zero);
+        assert_eq!(schmitt.evaluate().outputs, vec![StateType::Zero]);
+        assert_eq!(schmitt.next_wakeup(6), None);
+
+        // A held change does, counted in evaluations however far apart
+        let mut slow = SchmittGate::new("st".to_string(), 3, 1);
+        slow.set_input(0, StateType::Zero);
+        for _ in 0..3 {
+            slow.evaluate();
+        }
+        slow.set_input(0, StateType::One);
+        let outputs: Vec<StateType> = (0..3).map(|_| slow.evaluate().outputs[0]).collect();
+        assert_eq!(outputs, vec![StateType::Zero, StateType::Zero, StateType::One]);
+    }
+
+    #[test]
+    fn test_debounce_collapses_bounces() {
+        let mut debounce = DebounceGate::new("db".to_string(), 5, 1);
+        debounce.set_input(0, StateType::Zero);
+        debounce.tick(0);
+        assert_eq!(debounce.evaluate().outputs, vec![StateType::Zero]);
+
+        // Contact bounce: the first edge passes, the chatter after it does not
+        let bounces = [
+            (10, StateType::One),
+            (11, StateType::Zero),
+            (12, StateType::One),
+            (13, StateType::Zero),
+            (14, StateType::One),
+        ];
+        let mut transitions = 0;
+        for (time, level) in bounces {
+            let before = debounce.get_outputs()[0];
+            debounce.set_input(0, level);
+            debounce.tick(time);
+            if debounce.evaluate().outputs[0] != before {
+                transitions += 1;
+            }
+        }
+        assert_eq!(transitions, 1);
+        assert_eq!(debounce.get_outputs(), &[StateType::One]);
+        assert_eq!(debounce.next_wakeup(14), None);
+
+        // An input that settles inside the window is taken once it closes
+        debounce.set_input(0, StateType::Zero);
+        debounce.tick(20);
+        assert_eq!(debounce.evaluate().outputs, vec![StateType::Zero]);
+        debounce.set_input(0, StateType::One);
+        debounce.tick(21);
+        assert_eq!(debounce.evaluate().outputs, vec![StateType::Zero]);
+        assert_eq!(debounce.next_wakeup(21), Some(25));
+        debounce.tick(25);
+        assert_eq!(debounce.evaluate().outputs, vec![StateType::One]);
+
+        // The window is configured through init_data, not pulse_width
+        let params = GateParams { init_data: Some(vec![8]), pulse_width: Some(2), ..Default::default() };
+        let mut configured = create_gate("DEBOUNCE", "db".to_string(), &params);
+        configured.set_input(0, StateType::One);
+        configured.tick(0);
+        configured.evaluate();
+        configured.set_input(0, StateType::Zero);
+        configured.tick(1);
+        configured.evaluate();
+        assert_eq!(configured.next_wakeup(1), Some(8));
+    }
+
+    #[test]
+    fn test_shift_register_clocks_in_serial_bits() {
+        let mut reg = ShiftRegisterGate::new("sr".to_string(), 4, 1);
+        reg.set_input(2, StateType::Zero);
+        for bit in [StateType::One, StateType::Zero, StateType::One] {
+            reg.set_input(0, bit);
+            reg.set_input(1, StateType::Zero);
+            reg.evaluate();
+            reg.set_input(1, StateType::One);
+            reg.evaluate();
+        }
+        // Newest bit in q0, serial_out mirrors q3
+        assert_eq!(reg.get_outputs(), &bits(0b00101, 5)[..]);
+
+        reg.set_input(2, StateType::One);
+        assert_eq!(reg.evaluate().outputs, bits(0, 5));
+    }
+
+    #[test]
+    fn test_ram_warm_reset_keeps_contents() {
+        let mut ram = RamGate::new("ram".to_string(), 1, 2, 1);
+        // [a0, d0, d1, we, clk]: write 0b10 to address 1
+        for (port, state) in bits(0b01101, 5).into_iter().enumerate() {
+            ram.set_input(port, state);
+        }
+        ram.evaluate();
+        ram.set_input(4, StateType::One);
+        assert_eq!(ram.evaluate().outputs, bits(0b10, 2));
+
+        ram.reset_mode(ResetMode::StateOnly);
+        assert_eq!(ram.get_outputs(), &[StateType::Unknown; 2]);
+        ram.set_input(0, StateType::One);
+        assert_eq!(ram.evaluate().outputs, bits(0b10, 2));
+
+        ram.reset_mode(ResetMode::Full);
+        ram.set_input(0, StateType::One);
+        assert_eq!(ram.evaluate().outputs, bits(0b00, 2));
+    }
+
+    #[test]
+    fn test_port_vectors_match_declared_counts() {
+        let gate_types = [
+            "AND", "OR", "NOT", "XOR", "NAND", "NOR", "XNOR", "BUFFER", "TRI_BUFFER", "TGATE", "TRANSCEIVER",
+            "TOGGLE", "CLOCK", "PULSE", "CONST_HIGH", "CONST_LOW", "PULLUP", "PULLDOWN", "LED", "SEVEN_SEG",
+            "DFF", "JKFF", "TFF", "SR_LATCH", "DECODER", "DECODER_EN", "MAJORITY", "HALF_ADDER", "FULL_ADDER",
+            "FULL_SUB", "ADDER", "BUS", "REGISTER", "COMPARATOR", "COUNTER", "DIVIDER", "ROM", "RAM",
+            "DEMUX2", "DEMUX2_TRI", "SHIFT_REG", "PRIORITY_ENC", "ONESHOT", "SCHMITT", "DEBOUNCE", "LUT",
+            "MATCH", "RANGE", "BCD",
+        ];
+
+        for gate_type in gate_types {
+            let mut gate = create_gate(gate_type, "g".to_string(), &GateParams::default());
+            assert_eq!(gate.get_inputs().len(), gate.input_count(), "{} inputs", gate_type);
+            assert_eq!(gate.get_outputs().len(), gate.output_count(), "{} outputs", gate_type);
+
+            let result = gate.evaluate();
+            assert_eq!(result.outputs.len(), gate.output_count(), "{} result", gate_type);
+            assert_eq!(gate.get_outputs().len(), gate.output_count(), "{} after evaluate", gate_type);
+        }
     }
 }
@@ -1,6 +1,6 @@
 //! Gate trait and common functionality
 
-use super::state::StateType;
+use super::state::{StateType, UnknownPolicy};
 
 /// Gate evaluation result
 pub struct GateResult {
@@ -8,6 +8,48 @@ pub struct GateResult {
     pub delay: u64,
 }
 
+/// How much gate state a reset clears
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ResetMode {
+    /// Return to the power-on state, clearing memory contents
+    #[default]
+    Full,
+    /// Warm reset: clear inputs, outputs and control state but keep stored
+    /// memory contents
+    StateOnly,
+}
+
+impl ResetMode {
+    pub fn from_u8(value: u8) -> Self {
+        match value {
+            1 => ResetMode::StateOnly,
+            _ => ResetMode::Full,
+        }
+    }
+}
+
+/// Process corner selecting which delay the engine schedules with
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DelayCorner {
+    /// The gate's normal delays, including separate rise/fall delays
+    #[default]
+    Typical,
+    /// Fast corner: `min_delay` where the gate sets one
+    Min,
+    /// Slow corner: `max_delay` where the gate sets one
+    Max,
+}
+
+impl DelayCorner {
+    pub fn from_u8(value: u8) -> Self {
+        match value {
+            1 => DelayCorner::Min,
+            2 => DelayCorner::Max,
+            _ => DelayCorner::Typical,
+        }
+    }
+}
+
 /// Trait for all logic gates
 pub trait Gate {
     /// Get gate ID
@@ -31,25 +73,110 @@ pub trait Gate {
     /// Set input state at index
     fn set_input(&mut self, index: usize, state: StateType);
 
+    /// Overwrite output state at index (used when restoring saved state)
+    fn set_output(&mut self, _index: usize, _state: StateType) {}
+
     /// Evaluate gate logic and return outputs
     fn evaluate(&mut self) -> GateResult;
 
     /// Reset gate to initial state
     fn reset(&mut self);
 
+    /// Reset according to `mode`; gates with memory override this to keep
+    /// their contents under `ResetMode::StateOnly`
+    fn reset_mode(&mut self, _mode: ResetMode) {
+        self.reset();
+    }
+
     /// Get propagation delay
     fn delay(&self) -> u64 {
         1
     }
 
+    /// Propagation delay for an output rising to ONE
+    fn rise_delay(&self) -> u64 {
+        self.delay()
+    }
+
+    /// Propagation delay for an output falling to ZERO
+    fn fall_delay(&self) -> u64 {
+        self.delay()
+    }
+
+    /// Delay for every output at a non-typical corner, if the gate sets one;
+    /// otherwise the typical delays apply
+    fn corner_delay(&self, _corner: DelayCorner) -> Option<u64> {
+        None
+    }
+
     /// Check for rising edge on input (for sequential logic)
     fn is_rising_edge(&self, _index: usize) -> bool {
         false
     }
 
+    /// Whether the gate holds state across clock edges, enables or time
+    /// (flip-flops, latches, registers, one-shots, debouncers); timing paths
+    /// end at such gates
+    fn is_sequential(&self) -> bool {
+        false
+    }
+
     /// Update previous inputs for edge detection
     fn update_previous_inputs(&mut self) {}
 
     /// Toggle gate state (for interactive gates like switches)
     fn toggle(&mut self) {}
+
+    /// Set gate state to an absolute level (for interactive gates like switches)
+    fn set_level(&mut self, _state: StateType) {}
+
+    /// Advance time-dependent state (for clocks and timers) before evaluation
+    fn tick(&mut self, _time: u64) {}
+
+    /// Time at which the gate must next re-evaluate itself, if any
+    fn next_wakeup(&self, _time: u64) -> Option<u64> {
+        None
+    }
+
+    /// Set oscillation period (for clock gates)
+    fn set_period(&mut self, _period: u64) {}
+
+    /// Shift the oscillation phase by `offset` time units (for clock gates)
+    fn set_phase(&mut self, _offset: u64) {}
+
+    /// Drive the output high until `end_time` (for pulse buttons)
+    fn fire_pulse(&mut self, _end_time: u64) {}
+
+    /// Decoded display segments [a, b, c, d, e, f, g] (for display gates)
+    fn segments(&self) -> Option<Vec<StateType>> {
+        None
+    }
+
+    /// Capture internal state not visible through inputs/outputs
+    fn save_state(&self) -> Vec<u64> {
+        Vec::new()
+    }
+
+    /// Restore internal state captured by `save_state`
+    fn load_state(&mut self, _state: &[u64]) {}
+
+    /// Whether the gate resolves HiZ inputs itself (bus nodes), bypassing
+    /// the engine's floating input policy
+    fn passes_high_impedance(&self) -> bool {
+        false
+    }
+
+    /// Per-port input inversion mask (inverted ports store the negated state)
+    fn inverted_inputs(&self) -> Option<&[bool]> {
+        None
+    }
+
+    /// Input that senses the inout pin driven by `output`, if that output is
+    /// one; the engine feeds it the value resolved across the pin's net
+    fn inout_input(&self, _output: usize) -> Option<usize> {
+        None
+    }
+
+    /// Choose how the gate's logic treats UNKNOWN inputs
+    fn set_unknown_policy(&mut self, _policy: UnknownPolicy) {}
 }
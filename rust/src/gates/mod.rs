@@ -1,3 +1,4 @@
 pub mod state;
 pub mod basic;
 pub mod gate;
+pub mod subcircuit;
@@ -1,16 +1,23 @@
-//! 5-state logic types for digital simulation
+//! Multi-state logic types for digital simulation
+
+use std::fmt;
 
 use serde::{Deserialize, Serialize};
 
-/// Logic state type (5-state)
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+/// Logic state type (5 strong states plus weak pull-up/pull-down levels)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
 #[repr(u8)]
 pub enum StateType {
     Zero = 0,
     One = 1,
     HiZ = 2,
     Conflict = 3,
+    #[default]
     Unknown = 4,
+    /// Weakly driven ZERO (pull-down); overridden by any strong driver
+    WeakZero = 5,
+    /// Weakly driven ONE (pull-up); overridden by any strong driver
+    WeakOne = 6,
 }
 
 impl StateType {
@@ -22,6 +29,8 @@ impl StateType {
             2 => StateType::HiZ,
             3 => StateType::Conflict,
             4 => StateType::Unknown,
+            5 => StateType::WeakZero,
+            6 => StateType::WeakOne,
             _ => StateType::Unknown,
         }
     }
@@ -31,29 +40,61 @@ impl StateType {
         self as u8
     }
 
+    /// Parse the symbol written by `Display` ("0", "1", "Z", "C", "X", and
+    /// "L"/"H" for weak levels); letters are case-insensitive
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s.to_ascii_uppercase().as_str() {
+            "0" => Some(StateType::Zero),
+            "1" => Some(StateType::One),
+            "Z" => Some(StateType::HiZ),
+            "C" => Some(StateType::Conflict),
+            "X" => Some(StateType::Unknown),
+            "L" => Some(StateType::WeakZero),
+            "H" => Some(StateType::WeakOne),
+            _ => None,
+        }
+    }
+
+    /// Logic level seen by a gate input: weak levels read as their strong value
+    pub fn to_strong(self) -> Self {
+        match self {
+            StateType::WeakZero => StateType::Zero,
+            StateType::WeakOne => StateType::One,
+            other => other,
+        }
+    }
+
+    /// Whether this is a weakly driven level
+    pub fn is_weak(self) -> bool {
+        matches!(self, StateType::WeakZero | StateType::WeakOne)
+    }
+
     /// Logical NOT operation
+    #[allow(clippy::should_implement_trait)]
     pub fn not(self) -> Self {
-        match self {
+        match self.to_strong() {
             StateType::Zero => StateType::One,
             StateType::One => StateType::Zero,
             StateType::HiZ => StateType::Unknown,
             StateType::Conflict => StateType::Conflict,
-            StateType::Unknown => StateType::Unknown,
+            _ => StateType::Unknown,
         }
     }
 
     /// Logical AND operation
     pub fn and(self, other: Self) -> Self {
-        if self == StateType::Zero || other == StateType::Zero {
+        let (a, b) = (self.to_strong(), other.to_strong());
+        if a == StateType::Zero || b == StateType::Zero {
             return StateType::Zero;
         }
-        if self == StateType::Conflict || other == StateType::Conflict {
+        if a == StateType::Conflict || b == StateType::Conflict {
             return StateType::Conflict;
         }
-        if self == StateType::Unknown || other == StateType::Unknown {
+        if a == StateType::Unknown || b == StateType::Unknown {
             return StateType::Unknown;
         }
-        if self == StateType::HiZ || other == StateType::HiZ {
+        if a == StateType::HiZ || b == StateType::HiZ {
             return StateType::Unknown;
         }
         StateType::One
@@ -61,16 +102,17 @@ impl StateType {
 
     /// Logical OR operation
     pub fn or(self, other: Self) -> Self {
-        if self == StateType::One || other == StateType::One {
+        let (a, b) = (self.to_strong(), other.to_strong());
+        if a == StateType::One || b == StateType::One {
             return StateType::One;
         }
-        if self == StateType::Conflict || other == StateType::Conflict {
+        if a == StateType::Conflict || b == StateType::Conflict {
             return StateType::Conflict;
         }
-        if self == StateType::Unknown || other == StateType::Unknown {
+        if a == StateType::Unknown || b == StateType::Unknown {
             return StateType::Unknown;
         }
-        if self == StateType::HiZ || other == StateType::HiZ {
+        if a == StateType::HiZ || b == StateType::HiZ {
             return StateType::Unknown;
         }
         StateType::Zero
@@ -78,46 +120,160 @@ impl StateType {
 
     /// Logical XOR operation
     pub fn xor(self, other: Self) -> Self {
-        if self == StateType::Conflict || other == StateType::Conflict {
+        let (a, b) = (self.to_strong(), other.to_strong());
+        if a == StateType::Conflict || b == StateType::Conflict {
             return StateType::Conflict;
         }
-        if self == StateType::Unknown || other == StateType::Unknown {
+        if a == StateType::Unknown || b == StateType::Unknown {
             return StateType::Unknown;
         }
-        if self == StateType::HiZ || other == StateType::HiZ {
+        if a == StateType::HiZ || b == StateType::HiZ {
             return StateType::Unknown;
         }
-        if self == other {
+        if a == b {
             StateType::Zero
         } else {
             StateType::One
         }
     }
-}
 
-impl Default for StateType {
-    fn default() -> Self {
-        StateType::Unknown
+    /// AND of all states, as folding `and` from the first (UNKNOWN when
+    /// empty); stops at the first ZERO
+    pub fn reduce_and(states: &[StateType]) -> Self {
+        Self::reduce_and_with_policy(states, UnknownPolicy::Pessimistic)
+    }
+
+    /// `reduce_and` reading every operand under an unknown policy
+    pub fn reduce_and_with_policy(states: &[StateType], policy: UnknownPolicy) -> Self {
+        Self::reduce_until(states, policy, Self::and, StateType::Zero)
+    }
+
+    /// OR of all states, as folding `or` from the first (UNKNOWN when
+    /// empty); stops at the first ONE
+    pub fn reduce_or(states: &[StateType]) -> Self {
+        Self::reduce_or_with_policy(states, UnknownPolicy::Pessimistic)
+    }
+
+    /// `reduce_or` reading every operand under an unknown policy
+    pub fn reduce_or_with_policy(states: &[StateType], policy: UnknownPolicy) -> Self {
+        Self::reduce_until(states, policy, Self::or, StateType::One)
+    }
+
+    /// XOR of all states, as folding `xor` from the first (UNKNOWN when
+    /// empty); stops at the first CONFLICT
+    pub fn reduce_xor(states: &[StateType]) -> Self {
+        Self::reduce_xor_with_policy(states, UnknownPolicy::Pessimistic)
+    }
+
+    /// `reduce_xor` reading every operand under an unknown policy
+    pub fn reduce_xor_with_policy(states: &[StateType], policy: UnknownPolicy) -> Self {
+        Self::reduce_until(states, policy, Self::xor, StateType::Conflict)
+    }
+
+    /// Fold `op` over the operands read under `policy`, returning `absorbing`
+    /// as soon as an operand of that strength decides the result
+    ///
+    /// A lone operand is returned as read, weak strength included.
+    fn reduce_until(states: &[StateType], policy: UnknownPolicy, op: fn(Self, Self) -> Self, absorbing: Self) -> Self {
+        if let [single] = states {
+            return policy.read(*single);
+        }
+        let mut result = None;
+        for &state in states {
+            let state = policy.read(state);
+            if state.to_strong() == absorbing {
+                return absorbing;
+            }
+            result = Some(result.map_or(state, |result| op(result, state)));
+        }
+        result.unwrap_or(StateType::Unknown)
+    }
+
+    /// Logical NOT of the operand as read under an unknown policy
+    pub fn not_with_policy(self, policy: UnknownPolicy) -> Self {
+        policy.read(self).not()
+    }
+
+    /// Logical XOR of the operands as read under an unknown policy
+    ///
+    /// Pessimistic is plain `xor`. Optimistic reads UNKNOWN and HiZ operands
+    /// as ZERO, so the result follows the other operand; Conflict still wins.
+    pub fn xor_with_policy(self, other: Self, policy: UnknownPolicy) -> Self {
+        policy.read(self).xor(policy.read(other))
     }
 }
 
-/// Resolve wire state from multiple sources
-pub fn resolve_wire_state(sources: &[StateType]) -> StateType {
-    if sources.is_empty() {
-        return StateType::HiZ;
+/// How gate logic (AND, OR, NOT, XOR and the gates built on them) treats
+/// UNKNOWN operands
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UnknownPolicy {
+    /// Any unknown operand that could affect the result makes it UNKNOWN
+    #[default]
+    Pessimistic,
+    /// Unknown and floating operands read as ZERO, keeping results defined
+    Optimistic,
+}
+
+impl UnknownPolicy {
+    pub fn from_u8(value: u8) -> Self {
+        match value {
+            1 => UnknownPolicy::Optimistic,
+            _ => UnknownPolicy::Pessimistic,
+        }
     }
 
+    /// Operand as gate logic reads it: unchanged when pessimistic, UNKNOWN
+    /// and HiZ as ZERO (and weak levels as strong) when optimistic
+    pub fn read(self, state: StateType) -> StateType {
+        match (self, state.to_strong()) {
+            (UnknownPolicy::Pessimistic, _) => state,
+            (UnknownPolicy::Optimistic, StateType::Unknown | StateType::HiZ) => StateType::Zero,
+            (UnknownPolicy::Optimistic, strong) => strong,
+        }
+    }
+}
+
+/// Strength with which a source drives a net
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DriveStrength {
+    /// Actively driven (gate outputs)
+    Strong,
+    /// Resistively driven (pull-ups, open-drain idle level)
+    Weak,
+    /// Not driving the net
+    HiZ,
+}
+
+/// Resolve a net from sources with explicit drive strengths
+///
+/// Any strong driver overrides weak ones, and only opposing strong drivers
+/// produce Conflict. Opposing (or unknown) weak drivers with no strong driver
+/// resolve to Unknown. Weak states are always treated as weak drivers and
+/// HiZ values never drive the net, whatever strength they are paired with.
+pub fn resolve_driven_state(sources: &[(StateType, DriveStrength)]) -> StateType {
     let mut has_zero = false;
     let mut has_one = false;
     let mut has_unknown = false;
+    let mut has_weak_zero = false;
+    let mut has_weak_one = false;
+    let mut has_weak_unknown = false;
+
+    for &(state, strength) in sources {
+        let strength = match state {
+            StateType::HiZ => DriveStrength::HiZ,
+            StateType::WeakZero | StateType::WeakOne if strength == DriveStrength::Strong => DriveStrength::Weak,
+            _ => strength,
+        };
 
-    for &state in sources {
-        match state {
-            StateType::Conflict => return StateType::Conflict,
-            StateType::Zero => has_zero = true,
-            StateType::One => has_one = true,
-            StateType::Unknown => has_unknown = true,
-            StateType::HiZ => {} // HiZ doesn't drive the wire
+        match (strength, state.to_strong()) {
+            (DriveStrength::HiZ, _) => {}
+            (DriveStrength::Strong, StateType::Conflict) => return StateType::Conflict,
+            (DriveStrength::Strong, StateType::Zero) => has_zero = true,
+            (DriveStrength::Strong, StateType::One) => has_one = true,
+            (DriveStrength::Strong, _) => has_unknown = true,
+            (DriveStrength::Weak, StateType::Zero) => has_weak_zero = true,
+            (DriveStrength::Weak, StateType::One) => has_weak_one = true,
+            (DriveStrength::Weak, _) => has_weak_unknown = true,
         }
     }
 
@@ -127,17 +283,100 @@ pub fn resolve_wire_state(sources: &[StateType]) -> StateType {
         StateType::One
     } else if has_zero {
         StateType::Zero
-    } else if has_unknown {
+    } else if has_unknown || has_weak_unknown || (has_weak_zero && has_weak_one) {
         StateType::Unknown
+    } else if has_weak_one {
+        StateType::WeakOne
+    } else if has_weak_zero {
+        StateType::WeakZero
     } else {
         StateType::HiZ
     }
 }
 
+/// How the drivers of one net combine
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WireResolution {
+    /// Drivers contend (see `resolve_wire_state`)
+    #[default]
+    Standard,
+    /// Driven values are ORed together; HiZ drivers are ignored
+    WiredOr,
+    /// Driven values are ANDed together; HiZ drivers are ignored
+    WiredAnd,
+}
+
+impl WireResolution {
+    /// Resolve a net driven by `sources` under this policy
+    pub fn resolve(self, sources: &[StateType]) -> StateType {
+        let combine: fn(StateType, StateType) -> StateType = match self {
+            WireResolution::Standard => return resolve_wire_state(sources),
+            WireResolution::WiredOr => StateType::or,
+            WireResolution::WiredAnd => StateType::and,
+        };
+        sources
+            .iter()
+            .copied()
+            .filter(|&state| state != StateType::HiZ)
+            .reduce(combine)
+            .map_or(StateType::HiZ, StateType::to_strong)
+    }
+}
+
+/// Resolve wire state from multiple sources
+///
+/// Every source is a strong driver except weak states (pull resistors) and
+/// HiZ; see `resolve_driven_state`.
+pub fn resolve_wire_state(sources: &[StateType]) -> StateType {
+    let driven: Vec<(StateType, DriveStrength)> =
+        sources.iter().map(|&state| (state, DriveStrength::Strong)).collect();
+    resolve_driven_state(&driven)
+}
+
+/// Resolve a bus bit-by-bit from multiple drivers of equal width
+pub fn resolve_bus_state(sources: &[&[StateType]], width: usize) -> Vec<StateType> {
+    (0..width)
+        .map(|bit| {
+            let bit_states: Vec<StateType> = sources
+                .iter()
+                .map(|source| source.get(bit).copied().unwrap_or(StateType::HiZ))
+                .collect();
+            resolve_wire_state(&bit_states)
+        })
+        .collect()
+}
+
+impl fmt::Display for StateType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let symbol = match self {
+            StateType::Zero => "0",
+            StateType::One => "1",
+            StateType::HiZ => "Z",
+            StateType::Conflict => "C",
+            StateType::Unknown => "X",
+            StateType::WeakZero => "L",
+            StateType::WeakOne => "H",
+        };
+        f.write_str(symbol)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_display_round_trip() {
+        for value in 0..=6 {
+            let state = StateType::from_u8(value);
+            assert_eq!(StateType::from_str(&state.to_string()), Some(state));
+        }
+        assert_eq!(StateType::HiZ.to_string(), "Z");
+        assert_eq!(StateType::from_str("x"), Some(StateType::Unknown));
+        assert_eq!(StateType::from_str("2"), None);
+    }
+
     #[test]
     fn test_not() {
         assert_eq!(StateType::Zero.not(), StateType::One);
@@ -152,6 +391,17 @@ mod tests {
         assert_eq!(StateType::One.and(StateType::One), StateType::One);
     }
 
+    #[test]
+    fn test_xor_with_policy() {
+        let pessimistic = UnknownPolicy::Pessimistic;
+        let optimistic = UnknownPolicy::Optimistic;
+        assert_eq!(StateType::One.xor_with_policy(StateType::Unknown, pessimistic), StateType::Unknown);
+        assert_eq!(StateType::One.xor_with_policy(StateType::Unknown, optimistic), StateType::One);
+        assert_eq!(StateType::HiZ.xor_with_policy(StateType::WeakZero, optimistic), StateType::Zero);
+        assert_eq!(StateType::Conflict.xor_with_policy(StateType::One, optimistic), StateType::Conflict);
+        assert_eq!(StateType::One.xor_with_policy(StateType::One, optimistic), StateType::Zero);
+    }
+
     #[test]
     fn test_wire_resolution() {
         assert_eq!(resolve_wire_state(&[]), StateType::HiZ);
@@ -159,4 +409,87 @@ mod tests {
         assert_eq!(resolve_wire_state(&[StateType::One]), StateType::One);
         assert_eq!(resolve_wire_state(&[StateType::Zero, StateType::One]), StateType::Conflict);
     }
+
+    #[test]
+    fn test_bus_resolution() {
+        let a = [StateType::One, StateType::HiZ, StateType::Zero];
+        let b = [StateType::HiZ, StateType::Zero, StateType::One];
+        assert_eq!(
+            resolve_bus_state(&[&a, &b], 3),
+            vec![StateType::One, StateType::Zero, StateType::Conflict]
+        );
+        assert_eq!(resolve_bus_state(&[], 2), vec![StateType::HiZ, StateType::HiZ]);
+    }
+
+    #[test]
+    fn test_reducers_match_fold() {
+        fn fold(states: &[StateType], op: impl Fn(StateType, StateType) -> StateType) -> StateType {
+            let mut result = states.first().copied().unwrap_or(StateType::Unknown);
+            for &state in states.iter().skip(1) {
+                result = op(result, state);
+            }
+            result
+        }
+
+        // xorshift64 so the inputs are reproducible
+        let mut seed: u64 = 0x9e37_79b9_7f4a_7c15;
+        let mut next = || {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            seed
+        };
+        for _ in 0..2000 {
+            let len = (next() % 9) as usize;
+            let states: Vec<StateType> = (0..len).map(|_| StateType::from_u8((next() % 7) as u8)).collect();
+            assert_eq!(StateType::reduce_and(&states), fold(&states, StateType::and), "{:?}", states);
+            assert_eq!(StateType::reduce_or(&states), fold(&states, StateType::or), "{:?}", states);
+            assert_eq!(StateType::reduce_xor(&states), fold(&states, StateType::xor), "{:?}", states);
+
+            // Policies read every operand, then fold as usual
+            let optimistic = UnknownPolicy::Optimistic;
+            let read: Vec<StateType> = states.iter().map(|&state| optimistic.read(state)).collect();
+            assert_eq!(StateType::reduce_and_with_policy(&states, optimistic), fold(&read, StateType::and), "{:?}", states);
+            assert_eq!(StateType::reduce_or_with_policy(&states, optimistic), fold(&read, StateType::or), "{:?}", states);
+            assert_eq!(StateType::reduce_xor_with_policy(&states, optimistic), fold(&read, StateType::xor), "{:?}", states);
+        }
+    }
+
+    #[test]
+    fn test_wired_resolution() {
+        use StateType::*;
+        let mixed = [Zero, HiZ, One, WeakZero];
+        assert_eq!(WireResolution::Standard.resolve(&mixed), Conflict);
+        assert_eq!(WireResolution::WiredOr.resolve(&mixed), One);
+        assert_eq!(WireResolution::WiredAnd.resolve(&mixed), Zero);
+
+        assert_eq!(WireResolution::WiredOr.resolve(&[Zero, Unknown]), Unknown);
+        assert_eq!(WireResolution::WiredAnd.resolve(&[One, WeakOne]), One);
+        assert_eq!(WireResolution::WiredAnd.resolve(&[Zero, Unknown]), Zero);
+        assert_eq!(WireResolution::WiredOr.resolve(&[HiZ, HiZ]), HiZ);
+    }
+
+    #[test]
+    fn test_weak_resolution() {
+        assert_eq!(resolve_wire_state(&[StateType::WeakOne]), StateType::WeakOne);
+        assert_eq!(resolve_wire_state(&[StateType::WeakOne, StateType::Zero]), StateType::Zero);
+        assert_eq!(resolve_wire_state(&[StateType::WeakZero, StateType::HiZ]), StateType::WeakZero);
+        assert_eq!(resolve_wire_state(&[StateType::WeakOne, StateType::WeakZero]), StateType::Unknown);
+        assert_eq!(StateType::from_u8(StateType::WeakOne.to_u8()), StateType::WeakOne);
+        assert_eq!(StateType::WeakOne.and(StateType::One), StateType::One);
+        assert_eq!(StateType::WeakZero.not(), StateType::One);
+    }
+
+    #[test]
+    fn test_drive_strength_resolution() {
+        use DriveStrength::{Strong, Weak};
+
+        // Open-drain bus: a strong ZERO pulls down the weak idle ONE
+        assert_eq!(resolve_driven_state(&[(StateType::One, Weak), (StateType::Zero, Strong)]), StateType::Zero);
+        assert_eq!(resolve_driven_state(&[(StateType::One, Weak), (StateType::One, Weak)]), StateType::WeakOne);
+        assert_eq!(resolve_driven_state(&[(StateType::One, Weak), (StateType::Zero, Weak)]), StateType::Unknown);
+        assert_eq!(resolve_driven_state(&[(StateType::One, Strong), (StateType::Zero, Strong)]), StateType::Conflict);
+        assert_eq!(resolve_driven_state(&[(StateType::One, DriveStrength::HiZ)]), StateType::HiZ);
+        assert_eq!(resolve_driven_state(&[(StateType::Conflict, Weak)]), StateType::Unknown);
+    }
 }
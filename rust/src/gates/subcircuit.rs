@@ -0,0 +1,248 @@
+//! Hierarchical subcircuit gate
+
+use super::gate::{Gate, GateResult, ResetMode};
+use super::state::{StateType, UnknownPolicy};
+use crate::simulation::engine::SimulationEngine;
+use crate::SubcircuitDefinition;
+
+/// Steps the inner circuit may take to settle before outputs go Unknown
+const SUBCIRCUIT_SETTLE_STEPS: u64 = 1000;
+
+/// Gate wrapping its own inner circuit
+///
+/// Each evaluation drives the inner input gates, runs the inner engine until
+/// only self-timed wakeups (clocks, one-shot ends) remain and reads the inner
+/// output gates, so the inner propagation appears as a single gate delay
+/// outside. The inner clock follows the outer one from the first evaluation:
+/// the earliest inner wakeup is forwarded through `next_wakeup`, and the
+/// inner engine is advanced by the time passed outside before each
+/// evaluation. `save_state` captures the whole inner engine state.
+pub struct SubcircuitGate {
+    id: String,
+    engine: SimulationEngine,
+    input_ids: Vec<String>,
+    output_ids: Vec<String>,
+    inputs: Vec<StateType>,
+    outputs: Vec<StateType>,
+    delay: u64,
+    /// Outer time of the current evaluation
+    time: u64,
+    /// Outer and inner time that correspond, fixed at the first evaluation
+    origin: Option<(u64, u64)>,
+}
+
+impl SubcircuitGate {
+    pub fn new(id: String, definition: SubcircuitDefinition, delay: u64) -> Self {
+        let mut engine = SimulationEngine::new();
+        engine.initialize(definition.gates, definition.wires);
+
+        let output_count = definition
+            .output_ids
+            .iter()
+            .map(|id| engine.observed_states(id).len())
+            .sum();
+
+        let mut gate = Self {
+            id,
+            engine,
+            inputs: vec![StateType::Unknown; definition.input_ids.len()],
+            outputs: vec![StateType::Unknown; output_count],
+            input_ids: definition.input_ids,
+            output_ids: definition.output_ids,
+            delay,
+            time: 0,
+            origin: None,
+        };
+        gate.settle();
+        gate.outputs.fill(StateType::Unknown);
+        gate
+    }
+
+    /// Run the inner circuit until only self-timed wakeups are pending and
+    /// read its outputs, or set them Unknown if it does not settle
+    fn settle(&mut self) {
+        let mut settled = false;
+        for _ in 0..SUBCIRCUIT_SETTLE_STEPS {
+            let Some(time) = self.engine.next_activity_time() else {
+                settled = true;
+                break;
+            };
+            let now = self.engine.get_current_time();
+            self.engine.advance_by(time.saturating_sub(now));
+        }
+
+        if settled {
+            let observed: Vec<StateType> = self
+                .output_ids
+                .iter()
+                .flat_map(|id| self.engine.observed_states(id))
+                .collect();
+            for (output, state) in self.outputs.iter_mut().zip(observed) {
+                *output = state;
+            }
+        } else {
+            self.outputs.fill(StateType::Unknown);
+        }
+    }
+}
+
+impl Gate for SubcircuitGate {
+    fn id(&self) -> &str { &self.id }
+    fn gate_type(&self) -> &str { "SUBCIRCUIT" }
+    fn input_count(&self) -> usize { self.inputs.len() }
+    fn output_count(&self) -> usize { self.outputs.len() }
+    fn get_inputs(&self) -> &[StateType] { &self.inputs }
+    fn get_outputs(&self) -> &[StateType] { &self.outputs }
+
+    fn set_input(&mut self, index: usize, state: StateType) {
+        if index < self.inputs.len() { self.inputs[index] = state; }
+    }
+
+    fn set_output(&mut self, index: usize, state: StateType) {
+        if index < self.outputs.len() { self.outputs[index] = state; }
+    }
+
+    fn evaluate(&mut self) -> GateResult {
+        // Let the inner circuit catch up with the time passed outside
+        let (outer_base, inner_base) = *self.origin.get_or_insert((self.time, self.engine.get_current_time()));
+        let target = inner_base.saturating_add(self.time.saturating_sub(outer_base));
+        let now = self.engine.get_current_time();
+        if target >= now {
+            self.engine.advance_by(target - now);
+        }
+
+        for (input_id, &state) in self.input_ids.iter().zip(&self.inputs) {
+            self.engine.drive_output(input_id, 0, state);
+        }
+
+        self.settle();
+        GateResult { outputs: self.outputs.clone(), delay: self.delay }
+    }
+
+    fn reset(&mut self) {
+        self.reset_mode(ResetMode::Full);
+    }
+
+    fn reset_mode(&mut self, mode: ResetMode) {
+        self.engine.reset(mode);
+        self.origin = None;
+        self.inputs.fill(StateType::Unknown);
+        self.settle();
+    }
+
+    fn delay(&self) -> u64 { self.delay }
+    fn is_sequential(&self) -> bool { self.engine.has_sequential_gates() }
+
+    fn save_state(&self) -> Vec<u64> {
+        // Whether the time origin is set, the origin and the outer time, then
+        // the inner snapshot as JSON: its byte length and the bytes packed
+        // eight per word
+        let bytes = serde_json::to_vec(&self.engine.export_state()).unwrap_or_default();
+        let (outer_base, inner_base) = self.origin.unwrap_or_default();
+        let mut state = vec![self.origin.is_some() as u64, outer_base, inner_base, self.time, bytes.len() as u64];
+        state.extend(bytes.chunks(8).map(|chunk| {
+            let mut word = [0; 8];
+            word[..chunk.len()].copy_from_slice(chunk);
+            u64::from_le_bytes(word)
+        }));
+        state
+    }
+
+    fn load_state(&mut self, state: &[u64]) {
+        let [has_origin, outer_base, inner_base, time, len, packed @ ..] = state else {
+            return;
+        };
+        let bytes: Vec<u8> = packed.iter().flat_map(|word| word.to_le_bytes()).take(*len as usize).collect();
+        if let Ok(snapshot) = serde_json::from_slice(&bytes) {
+            self.engine.import_state(snapshot);
+        }
+        self.origin = (*has_origin != 0).then_some((*outer_base, *inner_base));
+        self.time = *time;
+    }
+
+    fn tick(&mut self, time: u64) {
+        self.time = time;
+    }
+
+    fn next_wakeup(&self, time: u64) -> Option<u64> {
+        let (outer_base, inner_base) = self.origin?;
+        let wakeup = self.engine.next_event_time()?;
+        Some(outer_base.saturating_add(wakeup.saturating_sub(inner_base)).max(time + 1))
+    }
+
+    fn set_unknown_policy(&mut self, policy: UnknownPolicy) {
+        self.engine.set_unknown_policy(policy);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{gate, wire};
+
+    fn half_adder() -> SubcircuitDefinition {
+        SubcircuitDefinition {
+            gates: vec![
+                gate("a", "TOGGLE", 0),
+                gate("b", "TOGGLE", 0),
+                gate("xor", "XOR", 2),
+                gate("and", "AND", 2),
+                gate("sum", "LED", 1),
+                gate("carry", "LED", 1),
+            ],
+            wires: vec![
+                wire("w1", "a", 0, "xor", 0),
+                wire("w2", "b", 0, "xor", 1),
+                wire("w3", "a", 0, "and", 0),
+                wire("w4", "b", 0, "and", 1),
+                wire("w5", "xor", 0, "sum", 0),
+                wire("w6", "and", 0, "carry", 0),
+            ],
+            input_ids: vec!["a".to_string(), "b".to_string()],
+            output_ids: vec!["sum".to_string(), "carry".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_subcircuit_half_adder() {
+        let mut gate = SubcircuitGate::new("ha".to_string(), half_adder(), 1);
+        assert_eq!(gate.input_count(), 2);
+        assert_eq!(gate.output_count(), 2);
+
+        let (zero, one) = (StateType::Zero, StateType::One);
+        for (a, b, sum, carry) in [(zero, zero, zero, zero), (one, zero, one, zero), (one, one, zero, one)] {
+            gate.set_input(0, a);
+            gate.set_input(1, b);
+            assert_eq!(gate.evaluate().outputs, vec![sum, carry]);
+        }
+
+        gate.set_input(1, StateType::Unknown);
+        assert_eq!(gate.evaluate().outputs, vec![StateType::Unknown, StateType::Unknown]);
+    }
+
+    #[test]
+    fn test_subcircuit_saves_inner_state() {
+        let register = || SubcircuitDefinition {
+            gates: vec![gate("d", "TOGGLE", 0), gate("c", "TOGGLE", 0), gate("dff", "DFF", 2), gate("q", "LED", 1)],
+            wires: vec![wire("w1", "d", 0, "dff", 0), wire("w2", "c", 0, "dff", 1), wire("w3", "dff", 0, "q", 0)],
+            input_ids: vec!["d".to_string(), "c".to_string()],
+            output_ids: vec!["q".to_string()],
+        };
+        let mut gate = SubcircuitGate::new("reg".to_string(), register(), 1);
+        for (time, clock) in [(0, StateType::Zero), (5, StateType::One), (10, StateType::Zero)] {
+            gate.tick(time);
+            gate.set_input(0, StateType::One);
+            gate.set_input(1, clock);
+            gate.evaluate();
+        }
+        assert_eq!(gate.get_outputs(), &[StateType::One]);
+
+        // A fresh copy only holds the stored bit once the state is loaded
+        let mut copy = SubcircuitGate::new("reg".to_string(), register(), 1);
+        copy.load_state(&gate.save_state());
+        copy.tick(15);
+        copy.set_input(0, StateType::Zero);
+        copy.set_input(1, StateType::Zero);
+        assert_eq!(copy.evaluate().outputs, vec![StateType::One]);
+    }
+}
@@ -2,46 +2,471 @@
 //!
 //! High-performance digital logic simulation engine compiled to WebAssembly.
 
-mod simulation;
-mod gates;
+pub mod simulation;
+pub mod gates;
+#[cfg(test)]
+mod test_support;
+
+use std::cell::{Cell, RefCell};
+use std::collections::BTreeMap;
+use std::rc::Rc;
 
 use wasm_bindgen::prelude::*;
 use serde::{Deserialize, Serialize};
-use simulation::engine::SimulationEngine;
+use simulation::engine::{EdgeKind, FloatingInputPolicy, SimulationEngine};
+use simulation::error::SimError;
+use gates::gate::{DelayCorner, ResetMode};
+use gates::state::{StateType, UnknownPolicy, WireResolution};
 
 /// Gate state representation for JS interop
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct GateState {
     pub id: String,
     #[serde(rename = "type")]
     pub gate_type: String,
     pub input_states: Vec<u8>,
     pub output_states: Vec<u8>,
+    /// Propagation delay in time units (defaults to 1)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delay: Option<u64>,
+    /// Delay for outputs rising to ONE (defaults to `delay`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rise_delay: Option<u64>,
+    /// Delay for outputs falling to ZERO (defaults to `delay`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fall_delay: Option<u64>,
+    /// Delay for every output at the fast corner (defaults to the typical
+    /// delays above)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_delay: Option<u64>,
+    /// Delay for every output at the slow corner (defaults to the typical
+    /// delays above)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_delay: Option<u64>,
+    /// Per-input inversion bubbles (true = port is inverted)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inverted_inputs: Option<Vec<bool>>,
+    /// Count modulus for COUNTER gates (defaults to 2^width)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modulus: Option<u64>,
+    /// Initial contents for memory-like gates (e.g. ROM words), or
+    /// configuration words (RANGE bounds, DIVIDER divisor, SCHMITT
+    /// evaluations a new input must persist for, DEBOUNCE time units further
+    /// transitions are ignored after an accepted one)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub init_data: Option<Vec<u32>>,
+    /// Output pulse length in time units (ONESHOT)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pulse_width: Option<u64>,
+    /// Decoded segment states [a..g] for display gates (snapshot only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub segments: Option<Vec<u8>>,
+    /// Inner circuit of a SUBCIRCUIT gate
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subcircuit: Option<SubcircuitDefinition>,
+    /// Module the gate belongs to (e.g. "ALU"), for fetching one part of a
+    /// large design with `get_group_snapshot`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+}
+
+/// Inner circuit of a hierarchical SUBCIRCUIT gate
+///
+/// External input `i` drives the output of inner gate `input_ids[i]`
+/// (normally a TOGGLE). External outputs are the observed states of the
+/// `output_ids` gates in order: their outputs, or their inputs for gates
+/// without outputs such as LEDs.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SubcircuitDefinition {
+    pub gates: Vec<GateState>,
+    pub wires: Vec<WireState>,
+    pub input_ids: Vec<String>,
+    pub output_ids: Vec<String>,
+}
+
+/// Wire value: a scalar for single-bit wires, one entry per bit for buses
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(untagged)]
+pub enum WireValue {
+    Scalar(u8),
+    Bus(Vec<u8>),
+}
+
+impl WireValue {
+    /// Expand into per-bit states for a wire of the given width
+    pub fn to_states(&self, width: usize) -> Vec<StateType> {
+        match self {
+            WireValue::Scalar(value) => vec![StateType::from_u8(*value); width],
+            WireValue::Bus(values) => {
+                let mut states: Vec<StateType> = values.iter().map(|&v| StateType::from_u8(v)).collect();
+                states.resize(width, StateType::Unknown);
+                states
+            }
+        }
+    }
+
+    /// Build from per-bit states (width 1 serializes as a scalar)
+    pub fn from_states(states: &[StateType]) -> Self {
+        if states.len() == 1 {
+            WireValue::Scalar(states[0].to_u8())
+        } else {
+            WireValue::Bus(states.iter().map(|s| s.to_u8()).collect())
+        }
+    }
+}
+
+fn default_wire_width() -> u32 {
+    1
+}
+
+fn is_single_bit(width: &u32) -> bool {
+    *width == 1
+}
+
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+fn is_zero(value: &u64) -> bool {
+    *value == 0
+}
+
+fn is_standard_resolution(resolution: &WireResolution) -> bool {
+    *resolution == WireResolution::Standard
 }
 
 /// Wire state representation for JS interop
-#[derive(Serialize, Deserialize)]
+///
+/// A wire of width N connects source ports `source_port_index..+N` to target
+/// ports `target_port_index..+N`, bit 0 being the lowest port.
+///
+/// A `bidirectional` wire instead joins two inout pins: both port indices
+/// name the pins' drive outputs, every pin on the net drives it and senses
+/// the resolved value (see `Gate::inout_input`).
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct WireState {
     pub id: String,
-    pub state: u8,
+    pub state: WireValue,
+    #[serde(default = "default_wire_width", skip_serializing_if = "is_single_bit")]
+    pub width: u32,
     pub source_gate_id: String,
     pub source_port_index: u32,
     pub target_gate_id: String,
     pub target_port_index: u32,
+    /// Optional signal name for probing (labels need not be unique)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// Joins two inout pins rather than an output to an input
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub bidirectional: bool,
+    /// Extra time units a change takes to reach the target, on top of the
+    /// source gate's delay (ignored for bidirectional wires)
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub delay: u64,
+    /// How this wire's value combines with other wires driving the same
+    /// input port; a net uses the first non-standard policy by wire ID
+    #[serde(default, skip_serializing_if = "is_standard_resolution")]
+    pub resolution: WireResolution,
 }
 
 /// Simulation snapshot for JS interop
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct SimulationSnapshot {
     pub time: u64,
     pub gates: Vec<GateState>,
     pub wires: Vec<WireState>,
 }
 
+impl SimulationSnapshot {
+    /// Compare against `other`, listing every gate and wire (by ID) whose
+    /// state differs or that exists in only one of the snapshots
+    pub fn diff(&self, other: &SimulationSnapshot) -> SnapshotDiff {
+        SnapshotDiff {
+            time: (self.time != other.time).then_some((self.time, other.time)),
+            gates: diff_by_id(&self.gates, &other.gates, |g| &g.id)
+                .into_iter()
+                .map(|(id, before, after)| GateDiff { id, before, after })
+                .collect(),
+            wires: diff_by_id(&self.wires, &other.wires, |w| &w.id)
+                .into_iter()
+                .map(|(id, before, after)| WireDiff { id, before, after })
+                .collect(),
+        }
+    }
+}
+
+/// Pair up items by ID and keep the pairs that are not equal, sorted by ID
+fn diff_by_id<T: Clone + PartialEq>(
+    before: &[T],
+    after: &[T],
+    id: impl Fn(&T) -> &String,
+) -> Vec<(String, Option<T>, Option<T>)> {
+    let mut pairs: BTreeMap<&String, (Option<&T>, Option<&T>)> = BTreeMap::new();
+    for item in before {
+        pairs.entry(id(item)).or_default().0 = Some(item);
+    }
+    for item in after {
+        pairs.entry(id(item)).or_default().1 = Some(item);
+    }
+    pairs
+        .into_iter()
+        .filter(|(_, (a, b))| a != b)
+        .map(|(id, (a, b))| (id.clone(), a.cloned(), b.cloned()))
+        .collect()
+}
+
+/// Differences between two snapshots, from `diff`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SnapshotDiff {
+    /// `(before, after)` times when they differ
+    pub time: Option<(u64, u64)>,
+    pub gates: Vec<GateDiff>,
+    pub wires: Vec<WireDiff>,
+}
+
+impl SnapshotDiff {
+    /// True if the snapshots have identical gates and wires (time is ignored)
+    pub fn is_empty(&self) -> bool {
+        self.gates.is_empty() && self.wires.is_empty()
+    }
+}
+
+/// A gate that differs between snapshots; `None` where it is absent
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct GateDiff {
+    pub id: String,
+    pub before: Option<GateState>,
+    pub after: Option<GateState>,
+}
+
+/// A wire that differs between snapshots; `None` where it is absent
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct WireDiff {
+    pub id: String,
+    pub before: Option<WireState>,
+    pub after: Option<WireState>,
+}
+
+/// One input combination of a truth table
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct TruthTableRow {
+    pub inputs: Vec<u8>,
+    pub outputs: Vec<u8>,
+    /// False if the circuit did not settle for this combination
+    pub stable: bool,
+}
+
+/// Truth table over a set of TOGGLE inputs and observed output gates
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct TruthTable {
+    pub input_ids: Vec<String>,
+    pub output_ids: Vec<String>,
+    pub rows: Vec<TruthTableRow>,
+}
+
+/// Outcome of one stuck-at fault in a fault campaign
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct FaultResult {
+    pub gate_id: String,
+    pub port: usize,
+    /// Level the output was stuck at (0 or 1)
+    pub stuck_at: u8,
+    /// Index of the first test vector whose outputs differed from the
+    /// fault-free circuit, or None if the fault went undetected
+    pub detected_by: Option<usize>,
+}
+
+/// Stuck-at faults on every gate output and which ones the vectors detect
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct FaultReport {
+    pub faults: Vec<FaultResult>,
+    pub detected: usize,
+    /// Fraction of faults detected (1.0 when there are no faults)
+    pub coverage: f64,
+}
+
+/// Longest-delay path found by `compute_critical_path`
+///
+/// Empty, with zero delay, if no end gate is reachable from a start gate.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PathReport {
+    /// Gate IDs from the start gate to the end gate
+    pub path: Vec<String>,
+    /// Time at which a change reaches each gate on the path, relative to
+    /// the start gate switching
+    pub arrival_times: Vec<u64>,
+    pub total_delay: u64,
+}
+
+/// Assertion whose wire did not hold the expected state at its time
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct AssertionFailure {
+    pub wire_id: String,
+    pub time: u64,
+    pub expected: u8,
+    /// State the wire held, or None if it no longer exists
+    pub actual: Option<WireValue>,
+}
+
+/// Gate output that changed more than once before the circuit settled
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct GlitchReport {
+    pub gate_id: String,
+    pub port_index: u32,
+    /// Time of the first transition in the window
+    pub start_time: u64,
+    /// Time the circuit settled
+    pub end_time: u64,
+    /// Output value before the window followed by every transition
+    pub values: Vec<u8>,
+}
+
+/// What processing one queued event did (see `step_single_event`)
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct EventReport {
+    pub time: u64,
+    pub gate_id: String,
+    /// False if the gate was disabled or no longer exists, or the event only
+    /// delivered a fed-back input
+    pub evaluated: bool,
+    /// Gate inputs the evaluation saw
+    pub inputs: Vec<u8>,
+    pub previous_outputs: Vec<u8>,
+    pub outputs: Vec<u8>,
+    /// Output ports whose state changed
+    pub changed_outputs: Vec<u32>,
+    /// Wire whose breakpoint this event fired, as for `step`
+    pub breakpoint_hit: Option<String>,
+}
+
+/// A source driving a contended net and the state it drives
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ConflictDriver {
+    pub gate_id: String,
+    pub port_index: u32,
+    pub state: u8,
+}
+
+/// Net whose drivers contend, resolving to CONFLICT
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ConflictInfo {
+    /// Wires making up the net
+    pub wire_ids: Vec<String>,
+    /// Sources actively driving the net (HiZ drivers are omitted)
+    pub drivers: Vec<ConflictDriver>,
+    /// Time the contention began
+    pub time: u64,
+}
+
+/// Output toggles of one gate, for dynamic power estimation
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct GateActivity {
+    pub gate_id: String,
+    pub gate_type: String,
+    pub transitions: u64,
+}
+
+/// Switching activity per gate since initialize/reset, busiest gate first
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ActivityReport {
+    /// Simulation time the report covers up to
+    pub time: u64,
+    pub total_transitions: u64,
+    pub gates: Vec<GateActivity>,
+}
+
+/// Size and health of the loaded circuit, for status displays
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct CircuitSummary {
+    pub gate_count: usize,
+    /// Number of gates of each type, keyed by type name
+    pub gate_counts: BTreeMap<String, usize>,
+    pub wire_count: usize,
+    /// Interactive sources (TOGGLE, CLOCK, PULSE)
+    pub input_count: usize,
+    /// LEDs
+    pub output_count: usize,
+    pub has_conflicts: bool,
+    pub has_floating_inputs: bool,
+}
+
+/// Value of a traced gate or wire from `time` until the next sample
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct TraceSample {
+    pub time: u64,
+    /// Gate outputs or wire bits
+    pub values: Vec<u8>,
+}
+
+/// Gate state including internal (non-observable) state, for save/restore
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct GateSnapshot {
+    pub id: String,
+    pub input_states: Vec<u8>,
+    pub output_states: Vec<u8>,
+    pub internal_state: Vec<u64>,
+}
+
+/// Upcoming event, for visualizing the scheduler
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PendingEvent {
+    pub time: u64,
+    pub gate_id: String,
+    /// Input port a fed-back value is delivered to, or None for a full
+    /// evaluation of the gate
+    pub port: Option<u32>,
+}
+
+/// Pending simulation event, for save/restore
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct EventSnapshot {
+    pub time: u64,
+    pub gate_id: String,
+    pub port_index: i32,
+    pub new_state: u8,
+}
+
+/// Simulation state: restoring it onto the same circuit resumes the
+/// simulation exactly where it was saved
+///
+/// Covers gate and wire states, pending events, a running power-on reset
+/// and the user's forced outputs and disabled gates. Observations of the run (trace, history,
+/// transition counts, conflicts, assertions) and settings (breakpoints,
+/// policies) are not part of it.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct EngineSnapshot {
+    pub time: u64,
+    pub gates: Vec<GateSnapshot>,
+    pub wires: Vec<WireState>,
+    pub events: Vec<EventSnapshot>,
+    /// Forced outputs as (gate ID, port, state), sorted
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub forced_outputs: Vec<(String, usize, u8)>,
+    /// Disabled gate IDs, sorted
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub disabled_gates: Vec<String>,
+    /// Time a running power-on reset releases its hold (see `power_on_reset`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub power_on_reset_until: Option<u64>,
+}
+
 /// WASM-exposed simulation engine wrapper
 #[wasm_bindgen]
 pub struct WasmSimulation {
-    engine: SimulationEngine,
+    /// Shared with the `run_with_callback` animation loop
+    engine: Rc<RefCell<SimulationEngine>>,
+    /// Bumped by every `run_with_callback` so a replaced loop stops itself
+    animation_generation: Rc<Cell<u32>>,
+}
+
+/// Animation frame closure that re-requests itself; emptied to end the loop
+type FrameCallback = Rc<RefCell<Option<Closure<dyn FnMut()>>>>;
+
+/// Schedule `callback` for the browser's next animation frame
+fn request_animation_frame(callback: &Closure<dyn FnMut()>) -> Result<i32, JsValue> {
+    web_sys::window()
+        .ok_or_else(|| JsValue::from_str("requestAnimationFrame needs a browser window"))?
+        .request_animation_frame(callback.as_ref().unchecked_ref())
 }
 
 #[wasm_bindgen]
@@ -53,72 +478,686 @@ impl WasmSimulation {
         console_error_panic_hook::set_once();
 
         WasmSimulation {
-            engine: SimulationEngine::new(),
+            engine: Rc::new(RefCell::new(SimulationEngine::new())),
+            animation_generation: Rc::new(Cell::new(0)),
         }
     }
 
     /// Initialize simulation with gates and wires
+    ///
+    /// Returns an array of wiring errors (empty when every wire is valid).
     #[wasm_bindgen]
-    pub fn initialize(&mut self, gates_js: JsValue, wires_js: JsValue) -> Result<(), JsValue> {
+    pub fn initialize(&mut self, gates_js: JsValue, wires_js: JsValue) -> Result<JsValue, JsValue> {
         let gates: Vec<GateState> = serde_wasm_bindgen::from_value(gates_js)
             .map_err(|e| JsValue::from_str(&format!("Failed to parse gates: {}", e)))?;
         let wires: Vec<WireState> = serde_wasm_bindgen::from_value(wires_js)
             .map_err(|e| JsValue::from_str(&format!("Failed to parse wires: {}", e)))?;
 
-        self.engine.initialize(gates, wires);
+        let errors = self.engine.borrow_mut().initialize(gates, wires);
+        serde_wasm_bindgen::to_value(&errors)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize wire errors: {}", e)))
+    }
+
+    /// Add (or replace) a single gate without re-initializing
+    #[wasm_bindgen]
+    pub fn add_gate(&mut self, gate_js: JsValue) -> Result<(), JsValue> {
+        let gate: GateState = serde_wasm_bindgen::from_value(gate_js)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse gate: {}", e)))?;
+        self.engine.borrow_mut().add_gate(gate);
+        Ok(())
+    }
+
+    /// Add (or replace) a single wire without re-initializing
+    #[wasm_bindgen]
+    pub fn add_wire(&mut self, wire_js: JsValue) -> Result<(), JsValue> {
+        let wire: WireState = serde_wasm_bindgen::from_value(wire_js)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse wire: {}", e)))?;
+        self.engine.borrow_mut().add_wire(wire);
         Ok(())
     }
 
-    /// Run a single simulation step
+    /// Replace the circuit with one described by a JSON netlist
+    ///
+    /// Returns an array of wiring errors (empty when every wire is valid).
+    #[wasm_bindgen]
+    pub fn load_netlist(&mut self, json: &str) -> Result<JsValue, JsValue> {
+        let errors = self.engine.borrow_mut()
+            .import_netlist(json)
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+        serde_wasm_bindgen::to_value(&errors)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize wire errors: {}", e)))
+    }
+
+    /// Start recording stimuli and steps into a fresh command log, or stop
+    #[wasm_bindgen]
+    pub fn set_command_logging(&mut self, enabled: bool) {
+        self.engine.borrow_mut().set_command_logging(enabled);
+    }
+
+    /// Get the recorded command log as JSON, for attaching to bug reports
+    #[wasm_bindgen]
+    pub fn export_command_log(&self) -> String {
+        self.engine.borrow().export_command_log()
+    }
+
+    /// Re-issue a recorded command log against the loaded circuit
+    #[wasm_bindgen]
+    pub fn replay_command_log(&mut self, log: &str) -> Result<(), JsValue> {
+        self.engine.borrow_mut()
+            .replay_command_log(log)
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Run `count` simulation steps; returns the total number of events processed
     #[wasm_bindgen]
-    pub fn step(&mut self, count: u32) {
+    pub fn step(&mut self, count: u32) -> u32 {
+        let mut events_processed: u32 = 0;
         for _ in 0..count {
-            self.engine.step();
+            events_processed = events_processed.saturating_add(self.engine.borrow_mut().step());
         }
+        events_processed
+    }
+
+    /// Process exactly one queued event; returns what happened, or null when
+    /// no events are pending
+    #[wasm_bindgen]
+    pub fn step_single_event(&mut self) -> Result<JsValue, JsValue> {
+        serde_wasm_bindgen::to_value(&self.engine.borrow_mut().step_single_event())
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize event: {}", e)))
+    }
+
+    /// Advance simulated time by `time_units`, processing every event due
+    /// along the way; returns the number of events processed
+    #[wasm_bindgen]
+    pub fn advance_by(&mut self, time_units: u64) -> u32 {
+        self.engine.borrow_mut().advance_by(time_units)
+    }
+
+    /// Disable a gate (outputs float at HiZ, evaluation skipped) or re-enable it
+    #[wasm_bindgen]
+    pub fn set_gate_enabled(&mut self, gate_id: &str, enabled: bool) -> Result<(), JsValue> {
+        self.engine.borrow_mut()
+            .set_gate_enabled(gate_id, enabled)
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Hold a gate output at a fixed state (e.g. a stuck-at fault) until released
+    #[wasm_bindgen]
+    pub fn force_output(&mut self, gate_id: &str, port: usize, value: u8) -> Result<(), JsValue> {
+        self.engine.borrow_mut()
+            .force_output(gate_id, port, StateType::from_u8(value))
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Return a forced output to the gate's own logic
+    #[wasm_bindgen]
+    pub fn release_output(&mut self, gate_id: &str, port: usize) -> Result<(), JsValue> {
+        self.engine.borrow_mut()
+            .release_output(gate_id, port)
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Pause stepping when a wire makes a transition: 0 = rising, 1 = falling, 2 = any
+    #[wasm_bindgen]
+    pub fn set_breakpoint(&mut self, wire_id: String, on: u8) -> Result<(), JsValue> {
+        self.engine.borrow_mut()
+            .set_breakpoint(wire_id, EdgeKind::from_u8(on))
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Remove the breakpoint on a wire
+    #[wasm_bindgen]
+    pub fn clear_breakpoint(&mut self, wire_id: &str) {
+        self.engine.borrow_mut().clear_breakpoint(wire_id);
+    }
+
+    /// Wire whose breakpoint stopped the last step, if any
+    #[wasm_bindgen]
+    pub fn breakpoint_hit(&self) -> Option<String> {
+        self.engine.borrow().breakpoint_hit().map(str::to_string)
+    }
+
+    /// Get the number of inputs driven by a gate output port
+    #[wasm_bindgen]
+    pub fn get_fanout(&self, gate_id: &str, port: u32) -> usize {
+        self.engine.borrow().get_fanout(gate_id, port)
+    }
+
+    /// Set the fan-out above which outputs are reported as violations
+    #[wasm_bindgen]
+    pub fn set_fanout_limit(&mut self, limit: usize) {
+        self.engine.borrow_mut().set_fanout_limit(limit);
+    }
+
+    /// Get [gate_id, port, fanout] for every output over the fan-out limit
+    #[wasm_bindgen]
+    pub fn get_fanout_violations(&self) -> Result<JsValue, JsValue> {
+        serde_wasm_bindgen::to_value(&self.engine.borrow().get_fanout_violations())
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize fan-out violations: {}", e)))
+    }
+
+    /// Get the number of events waiting in the queue
+    #[wasm_bindgen]
+    pub fn get_queue_length(&self) -> usize {
+        self.engine.borrow().queue_length()
+    }
+
+    /// Keep the wire states passed to `initialize` through time 0 so seeded
+    /// sequential circuits power on in a defined state (also applies on reset)
+    #[wasm_bindgen]
+    pub fn set_initial_hold(&mut self, enabled: bool) {
+        self.engine.borrow_mut().set_initial_hold(enabled);
+    }
+
+    /// Limit the number of events processed by a single step (default 10000)
+    #[wasm_bindgen]
+    pub fn set_max_events_per_step(&mut self, max_events: usize) {
+        self.engine.borrow_mut().set_max_events_per_step(max_events);
+    }
+
+    /// Get up to `limit` upcoming events in the order they will be processed
+    #[wasm_bindgen]
+    pub fn peek_pending_events(&self, limit: usize) -> Result<JsValue, JsValue> {
+        serde_wasm_bindgen::to_value(&self.engine.borrow().peek_pending_events(limit))
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize pending events: {}", e)))
+    }
+
+    /// Whether the last step stopped at the event limit with events still due
+    #[wasm_bindgen]
+    pub fn last_step_truncated(&self) -> bool {
+        self.engine.borrow().last_step_truncated()
+    }
+
+    /// Step until the circuit settles; returns false if `max_steps` was
+    /// reached first (possible oscillation)
+    #[wasm_bindgen]
+    pub fn step_until_stable(&mut self, max_steps: u64) -> bool {
+        self.engine.borrow_mut().run_until_stable(max_steps)
     }
 
     /// Start continuous simulation
     #[wasm_bindgen]
     pub fn run(&mut self) {
-        self.engine.set_running(true);
+        self.engine.borrow_mut().set_running(true);
+    }
+
+    /// Start continuous simulation driven from `requestAnimationFrame`
+    ///
+    /// Every frame runs `steps_per_tick` steps and then calls `callback` with
+    /// the latest snapshot, until `pause()` is called. Calling this again
+    /// replaces the running loop. If the callback throws, the loop pauses.
+    #[wasm_bindgen]
+    pub fn run_with_callback(&mut self, steps_per_tick: u32, callback: js_sys::Function) -> Result<(), JsValue> {
+        let generation = self.animation_generation.get().wrapping_add(1);
+        self.animation_generation.set(generation);
+        self.engine.borrow_mut().set_running(true);
+
+        let engine = Rc::clone(&self.engine);
+        let current_generation = Rc::clone(&self.animation_generation);
+        let frame: FrameCallback = Rc::new(RefCell::new(None));
+        let next_frame = Rc::clone(&frame);
+        *frame.borrow_mut() = Some(Closure::new(move || {
+            let snapshot = {
+                let mut engine = engine.borrow_mut();
+                if !engine.is_running() || current_generation.get() != generation {
+                    // Dropping the closure ends the loop
+                    next_frame.borrow_mut().take();
+                    return;
+                }
+                for _ in 0..steps_per_tick {
+                    engine.step();
+                }
+                serde_wasm_bindgen::to_value(&engine.get_snapshot()).map_err(JsValue::from)
+            };
+
+            // The engine is released here so the callback may call back in
+            let delivered = snapshot.and_then(|snapshot| callback.call1(&JsValue::NULL, &snapshot));
+            let scheduled = delivered.and_then(|_| match next_frame.borrow().as_ref() {
+                Some(next) => request_animation_frame(next).map(|_| ()),
+                None => Ok(()),
+            });
+            if scheduled.is_err() {
+                engine.borrow_mut().set_running(false);
+                next_frame.borrow_mut().take();
+            }
+        }));
+
+        let started = match frame.borrow().as_ref() {
+            Some(first) => request_animation_frame(first).map(|_| ()),
+            None => Ok(()),
+        };
+        if started.is_err() {
+            self.engine.borrow_mut().set_running(false);
+            frame.borrow_mut().take();
+        }
+        started
     }
 
     /// Pause simulation
     #[wasm_bindgen]
     pub fn pause(&mut self) {
-        self.engine.set_running(false);
+        self.engine.borrow_mut().set_running(false);
     }
 
-    /// Reset simulation to initial state
+    /// Reset simulation to initial state: mode 0 (default) is a full reset,
+    /// 1 keeps memory contents
     #[wasm_bindgen]
-    pub fn reset(&mut self) {
-        self.engine.reset();
+    pub fn reset(&mut self, mode: Option<u8>) {
+        self.engine.borrow_mut().reset(ResetMode::from_u8(mode.unwrap_or(0)));
     }
 
     /// Toggle an input gate
     #[wasm_bindgen]
-    pub fn toggle_input(&mut self, gate_id: &str) {
-        self.engine.toggle_input(gate_id);
+    pub fn toggle_input(&mut self, gate_id: &str) -> Result<(), JsValue> {
+        self.engine.borrow_mut()
+            .toggle_input(gate_id)
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Drive an input gate to an absolute level (0 = ZERO, 1 = ONE)
+    #[wasm_bindgen]
+    pub fn set_input(&mut self, gate_id: &str, value: u8) -> Result<(), JsValue> {
+        self.engine.borrow_mut()
+            .set_input_level(gate_id, StateType::from_u8(value))
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Set several input gates at once from an array of [gate_id, level] pairs
+    #[wasm_bindgen]
+    pub fn set_inputs(&mut self, values_js: JsValue) -> Result<(), JsValue> {
+        let values: Vec<(String, bool)> = serde_wasm_bindgen::from_value(values_js)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse input values: {}", e)))?;
+        self.engine.borrow_mut()
+            .set_inputs(values)
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Remove a gate and all wires connected to it
+    #[wasm_bindgen]
+    pub fn remove_gate(&mut self, gate_id: &str) -> Result<(), JsValue> {
+        self.engine.borrow_mut()
+            .remove_gate(gate_id)
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Set the period of a clock gate
+    #[wasm_bindgen]
+    pub fn set_clock_period(&mut self, gate_id: &str, period: u64) -> Result<(), JsValue> {
+        self.engine.borrow_mut()
+            .set_clock_period(gate_id, period)
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Press a PULSE button, holding its output high for `width` time units
+    #[wasm_bindgen]
+    pub fn fire_pulse(&mut self, gate_id: &str, width: u64) -> Result<(), JsValue> {
+        self.engine.borrow_mut()
+            .fire_pulse(gate_id, width)
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Shift a clock gate's phase by `offset` time units
+    #[wasm_bindgen]
+    pub fn set_clock_phase(&mut self, gate_id: &str, offset: u64) -> Result<(), JsValue> {
+        self.engine.borrow_mut()
+            .set_clock_phase(gate_id, offset)
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Enable or disable recording of glitches (outputs changing more than once before settling)
+    #[wasm_bindgen]
+    pub fn set_glitch_detection(&mut self, enabled: bool) {
+        self.engine.borrow_mut().set_glitch_detection(enabled);
+    }
+
+    /// Get glitches recorded since detection was enabled or last cleared
+    #[wasm_bindgen]
+    pub fn get_glitches(&self) -> Result<JsValue, JsValue> {
+        serde_wasm_bindgen::to_value(self.engine.borrow().get_glitches())
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize glitches: {}", e)))
+    }
+
+    /// Discard recorded glitches
+    #[wasm_bindgen]
+    pub fn clear_glitches(&mut self) {
+        self.engine.borrow_mut().clear_glitches();
+    }
+
+    /// Set gate outputs and wires to a reproducible pseudo-random power-on state
+    #[wasm_bindgen]
+    pub fn randomize_initial_state(&mut self, seed: u64) {
+        self.engine.borrow_mut().randomize_initial_state(seed);
+    }
+
+    /// Reset every sequential gate and hold it in reset for `hold_cycles`
+    /// time units while combinational logic settles
+    #[wasm_bindgen]
+    pub fn power_on_reset(&mut self, hold_cycles: u64) {
+        self.engine.borrow_mut().power_on_reset(hold_cycles);
+    }
+
+    /// Choose how undriven gate inputs read: 0 = unknown (default), 1 = ZERO, 2 = ONE
+    #[wasm_bindgen]
+    pub fn set_floating_input_policy(&mut self, policy: u8) {
+        self.engine.borrow_mut().set_floating_input_policy(FloatingInputPolicy::from_u8(policy));
+    }
+
+    /// Choose how gate logic treats unknown inputs: 0 = pessimistic (default), 1 = optimistic
+    #[wasm_bindgen]
+    pub fn set_unknown_policy(&mut self, policy: u8) {
+        self.engine.borrow_mut().set_unknown_policy(UnknownPolicy::from_u8(policy));
+    }
+
+    /// Choose the delay corner: 0 = typical (default), 1 = min, 2 = max
+    #[wasm_bindgen]
+    pub fn set_delay_corner(&mut self, corner: u8) {
+        self.engine.borrow_mut().set_delay_corner(DelayCorner::from_u8(corner));
+    }
+
+    /// Check that a wire holds `expected` (a state code) at `at_time`
+    #[wasm_bindgen]
+    pub fn add_assertion(&mut self, wire_id: String, expected: u8, at_time: u64) -> Result<(), JsValue> {
+        self.engine
+            .borrow_mut()
+            .add_assertion(wire_id, StateType::from_u8(expected), at_time)
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Get the assertions that have failed so far, in time order
+    #[wasm_bindgen]
+    pub fn get_assertion_failures(&self) -> Result<JsValue, JsValue> {
+        let engine = self.engine.borrow();
+        serde_wasm_bindgen::to_value(engine.get_assertion_failures())
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize assertion failures: {}", e)))
+    }
+
+    /// Get the current state of labelled wires, keyed by label
+    #[wasm_bindgen]
+    pub fn get_probe_values(&self, labels_js: JsValue) -> Result<JsValue, JsValue> {
+        let labels: Vec<String> = serde_wasm_bindgen::from_value(labels_js)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse probe labels: {}", e)))?;
+        let serializer = serde_wasm_bindgen::Serializer::new().serialize_maps_as_objects(true);
+        self.engine.borrow()
+            .get_probe_values(labels)
+            .serialize(&serializer)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize probe values: {}", e)))
+    }
+
+    /// Record the given gate and wire IDs on every change, replacing the
+    /// previous selection
+    #[wasm_bindgen]
+    pub fn trace_signals(&mut self, ids_js: JsValue) -> Result<(), JsValue> {
+        let ids: Vec<String> = serde_wasm_bindgen::from_value(ids_js)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse signal IDs: {}", e)))?;
+        self.engine.borrow_mut().trace_signals(ids);
+        Ok(())
+    }
+
+    /// Get recorded samples as `{ id: [{ time, values }] }`
+    #[wasm_bindgen]
+    pub fn get_trace(&self) -> Result<JsValue, JsValue> {
+        let serializer = serde_wasm_bindgen::Serializer::new().serialize_maps_as_objects(true);
+        self.engine.borrow()
+            .get_trace()
+            .serialize(&serializer)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize trace: {}", e)))
+    }
+
+    /// Discard recorded trace samples
+    #[wasm_bindgen]
+    pub fn clear_trace(&mut self) {
+        self.engine.borrow_mut().clear_trace();
     }
 
     /// Get current simulation state as JSON
     #[wasm_bindgen]
     pub fn get_state(&self) -> Result<JsValue, JsValue> {
-        let snapshot = self.engine.get_snapshot();
+        let snapshot = self.engine.borrow().get_snapshot();
+        serde_wasm_bindgen::to_value(&snapshot)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize state: {}", e)))
+    }
+
+    /// Get the state of one gate, or null if there is no such gate
+    #[wasm_bindgen]
+    pub fn get_gate(&self, gate_id: &str) -> Result<JsValue, JsValue> {
+        match self.engine.borrow().get_gate_state(gate_id) {
+            Some(state) => serde_wasm_bindgen::to_value(&state)
+                .map_err(|e| JsValue::from_str(&format!("Failed to serialize gate: {}", e))),
+            None => Ok(JsValue::NULL),
+        }
+    }
+
+    /// Get the wire from an output port to an input port, or null if none
+    /// connects them (the lowest wire ID wins among parallel wires)
+    #[wasm_bindgen]
+    pub fn get_wire_between(
+        &self,
+        source_gate: &str,
+        source_port: u32,
+        target_gate: &str,
+        target_port: u32,
+    ) -> Result<JsValue, JsValue> {
+        match self.engine.borrow().get_wire_between(source_gate, source_port, target_gate, target_port) {
+            Some(state) => serde_wasm_bindgen::to_value(&state)
+                .map_err(|e| JsValue::from_str(&format!("Failed to serialize wire: {}", e))),
+            None => Ok(JsValue::NULL),
+        }
+    }
+
+    /// Get the state of one gate group: its gates and every wire touching them
+    #[wasm_bindgen]
+    pub fn get_group_snapshot(&self, group: &str) -> Result<JsValue, JsValue> {
+        let snapshot = self.engine.borrow().get_group_snapshot(group);
         serde_wasm_bindgen::to_value(&snapshot)
             .map_err(|e| JsValue::from_str(&format!("Failed to serialize state: {}", e)))
     }
 
+    /// Compare the current state against a snapshot from `get_state`;
+    /// `before` entries come from the given snapshot, `after` from now
+    #[wasm_bindgen]
+    pub fn diff_against(&self, snapshot_js: JsValue) -> Result<JsValue, JsValue> {
+        let snapshot: SimulationSnapshot = serde_wasm_bindgen::from_value(snapshot_js)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse snapshot: {}", e)))?;
+        let diff = snapshot.diff(&self.engine.borrow().get_snapshot());
+        serde_wasm_bindgen::to_value(&diff)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize snapshot diff: {}", e)))
+    }
+
+    /// Export the loaded circuit as a structural Verilog module
+    #[wasm_bindgen]
+    pub fn export_verilog(&self, module_name: &str) -> String {
+        self.engine.borrow().export_verilog(module_name)
+    }
+
+    /// Get a fingerprint of the current simulation state (equal states hash equally)
+    #[wasm_bindgen]
+    pub fn state_hash(&self) -> u64 {
+        self.engine.borrow().state_hash()
+    }
+
+    /// Get IDs of gates detected as oscillating
+    #[wasm_bindgen]
+    pub fn get_unstable_gates(&self) -> Result<JsValue, JsValue> {
+        serde_wasm_bindgen::to_value(&self.engine.borrow().get_unstable_gates())
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize unstable gates: {}", e)))
+    }
+
+    /// Get [gate_id, port_index] pairs for gate inputs that no wire drives
+    #[wasm_bindgen]
+    pub fn get_floating_inputs(&self) -> Result<JsValue, JsValue> {
+        serde_wasm_bindgen::to_value(&self.engine.borrow().get_floating_inputs())
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize floating inputs: {}", e)))
+    }
+
+    /// Save the full simulation state (including internal gate state and pending events)
+    #[wasm_bindgen]
+    pub fn save_state(&self) -> Result<JsValue, JsValue> {
+        serde_wasm_bindgen::to_value(&self.engine.borrow().export_state())
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize engine state: {}", e)))
+    }
+
+    /// Restore a state produced by `save_state` onto the loaded circuit
+    #[wasm_bindgen]
+    pub fn load_state(&mut self, state_js: JsValue) -> Result<(), JsValue> {
+        let snapshot: EngineSnapshot = serde_wasm_bindgen::from_value(state_js)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse engine state: {}", e)))?;
+        self.engine.borrow_mut().import_state(snapshot);
+        Ok(())
+    }
+
+    /// Keep the last `capacity` states for `step_back` (0 disables history)
+    ///
+    /// Every entry is a full engine snapshot, so memory use grows with
+    /// circuit size times capacity.
+    #[wasm_bindgen]
+    pub fn enable_history(&mut self, capacity: usize) {
+        self.engine.borrow_mut().enable_history(capacity);
+    }
+
+    /// Undo the most recent step; returns false if no history is available
+    #[wasm_bindgen]
+    pub fn step_back(&mut self) -> bool {
+        self.engine.borrow_mut().step_back()
+    }
+
+    /// Get a wire's average frequency (cycles per time unit) over the last
+    /// `window` time units; undefined for unknown wires or a zero window
+    #[wasm_bindgen]
+    pub fn measure_frequency(&self, wire_id: &str, window: u64) -> Option<f64> {
+        self.engine.borrow().measure_frequency(wire_id, window)
+    }
+
+    /// Get the number of transitions seen on each wire, keyed by wire ID
+    #[wasm_bindgen]
+    pub fn get_wire_transition_counts(&self) -> Result<JsValue, JsValue> {
+        let serializer = serde_wasm_bindgen::Serializer::new().serialize_maps_as_objects(true);
+        self.engine.borrow()
+            .get_wire_transition_counts()
+            .serialize(&serializer)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize transition counts: {}", e)))
+    }
+
+    /// Get nets whose drivers currently contend, with the drivers' states
+    #[wasm_bindgen]
+    pub fn get_conflicts(&self) -> Result<JsValue, JsValue> {
+        serde_wasm_bindgen::to_value(&self.engine.borrow().get_conflicts())
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize conflicts: {}", e)))
+    }
+
+    /// Get output switching per gate since initialize/reset, busiest gate first
+    #[wasm_bindgen]
+    pub fn get_activity_report(&self) -> Result<JsValue, JsValue> {
+        serde_wasm_bindgen::to_value(&self.engine.borrow().get_activity_report())
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize activity report: {}", e)))
+    }
+
+    /// Get gate and wire counts and whether the circuit has conflicts or
+    /// floating inputs, in one call
+    #[wasm_bindgen]
+    pub fn get_summary(&self) -> Result<JsValue, JsValue> {
+        let serializer = serde_wasm_bindgen::Serializer::new().serialize_maps_as_objects(true);
+        self.engine.borrow()
+            .get_summary()
+            .serialize(&serializer)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize summary: {}", e)))
+    }
+
+    /// Enumerate all input combinations of the given TOGGLE gates and record
+    /// the settled values of the given output gates
+    #[wasm_bindgen]
+    pub fn compute_truth_table(&mut self, input_ids_js: JsValue, output_ids_js: JsValue) -> Result<JsValue, JsValue> {
+        let input_ids: Vec<String> = serde_wasm_bindgen::from_value(input_ids_js)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse input IDs: {}", e)))?;
+        let output_ids: Vec<String> = serde_wasm_bindgen::from_value(output_ids_js)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse output IDs: {}", e)))?;
+        let table = self
+            .engine
+            .borrow_mut()
+            .compute_truth_table(input_ids, output_ids)
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+        serde_wasm_bindgen::to_value(&table)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize truth table: {}", e)))
+    }
+
+    /// Hold a gate output stuck at 0 or 1 until released
+    #[wasm_bindgen]
+    pub fn inject_stuck_at(&mut self, gate_id: &str, port: usize, value: bool) -> Result<(), JsValue> {
+        self.engine.borrow_mut()
+            .inject_stuck_at(gate_id, port, value)
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Grade test vectors against every stuck-at fault
+    ///
+    /// Each vector is a number whose bits set the given input gates, the
+    /// first input being the most significant bit (as in truth table rows).
+    #[wasm_bindgen]
+    pub fn run_fault_campaign(&mut self, input_ids_js: JsValue, vectors_js: JsValue, output_ids_js: JsValue) -> Result<JsValue, JsValue> {
+        let input_ids: Vec<String> = serde_wasm_bindgen::from_value(input_ids_js)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse input IDs: {}", e)))?;
+        let vectors: Vec<u32> = serde_wasm_bindgen::from_value(vectors_js)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse test vectors: {}", e)))?;
+        let output_ids: Vec<String> = serde_wasm_bindgen::from_value(output_ids_js)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse output IDs: {}", e)))?;
+        let max = u32::BITS as usize;
+        if input_ids.len() > max {
+            return Err(JsValue::from_str(&SimError::TooManyInputs { max, got: input_ids.len() }.to_string()));
+        }
+
+        let test_vectors = vectors
+            .iter()
+            .map(|&vector| {
+                input_ids
+                    .iter()
+                    .enumerate()
+                    .map(|(i, id)| (id.clone(), vector & (1 << (input_ids.len() - 1 - i)) != 0))
+                    .collect()
+            })
+            .collect();
+        let report = self
+            .engine
+            .borrow_mut()
+            .run_fault_campaign(test_vectors, output_ids)
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+        serde_wasm_bindgen::to_value(&report)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize fault report: {}", e)))
+    }
+
+    /// Find the longest-delay path from any of the start gates to any of
+    /// the end gates (see `PathReport`)
+    #[wasm_bindgen]
+    pub fn compute_critical_path(&self, from_js: JsValue, to_js: JsValue) -> Result<JsValue, JsValue> {
+        let from: Vec<String> = serde_wasm_bindgen::from_value(from_js)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse start gate IDs: {}", e)))?;
+        let to: Vec<String> = serde_wasm_bindgen::from_value(to_js)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse end gate IDs: {}", e)))?;
+        let report = self
+            .engine
+            .borrow()
+            .compute_critical_path(from, to)
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+        serde_wasm_bindgen::to_value(&report)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize path report: {}", e)))
+    }
+
+    /// Get only the gates and wires that changed since the last delta read
+    #[wasm_bindgen]
+    pub fn get_state_delta(&mut self) -> Result<JsValue, JsValue> {
+        let delta = self.engine.borrow_mut().take_state_delta();
+        serde_wasm_bindgen::to_value(&delta)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize state delta: {}", e)))
+    }
+
     /// Get current simulation time
     #[wasm_bindgen]
     pub fn get_time(&self) -> u64 {
-        self.engine.get_current_time()
+        self.engine.borrow().get_current_time()
     }
 
     /// Check if simulation is running
     #[wasm_bindgen]
     pub fn is_running(&self) -> bool {
-        self.engine.is_running()
+        self.engine.borrow().is_running()
     }
 }
 
@@ -1,22 +1,417 @@
 //! Core simulation engine
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 
-use crate::gates::basic::create_gate;
-use crate::gates::gate::Gate;
-use crate::gates::state::{resolve_wire_state, StateType};
-use crate::{GateState, SimulationSnapshot, WireState};
+use crate::gates::basic::{create_gate, with_inverted_inputs, with_timing, GateParams, GateTiming};
+use crate::gates::gate::{DelayCorner, Gate, GateResult, ResetMode};
+use crate::gates::state::{resolve_wire_state, StateType, UnknownPolicy, WireResolution};
+use crate::gates::subcircuit::SubcircuitGate;
+use crate::{
+    ActivityReport, AssertionFailure, CircuitSummary, ConflictDriver, ConflictInfo, EngineSnapshot, EventReport, EventSnapshot, FaultReport, FaultResult, GateActivity, GateSnapshot, GateState, GlitchReport, PathReport, PendingEvent, SimulationSnapshot,
+    TraceSample, TruthTable, TruthTableRow, WireState, WireValue,
+};
 
+use super::error::{SimError, WireError};
 use super::event_queue::EventQueue;
+use super::replay::{Command, LoggedCommand};
 
-/// Wire representation
+/// Evaluations without the circuit settling before a gate is flagged unstable
+const OSCILLATION_THRESHOLD: u32 = 1000;
+
+/// Default limit on events processed by a single step
+const DEFAULT_MAX_EVENTS_PER_STEP: usize = 10000;
+
+/// Recent transition timestamps kept per wire for frequency measurement
+const TRANSITION_HISTORY_LIMIT: usize = 1024;
+
+/// Maximum number of inputs accepted by truth table generation
+const MAX_TRUTH_TABLE_INPUTS: usize = 16;
+
+/// Steps allowed per truth table row before it is marked unstable
+const TRUTH_TABLE_SETTLE_STEPS: u64 = 1000;
+
+/// Default number of inputs one output may drive (standard TTL fan-out)
+const DEFAULT_FANOUT_LIMIT: usize = 10;
+
+/// Time until which seeded wire states are held (see `set_initial_hold`)
+const INITIAL_HOLD_TIME: u64 = 1;
+
+/// Transitions after which an unsettled glitch window is reported anyway
+const GLITCH_WINDOW_LIMIT: usize = 64;
+
+/// Wire representation (one state per bit; width 1 for plain wires)
 struct Wire {
     id: String,
-    state: StateType,
+    state: Vec<StateType>,
     source_gate_id: String,
     source_port_index: u32,
     target_gate_id: String,
     target_port_index: u32,
+    label: Option<String>,
+    /// Joins two inout pins (see `WireState::bidirectional`)
+    bidirectional: bool,
+    /// Propagation delay added to the source gate's (see `WireState::delay`)
+    delay: u64,
+    /// How the wire combines with others on its target net
+    resolution: WireResolution,
+    /// Feeds a gate's output back into its own input; changes are delivered
+    /// as queued port events so the gate never sees its own output within
+    /// the evaluation that produced it
+    self_loop: bool,
+    transition_count: u64,
+    /// Times of the most recent transitions, oldest first
+    transition_times: VecDeque<u64>,
+}
+
+impl Wire {
+    fn from_state(wire_state: WireState) -> Self {
+        let width = wire_state.width.max(1) as usize;
+        let self_loop = !wire_state.bidirectional && wire_state.source_gate_id == wire_state.target_gate_id;
+        Wire {
+            id: wire_state.id,
+            state: wire_state.state.to_states(width),
+            source_gate_id: wire_state.source_gate_id,
+            source_port_index: wire_state.source_port_index,
+            target_gate_id: wire_state.target_gate_id,
+            target_port_index: wire_state.target_port_index,
+            label: wire_state.label,
+            bidirectional: wire_state.bidirectional,
+            resolution: wire_state.resolution,
+            delay: wire_state.delay,
+            self_loop,
+            transition_count: 0,
+            transition_times: VecDeque::new(),
+        }
+    }
+
+    fn width(&self) -> u32 {
+        self.state.len() as u32
+    }
+
+    /// Bit of this wire carrying the given source port, if covered
+    fn source_bit(&self, gate_id: &str, port: u32) -> Option<usize> {
+        if !self.bidirectional
+            && self.source_gate_id == gate_id
+            && port >= self.source_port_index
+            && port - self.source_port_index < self.width()
+        {
+            Some((port - self.source_port_index) as usize)
+        } else {
+            None
+        }
+    }
+
+    /// Bit of this wire driving the given target port, if covered
+    fn target_bit(&self, gate_id: &str, port: u32) -> Option<usize> {
+        if !self.bidirectional
+            && self.target_gate_id == gate_id
+            && port >= self.target_port_index
+            && port - self.target_port_index < self.width()
+        {
+            Some((port - self.target_port_index) as usize)
+        } else {
+            None
+        }
+    }
+
+    /// Bit of a bidirectional wire attached to the given inout pin (named by
+    /// its drive output port) at either end, if covered
+    fn pin_bit(&self, gate_id: &str, port: u32) -> Option<usize> {
+        if !self.bidirectional {
+            return None;
+        }
+        [(&self.source_gate_id, self.source_port_index), (&self.target_gate_id, self.target_port_index)]
+            .into_iter()
+            .find(|&(id, first)| id == gate_id && port >= first && port - first < self.width())
+            .map(|(_, first)| (port - first) as usize)
+    }
+
+    /// The two inout pins joined by one bit of a bidirectional wire
+    fn pins(&self, bit: usize) -> [(String, u32); 2] {
+        [
+            (self.source_gate_id.clone(), self.source_port_index.saturating_add(bit as u32)),
+            (self.target_gate_id.clone(), self.target_port_index.saturating_add(bit as u32)),
+        ]
+    }
+}
+
+/// Wire IDs attached to each port of each gate at one end of the wires, so
+/// lookups cost O(wires on the port, or on the gate) rather than O(all wires)
+#[derive(Default)]
+struct PortIndex {
+    wires: HashMap<String, HashMap<u32, Vec<String>>>,
+}
+
+impl PortIndex {
+    /// Record a wire covering ports `first_port..first_port + width`
+    /// (clipped at the largest port number)
+    fn insert(&mut self, gate_id: &str, first_port: u32, width: u32, wire_id: &str) {
+        let ports = self.wires.entry(gate_id.to_string()).or_default();
+        for port in first_port..first_port.saturating_add(width) {
+            ports.entry(port).or_default().push(wire_id.to_string());
+        }
+    }
+
+    /// Forget a wire recorded by `insert` with the same ports
+    fn remove(&mut self, gate_id: &str, first_port: u32, width: u32, wire_id: &str) {
+        let Some(ports) = self.wires.get_mut(gate_id) else { return };
+        for port in first_port..first_port.saturating_add(width) {
+            if let Some(ids) = ports.get_mut(&port) {
+                ids.retain(|id| id != wire_id);
+                if ids.is_empty() {
+                    ports.remove(&port);
+                }
+            }
+        }
+        if ports.is_empty() {
+            self.wires.remove(gate_id);
+        }
+    }
+
+    /// IDs of the wires attached to a port
+    fn get(&self, gate_id: &str, port: u32) -> &[String] {
+        self.wires
+            .get(gate_id)
+            .and_then(|ports| ports.get(&port))
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Ports of a gate that have wires attached, each with its wire IDs
+    fn ports_of(&self, gate_id: &str) -> impl Iterator<Item = (u32, &[String])> {
+        self.wires
+            .get(gate_id)
+            .into_iter()
+            .flatten()
+            .map(|(&port, ids)| (port, ids.as_slice()))
+    }
+
+    fn clear(&mut self) {
+        self.wires.clear();
+    }
+}
+
+/// Wire IDs of a net and its drivers as (source gate, port, driven state)
+type NetDrivers = (Vec<String>, Vec<(String, u32, StateType)>);
+
+/// Identifies a net for conflict tracking: the input port it feeds, or for
+/// a bidirectional net its lowest inout pin
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum NetKey {
+    Input(String, u32),
+    Pin(String, u32),
+}
+
+impl NetKey {
+    fn gate_id(&self) -> &str {
+        match self {
+            NetKey::Input(gate_id, _) | NetKey::Pin(gate_id, _) => gate_id,
+        }
+    }
+}
+
+/// What a run has recorded besides its simulation state (see
+/// `EngineSnapshot`), set aside while the engine probes the circuit
+struct RunRecords {
+    command_log: Option<Vec<LoggedCommand>>,
+    history: Option<VecDeque<EngineSnapshot>>,
+    traced_signals: Vec<String>,
+    trace: HashMap<String, Vec<TraceSample>>,
+    gate_transition_counts: HashMap<String, u64>,
+    /// Transition count and recent transition times per wire
+    wire_transitions: HashMap<String, (u64, VecDeque<u64>)>,
+    conflicts: HashMap<NetKey, ConflictInfo>,
+    unstable_gates: HashSet<String>,
+    output_transitions: HashMap<(String, u32), (u64, Vec<StateType>)>,
+    glitches: Vec<GlitchReport>,
+    breakpoints: HashMap<String, EdgeKind>,
+    breakpoint_hit: Option<String>,
+    assertions: Vec<(String, StateType, u64)>,
+    assertion_failures: Vec<AssertionFailure>,
+}
+
+/// Create a gate instance from its JS description
+fn build_gate(gate_state: &GateState) -> Box<dyn Gate> {
+    if let Some(definition) = &gate_state.subcircuit {
+        let gate = SubcircuitGate::new(
+            gate_state.id.clone(),
+            definition.clone(),
+            gate_state.delay.unwrap_or(1),
+        );
+        let gate = with_inverted_inputs(Box::new(gate), gate_state.inverted_inputs.clone());
+        let timing = GateTiming {
+            rise_delay: gate_state.rise_delay,
+            fall_delay: gate_state.fall_delay,
+            min_delay: gate_state.min_delay,
+            max_delay: gate_state.max_delay,
+        };
+        return with_timing(gate, timing);
+    }
+
+    let non_empty = |len: usize| if len == 0 { None } else { Some(len) };
+    let params = GateParams {
+        input_count: non_empty(gate_state.input_states.len()),
+        output_count: non_empty(gate_state.output_states.len()),
+        delay: gate_state.delay,
+        rise_delay: gate_state.rise_delay,
+        fall_delay: gate_state.fall_delay,
+        min_delay: gate_state.min_delay,
+        max_delay: gate_state.max_delay,
+        inverted_inputs: gate_state.inverted_inputs.clone(),
+        modulus: gate_state.modulus,
+        init_data: gate_state.init_data.clone(),
+        pulse_width: gate_state.pulse_width,
+    };
+
+    create_gate(&gate_state.gate_type, gate_state.id.clone(), &params)
+}
+
+/// How gate inputs with no active driver (HiZ or unconnected) are read
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FloatingInputPolicy {
+    /// Leave HiZ as is; gate logic treats it as unknown
+    #[default]
+    AsUnknown,
+    /// Floating inputs read as ZERO (pull-down)
+    AsZero,
+    /// Floating inputs read as ONE (pull-up)
+    AsOne,
+}
+
+impl FloatingInputPolicy {
+    pub fn from_u8(value: u8) -> Self {
+        match value {
+            1 => FloatingInputPolicy::AsZero,
+            2 => FloatingInputPolicy::AsOne,
+            _ => FloatingInputPolicy::AsUnknown,
+        }
+    }
+
+    /// Input state seen by a gate for a resolved net state
+    fn apply(self, state: StateType) -> StateType {
+        match (self, state) {
+            (FloatingInputPolicy::AsZero, StateType::HiZ) => StateType::Zero,
+            (FloatingInputPolicy::AsOne, StateType::HiZ) => StateType::One,
+            _ => state,
+        }
+    }
+}
+
+/// Wire transition that triggers a breakpoint
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EdgeKind {
+    /// ZERO to ONE
+    Rising,
+    /// ONE to ZERO
+    Falling,
+    /// Any change of state
+    Any,
+}
+
+impl EdgeKind {
+    pub fn from_u8(value: u8) -> Self {
+        match value {
+            0 => EdgeKind::Rising,
+            1 => EdgeKind::Falling,
+            _ => EdgeKind::Any,
+        }
+    }
+
+    fn matches(self, from: StateType, to: StateType) -> bool {
+        match self {
+            EdgeKind::Rising => from == StateType::Zero && to == StateType::One,
+            EdgeKind::Falling => from == StateType::One && to == StateType::Zero,
+            EdgeKind::Any => from != to,
+        }
+    }
+}
+
+/// 64-bit FNV-1a hash over explicitly encoded bytes, so results do not
+/// depend on the platform's `usize` width or the standard library version
+struct Fnv1a(u64);
+
+impl Fnv1a {
+    fn new() -> Self {
+        Fnv1a(0xcbf2_9ce4_8422_2325)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 ^= byte as u64;
+            self.0 = self.0.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+
+    fn write_u64(&mut self, value: u64) {
+        self.write(&value.to_le_bytes());
+    }
+
+    /// Length-prefixed so adjacent fields cannot run into each other
+    fn write_field(&mut self, bytes: &[u8]) {
+        self.write_u64(bytes.len() as u64);
+        self.write(bytes);
+    }
+}
+
+/// Small xorshift64 generator for reproducible pseudo-random states
+struct XorShift64(u64);
+
+impl XorShift64 {
+    fn new(seed: u64) -> Self {
+        // Zero is a fixed point of xorshift, so mix the seed first
+        XorShift64(seed ^ 0x9E37_79B9_7F4A_7C15)
+    }
+
+    fn next(&mut self) -> u64 {
+        let mut x = self.0;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.0 = x;
+        x
+    }
+
+    fn next_level(&mut self) -> StateType {
+        if self.next() & 1 == 1 { StateType::One } else { StateType::Zero }
+    }
+}
+
+/// Observable state of a gate for JS interop
+fn gate_snapshot(id: &str, gate: &dyn Gate, group: Option<&String>) -> GateState {
+    GateState {
+        id: id.to_string(),
+        gate_type: gate.gate_type().to_string(),
+        input_states: gate.get_inputs().iter().map(|s| s.to_u8()).collect(),
+        output_states: gate.get_outputs().iter().map(|s| s.to_u8()).collect(),
+        delay: None,
+        rise_delay: None,
+        fall_delay: None,
+        min_delay: None,
+        max_delay: None,
+        inverted_inputs: gate.inverted_inputs().map(|mask| mask.to_vec()),
+        modulus: None,
+        init_data: None,
+        pulse_width: None,
+        segments: gate.segments().map(|segments| segments.iter().map(|s| s.to_u8()).collect()),
+        subcircuit: None,
+        group: group.cloned(),
+    }
+}
+
+/// Observable state of a wire for JS interop
+fn wire_snapshot(wire: &Wire) -> WireState {
+    WireState {
+        id: wire.id.clone(),
+        state: WireValue::from_states(&wire.state),
+        width: wire.width(),
+        source_gate_id: wire.source_gate_id.clone(),
+        source_port_index: wire.source_port_index,
+        target_gate_id: wire.target_gate_id.clone(),
+        target_port_index: wire.target_port_index,
+        label: wire.label.clone(),
+        bidirectional: wire.bidirectional,
+        delay: wire.delay,
+        resolution: wire.resolution,
+    }
 }
 
 /// Core simulation engine
@@ -26,6 +421,62 @@ pub struct SimulationEngine {
     event_queue: EventQueue,
     current_time: u64,
     running: bool,
+    evaluation_counts: HashMap<String, u32>,
+    unstable_gates: HashSet<String>,
+    dirty_gates: HashSet<String>,
+    dirty_wires: HashSet<String>,
+    /// Gates whose wires may not carry their current outputs (after loading,
+    /// resetting or replacing them); their next evaluation propagates every
+    /// output rather than only the changed ones
+    unsynced_gates: HashSet<String>,
+    max_events_per_step: usize,
+    last_step_truncated: bool,
+    glitch_detection: bool,
+    /// Output transitions per (gate, port) since the circuit last settled:
+    /// time of the first change and the value sequence including the start
+    output_transitions: HashMap<(String, u32), (u64, Vec<StateType>)>,
+    glitches: Vec<GlitchReport>,
+    /// Snapshots taken before each step, oldest first (None when disabled)
+    history: Option<VecDeque<EngineSnapshot>>,
+    history_capacity: usize,
+    floating_input_policy: FloatingInputPolicy,
+    unknown_policy: UnknownPolicy,
+    delay_corner: DelayCorner,
+    /// Gate or wire IDs recorded into `trace`
+    traced_signals: Vec<String>,
+    /// Recorded value changes per traced signal, oldest first
+    trace: HashMap<String, Vec<TraceSample>>,
+    /// Gates whose evaluation is skipped and whose outputs float at HiZ
+    disabled_gates: HashSet<String>,
+    /// Output ports held at a fixed state whatever the gate computes, by gate
+    forced_outputs: HashMap<String, HashMap<usize, StateType>>,
+    /// Group tag of each gate that has one
+    gate_groups: HashMap<String, String>,
+    breakpoints: HashMap<String, EdgeKind>,
+    /// Wire whose breakpoint fired during the last step or advance
+    breakpoint_hit: Option<String>,
+    /// Time until which sequential gates are held by `power_on_reset`
+    power_on_reset_until: Option<u64>,
+    /// Unchecked assertions as (wire ID, expected state, time)
+    assertions: Vec<(String, StateType, u64)>,
+    assertion_failures: Vec<AssertionFailure>,
+    fanout_limit: usize,
+    /// Output toggles between ZERO and ONE per gate since initialize/reset
+    gate_transition_counts: HashMap<String, u64>,
+    /// Keep seeded wire states through time 0 (see `set_initial_hold`)
+    initial_hold: bool,
+    /// Wire states given to `initialize` that were not all UNKNOWN
+    initial_wire_states: HashMap<String, Vec<StateType>>,
+    /// Nets whose drivers currently contend
+    conflicts: HashMap<NetKey, ConflictInfo>,
+    /// Unidirectional wires by (target gate, input port)
+    target_index: PortIndex,
+    /// Unidirectional wires by (source gate, output port)
+    source_index: PortIndex,
+    /// Bidirectional wires by inout pin, indexed at both ends
+    pin_index: PortIndex,
+    /// External calls since the circuit was loaded (None when not logging)
+    command_log: Option<Vec<LoggedCommand>>,
 }
 
 impl SimulationEngine {
@@ -36,219 +487,4525 @@ impl SimulationEngine {
             event_queue: EventQueue::new(),
             current_time: 0,
             running: false,
+            evaluation_counts: HashMap::new(),
+            unstable_gates: HashSet::new(),
+            dirty_gates: HashSet::new(),
+            dirty_wires: HashSet::new(),
+            unsynced_gates: HashSet::new(),
+            max_events_per_step: DEFAULT_MAX_EVENTS_PER_STEP,
+            last_step_truncated: false,
+            glitch_detection: false,
+            output_transitions: HashMap::new(),
+            glitches: Vec::new(),
+            history: None,
+            history_capacity: 0,
+            floating_input_policy: FloatingInputPolicy::default(),
+            unknown_policy: UnknownPolicy::default(),
+            delay_corner: DelayCorner::default(),
+            traced_signals: Vec::new(),
+            trace: HashMap::new(),
+            disabled_gates: HashSet::new(),
+            forced_outputs: HashMap::new(),
+            gate_groups: HashMap::new(),
+            breakpoints: HashMap::new(),
+            breakpoint_hit: None,
+            power_on_reset_until: None,
+            assertions: Vec::new(),
+            assertion_failures: Vec::new(),
+            fanout_limit: DEFAULT_FANOUT_LIMIT,
+            gate_transition_counts: HashMap::new(),
+            initial_hold: false,
+            initial_wire_states: HashMap::new(),
+            conflicts: HashMap::new(),
+            target_index: PortIndex::default(),
+            source_index: PortIndex::default(),
+            pin_index: PortIndex::default(),
+            command_log: None,
         }
     }
 
     /// Initialize the simulation with gates and wires
-    pub fn initialize(&mut self, gates: Vec<GateState>, wires: Vec<WireState>) {
+    ///
+    /// Returns wiring problems (missing gates, port indices beyond a gate's
+    /// arity). Invalid wires are still loaded; their out-of-range bits are
+    /// simply never read or written.
+    pub fn initialize(&mut self, gates: Vec<GateState>, wires: Vec<WireState>) -> Vec<WireError> {
+        self.clear_history();
         self.gates.clear();
         self.wires.clear();
+        self.target_index.clear();
+        self.source_index.clear();
+        self.pin_index.clear();
         self.event_queue.clear();
         self.current_time = 0;
+        self.evaluation_counts.clear();
+        self.unstable_gates.clear();
+        self.output_transitions.clear();
+        self.gate_transition_counts.clear();
+        self.conflicts.clear();
+        self.trace.clear();
+        self.disabled_gates.clear();
+        self.forced_outputs.clear();
+        self.gate_groups.clear();
+        self.breakpoints.clear();
+        self.breakpoint_hit = None;
+        self.power_on_reset_until = None;
+        self.assertions.clear();
+        self.assertion_failures.clear();
+        if let Some(log) = self.command_log.as_mut() {
+            log.clear();
+        }
 
         // Create gate instances
         for gate_state in gates {
-            let input_count = if gate_state.input_states.is_empty() {
-                None
-            } else {
-                Some(gate_state.input_states.len())
-            };
-
-            let gate = create_gate(&gate_state.gate_type, gate_state.id.clone(), input_count);
+            let mut gate = build_gate(&gate_state);
+            gate.set_unknown_policy(self.unknown_policy);
+            if let Some(group) = gate_state.group {
+                self.gate_groups.insert(gate_state.id.clone(), group);
+            }
             self.gates.insert(gate_state.id, gate);
         }
 
         // Create wire connections
+        self.initial_wire_states.clear();
         for wire_state in wires {
-            let wire = Wire {
-                id: wire_state.id.clone(),
-                state: StateType::from_u8(wire_state.state),
-                source_gate_id: wire_state.source_gate_id,
-                source_port_index: wire_state.source_port_index,
-                target_gate_id: wire_state.target_gate_id,
-                target_port_index: wire_state.target_port_index,
-            };
-            self.wires.insert(wire_state.id, wire);
+            let wire = Wire::from_state(wire_state);
+            if wire.state.iter().any(|&state| state != StateType::Unknown) {
+                self.initial_wire_states.insert(wire.id.clone(), wire.state.clone());
+            }
+            self.index_wire(&wire);
+            self.wires.insert(wire.id.clone(), wire);
         }
 
-        // Schedule initial evaluation for all gates
-        // Collect keys first to avoid borrow conflict
-        let gate_ids: Vec<String> = self.gates.keys().cloned().collect();
-        for gate_id in gate_ids {
-            self.schedule_gate_evaluation(gate_id, 0);
-        }
-    }
+        self.dirty_gates.clear();
+        self.unsynced_gates.clear();
+        self.dirty_wires.clear();
+        self.mark_all_dirty();
+        self.apply_policy_to_unconnected_inputs();
 
-    /// Schedule a gate for evaluation
-    fn schedule_gate_evaluation(&mut self, gate_id: String, time: u64) {
-        self.event_queue
-            .push(time, gate_id, -1, StateType::Unknown);
+        self.schedule_initial_evaluations();
+        self.record_trace(0);
+
+        self.validate_wires()
     }
 
-    /// Propagate wire state to target gate
-    fn propagate_wire_state(&mut self, wire_id: &str, new_state: StateType) {
-        let wire = match self.wires.get_mut(wire_id) {
-            Some(w) => w,
-            None => return,
-        };
+    /// Check every wire's gates and port ranges, sorted by wire ID
+    pub fn validate_wires(&self) -> Vec<WireError> {
+        let mut wires: Vec<&Wire> = self.wires.values().collect();
+        wires.sort_by(|a, b| a.id.cmp(&b.id));
 
-        if wire.state == new_state {
-            return;
+        let mut errors = Vec::new();
+        for wire in wires {
+            let last_bit = wire.width() - 1;
+            match self.gates.get(&wire.source_gate_id) {
+                None => errors.push(WireError::MissingGate {
+                    wire_id: wire.id.clone(),
+                    gate_id: wire.source_gate_id.clone(),
+                }),
+                Some(gate) if wire.source_port_index.saturating_add(last_bit) as usize >= gate.output_count() => {
+                    errors.push(WireError::SourcePortOutOfRange {
+                        wire_id: wire.id.clone(),
+                        gate_id: wire.source_gate_id.clone(),
+                        port_index: wire.source_port_index.saturating_add(last_bit),
+                        output_count: gate.output_count(),
+                    })
+                }
+                Some(_) => {}
+            }
+            match self.gates.get(&wire.target_gate_id) {
+                None => errors.push(WireError::MissingGate {
+                    wire_id: wire.id.clone(),
+                    gate_id: wire.target_gate_id.clone(),
+                }),
+                // Both ends of a bidirectional wire are pins named by output
+                Some(gate)
+                    if wire.bidirectional
+                        && wire.target_port_index.saturating_add(last_bit) as usize >= gate.output_count() =>
+                {
+                    errors.push(WireError::TargetPinOutOfRange {
+                        wire_id: wire.id.clone(),
+                        gate_id: wire.target_gate_id.clone(),
+                        port_index: wire.target_port_index.saturating_add(last_bit),
+                        output_count: gate.output_count(),
+                    })
+                }
+                Some(_) if wire.bidirectional => {}
+                Some(gate) if wire.target_port_index.saturating_add(last_bit) as usize >= gate.input_count() => {
+                    errors.push(WireError::TargetPortOutOfRange {
+                        wire_id: wire.id.clone(),
+                        gate_id: wire.target_gate_id.clone(),
+                        port_index: wire.target_port_index.saturating_add(last_bit),
+                        input_count: gate.input_count(),
+                    })
+                }
+                Some(_) => {}
+            }
         }
+        errors
+    }
 
-        wire.state = new_state;
-        let target_gate_id = wire.target_gate_id.clone();
-        let target_port_index = wire.target_port_index;
+    /// Add a gate to the running circuit, replacing any gate with the same ID
+    ///
+    /// The gate's inputs are resolved from existing wires that target it, its
+    /// unwired inputs follow the floating input policy, and it is scheduled
+    /// for evaluation at the current time.
+    pub fn add_gate(&mut self, gate_state: GateState) {
+        self.log_command(|| Command::AddGate { gate: Box::new(gate_state.clone()) });
+        let gate_id = gate_state.id.clone();
+        self.event_queue.remove_events_for_gate(&gate_id);
+        self.evaluation_counts.clear();
+        self.unstable_gates.remove(&gate_id);
+        let mut gate = build_gate(&gate_state);
+        gate.set_unknown_policy(self.unknown_policy);
+        self.gates.insert(gate_id.clone(), gate);
+        match gate_state.group {
+            Some(group) => self.gate_groups.insert(gate_id.clone(), group),
+            None => self.gate_groups.remove(&gate_id),
+        };
+        self.dirty_gates.insert(gate_id.clone());
+        self.unsynced_gates.insert(gate_id.clone());
 
-        // Collect all inputs to the target port
-        let input_states: Vec<StateType> = self
-            .wires
-            .values()
-            .filter(|w| w.target_gate_id == target_gate_id && w.target_port_index == target_port_index)
-            .map(|w| w.state)
-            .collect();
+        let mut ports: Vec<u32> = self.target_index.ports_of(&gate_id).map(|(port, _)| port).collect();
+        ports.sort_unstable();
+        for port in ports {
+            self.update_target_input(gate_id.clone(), port, self.current_time);
+        }
+        self.apply_policy_to_floating_ports(&gate_id);
+        self.schedule_gate_evaluation(gate_id, self.current_time);
+    }
 
-        let resolved_state = resolve_wire_state(&input_states);
+    /// Add a wire to the running circuit, replacing any wire with the same ID
+    ///
+    /// The wire takes its source gate's current outputs and its target ports
+    /// are re-resolved and scheduled at the current time.
+    pub fn add_wire(&mut self, wire_state: WireState) {
+        self.log_command(|| Command::AddWire { wire: wire_state.clone() });
+        self.evaluation_counts.clear();
+        let mut ports: Vec<(String, u32)> = Vec::new();
+        let mut pins: Vec<(String, u32)> = Vec::new();
+        if let Some(old) = self.wires.remove(&wire_state.id) {
+            self.unindex_wire(&old);
+            for bit in 0..old.width() {
+                if old.bidirectional {
+                    pins.extend(old.pins(bit as usize));
+                } else {
+                    ports.push((old.target_gate_id.clone(), old.target_port_index.saturating_add(bit)));
+                }
+            }
+        }
 
-        // Update target gate input
-        if let Some(gate) = self.gates.get_mut(&target_gate_id) {
-            gate.set_input(target_port_index as usize, resolved_state);
+        let mut wire = Wire::from_state(wire_state);
+        if let Some(source) = self.gates.get(&wire.source_gate_id) {
+            let outputs = source.get_outputs();
+            for (bit, state) in wire.state.iter_mut().enumerate() {
+                if let Some(&output) = outputs.get(wire.source_port_index as usize + bit) {
+                    *state = output;
+                }
+            }
+        }
+        for bit in 0..wire.width() {
+            if wire.bidirectional {
+                pins.extend(wire.pins(bit as usize));
+            } else {
+                ports.push((wire.target_gate_id.clone(), wire.target_port_index.saturating_add(bit)));
+            }
         }
+        self.dirty_wires.insert(wire.id.clone());
+        self.index_wire(&wire);
+        self.wires.insert(wire.id.clone(), wire);
 
-        // Schedule target gate evaluation
-        self.schedule_gate_evaluation(target_gate_id, self.current_time + 1);
+        for (target_gate_id, port) in ports {
+            self.update_target_input(target_gate_id, port, self.current_time);
+        }
+        self.resolve_pins(pins);
     }
 
-    /// Process a single simulation step
-    pub fn step(&mut self) {
-        let max_events = 10000;
-        let mut events_processed = 0;
-
-        while !self.event_queue.is_empty() && events_processed < max_events {
-            let event = match self.event_queue.peek() {
-                Some(e) if e.time <= self.current_time => self.event_queue.pop().unwrap(),
-                _ => break,
-            };
+    /// Add a wire to the port indexes
+    fn index_wire(&mut self, wire: &Wire) {
+        let width = wire.width();
+        if wire.bidirectional {
+            self.pin_index.insert(&wire.source_gate_id, wire.source_port_index, width, &wire.id);
+            self.pin_index.insert(&wire.target_gate_id, wire.target_port_index, width, &wire.id);
+        } else {
+            self.source_index.insert(&wire.source_gate_id, wire.source_port_index, width, &wire.id);
+            self.target_index.insert(&wire.target_gate_id, wire.target_port_index, width, &wire.id);
+        }
+    }
 
-            events_processed += 1;
+    /// Remove a wire from the port indexes
+    fn unindex_wire(&mut self, wire: &Wire) {
+        let width = wire.width();
+        if wire.bidirectional {
+            self.pin_index.remove(&wire.source_gate_id, wire.source_port_index, width, &wire.id);
+            self.pin_index.remove(&wire.target_gate_id, wire.target_port_index, width, &wire.id);
+        } else {
+            self.source_index.remove(&wire.source_gate_id, wire.source_port_index, width, &wire.id);
+            self.target_index.remove(&wire.target_gate_id, wire.target_port_index, width, &wire.id);
+        }
+    }
 
-            let gate = match self.gates.get_mut(&event.gate_id) {
-                Some(g) => g,
+    /// Re-resolve the nets of the given inout pins from their gates' current
+    /// drives (pins of missing gates are skipped)
+    fn resolve_pins(&mut self, mut pins: Vec<(String, u32)>) {
+        pins.sort();
+        pins.dedup();
+        for (gate_id, port) in pins {
+            let drive = match self.gates.get(&gate_id) {
+                Some(gate) => gate.get_outputs().get(port as usize).copied().unwrap_or(StateType::HiZ),
                 None => continue,
             };
+            self.resolve_inout_net(&gate_id, port, drive, self.current_time);
+        }
+    }
 
-            // Store previous outputs
-            let previous_outputs: Vec<StateType> = gate.get_outputs().to_vec();
-
-            // Evaluate gate
-            let result = gate.evaluate();
-
-            // Check for output changes and propagate
-            for (i, &new_state) in result.outputs.iter().enumerate() {
-                let old_state = previous_outputs.get(i).copied().unwrap_or(StateType::Unknown);
+    /// Track output changes of an evaluated gate for glitch detection
+    fn record_output_transitions(&mut self, gate_id: &str, previous: &[StateType], outputs: &[StateType]) {
+        for (port, (&before, &after)) in previous.iter().zip(outputs).enumerate() {
+            if before == after {
+                continue;
+            }
+            let (_, values) = self
+                .output_transitions
+                .entry((gate_id.to_string(), port as u32))
+                .or_insert_with(|| (self.current_time, vec![before]));
+            values.push(after);
+        }
+    }
 
-                if old_state != new_state {
-                    // Propagate to connected wires
-                    let gate_id = event.gate_id.clone();
-                    let wire_ids: Vec<String> = self
-                        .wires
-                        .iter()
-                        .filter(|(_, w)| w.source_gate_id == gate_id && w.source_port_index == i as u32)
-                        .map(|(id, _)| id.clone())
-                        .collect();
+    /// Close the glitch windows of outputs whose fan-in cone has settled and
+    /// report those that changed more than once
+    ///
+    /// A cone is settled when none of its gates has a pending event. Pending
+    /// wakeups of input-less sources (clocks, pulses) are future stimulus
+    /// rather than activity in flight, so a free-running clock does not keep
+    /// every window open. Windows that never settle (an oscillating cone) are
+    /// reported once they reach `GLITCH_WINDOW_LIMIT` transitions.
+    fn collect_glitches(&mut self) {
+        if self.output_transitions.is_empty() {
+            return;
+        }
 
-                    for wire_id in wire_ids {
-                        self.propagate_wire_state(&wire_id, new_state);
+        let busy: HashSet<&str> = self
+            .event_queue
+            .pending_gate_ids()
+            .filter(|id| self.gates.get(*id).is_some_and(|gate| gate.input_count() > 0))
+            .collect();
+        let mut drivers: HashMap<&str, Vec<&str>> = HashMap::new();
+        if !busy.is_empty() {
+            for wire in self.wires.values() {
+                drivers.entry(wire.target_gate_id.as_str()).or_default().push(wire.source_gate_id.as_str());
+                if wire.bidirectional {
+                    drivers.entry(wire.source_gate_id.as_str()).or_default().push(wire.target_gate_id.as_str());
+                }
+            }
+        }
+        let cone_busy = |gate_id: &str| {
+            let mut seen = HashSet::from([gate_id]);
+            let mut stack = vec![gate_id];
+            while let Some(id) = stack.pop() {
+                if busy.contains(id) {
+                    return true;
+                }
+                for &driver in drivers.get(id).map_or(&[][..], Vec::as_slice) {
+                    if seen.insert(driver) {
+                        stack.push(driver);
                     }
                 }
             }
+            false
+        };
+
+        let closed: Vec<(String, u32)> = self
+            .output_transitions
+            .iter()
+            .filter(|((gate_id, _), (_, values))| values.len() >= GLITCH_WINDOW_LIMIT || busy.is_empty() || !cone_busy(gate_id))
+            .map(|(key, _)| key.clone())
+            .collect();
+
+        let mut settled: Vec<_> = closed
+            .into_iter()
+            .filter_map(|key| self.output_transitions.remove_entry(&key))
+            .filter(|(_, (_, values))| values.len() > 2)
+            .collect();
+        settled.sort_by(|a, b| a.1 .0.cmp(&b.1 .0).then_with(|| a.0.cmp(&b.0)));
+
+        for ((gate_id, port_index), (start_time, values)) in settled {
+            self.glitches.push(GlitchReport {
+                gate_id,
+                port_index,
+                start_time,
+                end_time: self.current_time,
+                values: values.iter().map(|s| s.to_u8()).collect(),
+            });
         }
+    }
 
-        // Advance time
-        if let Some(next_event) = self.event_queue.peek() {
-            self.current_time = self.current_time.max(next_event.time);
+    /// Schedule every gate for evaluation at `time`, in gate ID order so
+    /// same-time events tie-break identically on every run
+    fn schedule_all_gates(&mut self, time: u64) {
+        let mut gate_ids: Vec<String> = self.gates.keys().cloned().collect();
+        gate_ids.sort();
+        for gate_id in gate_ids {
+            self.schedule_gate_evaluation(gate_id, time);
         }
-        self.current_time += 1;
     }
 
-    /// Toggle an input gate
-    pub fn toggle_input(&mut self, gate_id: &str) {
-        if let Some(gate) = self.gates.get_mut(gate_id) {
-            gate.toggle();
+    /// Schedule the power-on evaluation of every gate at time 0
+    ///
+    /// With `initial_hold`, the seeded wire states are first restored into
+    /// the wires and their target inputs, and every gate evaluates again once
+    /// the hold ends so its outputs catch up with the held wires.
+    fn schedule_initial_evaluations(&mut self) {
+        self.schedule_all_gates(0);
+        if !self.initial_hold {
+            return;
         }
-        self.schedule_gate_evaluation(gate_id.to_string(), self.current_time);
+
+        let mut ports: Vec<(String, u32)> = Vec::new();
+        for (wire_id, states) in &self.initial_wire_states {
+            if let Some(wire) = self.wires.get_mut(wire_id) {
+                if wire.state.len() != states.len() {
+                    continue;
+                }
+                wire.state.copy_from_slice(states);
+                if !wire.bidirectional {
+                    ports.extend((0..wire.width()).map(|bit| (wire.target_gate_id.clone(), wire.target_port_index.saturating_add(bit))));
+                }
+            }
+        }
+        ports.sort();
+        ports.dedup();
+        for (target_gate_id, port) in ports {
+            self.update_target_input(target_gate_id, port, 0);
+        }
+        self.schedule_all_gates(INITIAL_HOLD_TIME);
     }
 
-    /// Set running state
-    pub fn set_running(&mut self, running: bool) {
-        self.running = running;
+    /// Whether a wire still holds its seeded state, ignoring its drivers
+    fn is_held(&self, wire_id: &str) -> bool {
+        self.initial_hold
+            && self.current_time < INITIAL_HOLD_TIME
+            && self.initial_wire_states.contains_key(wire_id)
     }
 
-    /// Check if simulation is running
-    pub fn is_running(&self) -> bool {
-        self.running
+    /// Keep the (not all UNKNOWN) wire states given to `initialize` through
+    /// time 0 instead of letting the first evaluations overwrite them
+    ///
+    /// Gate inputs start from the seeded wires, so a latch or other feedback
+    /// loop seeded consistently powers on in that state rather than UNKNOWN;
+    /// propagation onto the seeded wires resumes at time 1. `reset` re-seeds
+    /// the same wire states while the option is on. Set it before calling
+    /// `initialize`.
+    pub fn set_initial_hold(&mut self, enabled: bool) {
+        self.initial_hold = enabled;
     }
 
-    /// Get current simulation time
-    pub fn get_current_time(&self) -> u64 {
-        self.current_time
+    /// Schedule a gate for evaluation
+    fn schedule_gate_evaluation(&mut self, gate_id: String, time: u64) {
+        self.event_queue
+            .push(time, gate_id, -1, StateType::Unknown);
     }
 
-    /// Reset simulation
-    pub fn reset(&mut self) {
-        self.current_time = 0;
-        self.event_queue.clear();
+    /// Send a gate output to every wire bit it drives, and to the net of the
+    /// inout pin it drives, if any
+    fn propagate_output(&mut self, gate_id: &str, port: usize, new_state: StateType, delay: u64) {
+        let mut wire_bits: Vec<(String, usize)> = self
+            .source_index
+            .get(gate_id, port as u32)
+            .iter()
+            .filter_map(|id| self.wires.get(id))
+            .filter_map(|w| w.source_bit(gate_id, port as u32).map(|b| (w.id.clone(), b)))
+            .collect();
+        wire_bits.sort();
 
-        for gate in self.gates.values_mut() {
-            gate.reset();
+        for (wire_id, bit) in wire_bits {
+            self.propagate_wire_state(&wire_id, bit, new_state, delay);
         }
+        self.resolve_inout_net(gate_id, port as u32, new_state, self.current_time.saturating_add(delay.max(1)));
+    }
 
-        for wire in self.wires.values_mut() {
-            wire.state = StateType::Unknown;
+    /// Re-resolve the net attached to an inout pin whose drive is now `drive`
+    ///
+    /// The net is every pin reachable through bidirectional wire bits; a pin
+    /// with no such wires is a net of its own. The value resolved from all
+    /// pins' drives is stored on the net's wire bits and fed to each pin's
+    /// sense input, and pins whose sense input changed are scheduled at `time`.
+    fn resolve_inout_net(&mut self, gate_id: &str, port: u32, drive: StateType, time: u64) {
+        let mut pins: Vec<(String, u32)> = vec![(gate_id.to_string(), port)];
+        let mut wire_bits: Vec<(String, usize)> = Vec::new();
+        let mut next = 0;
+        while next < pins.len() {
+            let (pin_gate_id, pin_port) = pins[next].clone();
+            next += 1;
+            let wires = self.pin_index.get(&pin_gate_id, pin_port).iter().filter_map(|id| self.wires.get(id));
+            for wire in wires {
+                let wire_id = &wire.id;
+                let bit = match wire.pin_bit(&pin_gate_id, pin_port) {
+                    Some(bit) => bit,
+                    None => continue,
+                };
+                if wire_bits.contains(&(wire_id.clone(), bit)) {
+                    continue;
+                }
+                wire_bits.push((wire_id.clone(), bit));
+                for pin in wire.pins(bit) {
+                    if !pins.contains(&pin) {
+                        pins.push(pin);
+                    }
+                }
+            }
         }
 
-        let gate_ids: Vec<String> = self.gates.keys().cloned().collect();
-        for gate_id in gate_ids {
-            self.schedule_gate_evaluation(gate_id, 0);
+        let is_inout = |engine: &Self, (id, port): &(String, u32)| {
+            engine.gates.get(id).is_some_and(|gate| gate.inout_input(*port as usize).is_some())
+        };
+        if wire_bits.is_empty() && !is_inout(self, &pins[0]) {
+            return;
         }
-    }
 
-    /// Get current state snapshot
-    pub fn get_snapshot(&self) -> SimulationSnapshot {
-        let gates: Vec<GateState> = self
-            .gates
+        let drives: Vec<StateType> = pins
             .iter()
-            .map(|(id, gate)| GateState {
-                id: id.clone(),
-                gate_type: gate.gate_type().to_string(),
-                input_states: gate.get_inputs().iter().map(|s| s.to_u8()).collect(),
-                output_states: gate.get_outputs().iter().map(|s| s.to_u8()).collect(),
+            .map(|(id, pin_port)| {
+                if id == gate_id && *pin_port == port {
+                    return drive;
+                }
+                self.gates
+                    .get(id)
+                    .and_then(|gate| gate.get_outputs().get(*pin_port as usize).copied())
+                    .unwrap_or(StateType::HiZ)
             })
             .collect();
+        let resolved = resolve_wire_state(&drives);
+        self.record_conflict(
+            resolved,
+            &drives,
+            || {
+                let lowest_pin = pins.iter().min().cloned().unwrap_or_default();
+                NetKey::Pin(lowest_pin.0, lowest_pin.1)
+            },
+            |_| {
+                let wire_ids = wire_bits.iter().map(|(wire_id, _)| wire_id.clone()).collect();
+                let drivers = pins.iter().zip(&drives).map(|((id, pin_port), &state)| (id.clone(), *pin_port, state)).collect();
+                (wire_ids, drivers)
+            },
+        );
 
-        let wires: Vec<WireState> = self
-            .wires
-            .iter()
-            .map(|(id, wire)| WireState {
-                id: id.clone(),
-                state: wire.state.to_u8(),
-                source_gate_id: wire.source_gate_id.clone(),
-                source_port_index: wire.source_port_index,
-                target_gate_id: wire.target_gate_id.clone(),
-                target_port_index: wire.target_port_index,
-            })
-            .collect();
+        wire_bits.sort();
+        for (wire_id, bit) in wire_bits {
+            self.set_wire_bit(&wire_id, bit, resolved);
+        }
 
-        SimulationSnapshot {
-            time: self.current_time,
-            gates,
-            wires,
+        pins.sort();
+        for (pin_gate_id, pin_port) in pins {
+            let gate = match self.gates.get_mut(&pin_gate_id) {
+                Some(gate) => gate,
+                None => continue,
+            };
+            let input = match gate.inout_input(pin_port as usize) {
+                Some(input) => input,
+                None => continue,
+            };
+            let state = if gate.passes_high_impedance() {
+                resolved.to_strong()
+            } else {
+                self.floating_input_policy.apply(resolved.to_strong())
+            };
+            if gate.get_inputs().get(input) == Some(&state) {
+                continue;
+            }
+            gate.set_input(input, state);
+            self.dirty_gates.insert(pin_gate_id.clone());
+            self.schedule_gate_evaluation(pin_gate_id, time);
         }
     }
-}
 
-impl Default for SimulationEngine {
-    fn default() -> Self {
-        Self::new()
+    /// Propagate one bit of a wire's state to its target gate, scheduling the
+    /// target's evaluation `delay` time units from now (at least one) plus
+    /// the wire's own delay
+    fn propagate_wire_state(&mut self, wire_id: &str, bit: usize, new_state: StateType, delay: u64) {
+        if !self.set_wire_bit(wire_id, bit, new_state) {
+            return;
+        }
+        let wire = &self.wires[wire_id];
+        let target_gate_id = wire.target_gate_id.clone();
+        let target_port_index = wire.target_port_index.saturating_add(bit as u32);
+        let time = self.current_time.saturating_add(delay.max(1)).saturating_add(wire.delay);
+
+        if wire.self_loop || wire.delay > 0 {
+            // Hold fed-back and wire-delayed values back until they arrive, so
+            // other evaluations of the target don't see them early (see
+            // `process_event`)
+            self.event_queue.push(time, target_gate_id, target_port_index as i32, new_state);
+            return;
+        }
+        self.update_target_input(target_gate_id, target_port_index, time);
+    }
+
+    /// Store a new state on one wire bit, recording the transition and
+    /// checking breakpoints. Returns false if the bit already held it.
+    fn set_wire_bit(&mut self, wire_id: &str, bit: usize, new_state: StateType) -> bool {
+        if self.is_held(wire_id) {
+            return false;
+        }
+        let wire = match self.wires.get_mut(wire_id) {
+            Some(w) => w,
+            None => return false,
+        };
+
+        match wire.state.get(bit) {
+            Some(&state) if state != new_state => {}
+            _ => return false,
+        }
+
+        let previous_state = wire.state[bit];
+        wire.state[bit] = new_state;
+        wire.transition_count += 1;
+        if wire.transition_times.len() == TRANSITION_HISTORY_LIMIT {
+            wire.transition_times.pop_front();
+        }
+        wire.transition_times.push_back(self.current_time);
+        self.dirty_wires.insert(wire_id.to_string());
+        if self.breakpoint_hit.is_none()
+            && self.breakpoints.get(wire_id).is_some_and(|edge| edge.matches(previous_state, new_state))
+        {
+            self.breakpoint_hit = Some(wire_id.to_string());
+        }
+        true
+    }
+
+    /// Re-resolve a gate input port from all wires driving it and schedule
+    /// the gate for evaluation at `time`
+    fn update_target_input(&mut self, target_gate_id: String, target_port_index: u32, time: u64) {
+        // Collect all inputs to the target port
+        let mut input_states: Vec<StateType> = Vec::new();
+        let mut resolution: Option<(&str, WireResolution)> = None;
+        let wires = self.target_index.get(&target_gate_id, target_port_index).iter().filter_map(|id| self.wires.get(id));
+        for w in wires {
+            if let Some(b) = w.target_bit(&target_gate_id, target_port_index) {
+                input_states.push(w.state[b]);
+                if w.resolution != WireResolution::Standard && resolution.is_none_or(|(id, _)| w.id.as_str() < id) {
+                    resolution = Some((&w.id, w.resolution));
+                }
+            }
+        }
+
+        let resolution = resolution.map_or(WireResolution::Standard, |(_, resolution)| resolution);
+        let resolved_state = resolution.resolve(&input_states);
+        self.record_conflict(
+            resolved_state,
+            &input_states,
+            || NetKey::Input(target_gate_id.clone(), target_port_index),
+            |engine| {
+                let wires = engine.target_index.get(&target_gate_id, target_port_index).iter();
+                wires
+                    .filter_map(|id| engine.wires.get(id))
+                    .filter_map(|w| {
+                        let b = w.target_bit(&target_gate_id, target_port_index)?;
+                        Some((w.id.clone(), (w.source_gate_id.clone(), w.source_port_index.saturating_add(b as u32), w.state[b])))
+                    })
+                    .unzip()
+            },
+        );
+
+        // Update target gate input (gates read weak levels as logic levels)
+        if let Some(gate) = self.gates.get_mut(&target_gate_id) {
+            let state = if gate.passes_high_impedance() {
+                resolved_state.to_strong()
+            } else {
+                self.floating_input_policy.apply(resolved_state.to_strong())
+            };
+            gate.set_input(target_port_index as usize, state);
+            self.dirty_gates.insert(target_gate_id.clone());
+        }
+
+        // Schedule target gate evaluation
+        self.schedule_gate_evaluation(target_gate_id, time);
+    }
+
+    /// Track whether a net resolved to CONFLICT because its drivers contend
+    ///
+    /// Nets that merely carry a CONFLICT from upstream are not recorded, so
+    /// each report points at the place the contention happens.
+    ///
+    /// This runs for every input update, so the net's key is only built when
+    /// there are conflicts to update, and its wires and drivers (`net`) only
+    /// when it contends.
+    fn record_conflict(
+        &mut self,
+        resolved: StateType,
+        driven: &[StateType],
+        key: impl FnOnce() -> NetKey,
+        net: impl FnOnce(&Self) -> NetDrivers,
+    ) {
+        if resolved != StateType::Conflict || driven.contains(&StateType::Conflict) {
+            if !self.conflicts.is_empty() {
+                self.conflicts.remove(&key());
+            }
+            return;
+        }
+
+        let key = key();
+        let (mut wire_ids, drivers) = net(self);
+        wire_ids.sort();
+        wire_ids.dedup();
+        let mut drivers: Vec<ConflictDriver> = drivers
+            .into_iter()
+            .filter(|&(_, _, state)| state != StateType::HiZ)
+            .map(|(gate_id, port_index, state)| ConflictDriver { gate_id, port_index, state: state.to_u8() })
+            .collect();
+        drivers.sort_by(|a, b| (&a.gate_id, a.port_index).cmp(&(&b.gate_id, b.port_index)));
+        let time = self.conflicts.get(&key).map_or(self.current_time, |existing| existing.time);
+        self.conflicts.insert(key, ConflictInfo { wire_ids, drivers, time });
+    }
+
+    /// Nets whose drivers currently contend, sorted by wire IDs
+    ///
+    /// Each entry lists the wires of the net and every source driving it
+    /// with the state it drives; `time` is when the contention began.
+    pub fn get_conflicts(&self) -> Vec<ConflictInfo> {
+        let mut conflicts: Vec<ConflictInfo> = self.conflicts.values().cloned().collect();
+        conflicts.sort_by(|a, b| a.wire_ids.cmp(&b.wire_ids));
+        conflicts
+    }
+
+    /// Process a single simulation step
+    ///
+    /// Every propagation takes at least one time unit, so a combinational loop
+    /// re-evaluates once per step rather than spinning within one. Evaluation
+    /// counts therefore accumulate until the circuit settles (or receives new
+    /// stimulus), and gates exceeding the threshold are flagged as unstable.
+    ///
+    /// Returns the number of events processed.
+    pub fn step(&mut self) -> u32 {
+        self.log_command(|| Command::Step { count: 1 });
+        self.step_events()
+    }
+
+    fn step_events(&mut self) -> u32 {
+        if self.history.is_some() {
+            self.record_history();
+        }
+
+        let mut events_processed: u32 = 0;
+        self.last_step_truncated = false;
+        self.breakpoint_hit = None;
+        self.check_assertions(self.current_time);
+
+        while !self.event_queue.is_empty() && self.breakpoint_hit.is_none() {
+            let event = match self.event_queue.peek() {
+                Some(e) if e.time <= self.current_time => {
+                    if events_processed as usize >= self.max_events_per_step {
+                        self.last_step_truncated = true;
+                        break;
+                    }
+                    self.event_queue.pop().unwrap()
+                }
+                _ => break,
+            };
+
+            events_processed += 1;
+            self.process_event(&event.gate_id, event.port_index);
+        }
+
+        if self.event_queue.is_empty() {
+            self.evaluation_counts.clear();
+        }
+        self.collect_glitches();
+
+        if events_processed > 0 {
+            self.record_trace(self.current_time);
+        }
+
+        // Stay at the triggering time so the breakpoint state can be inspected
+        if self.breakpoint_hit.is_some() {
+            return events_processed;
+        }
+
+        // Advance time: jump straight to the next pending event so delayed
+        // events don't need empty steps, or tick once when idle
+        match self.event_queue.peek() {
+            Some(next_event) if next_event.time > self.current_time => {
+                self.current_time = next_event.time;
+            }
+            Some(_) => {} // Events still due now (step was truncated)
+            None => self.current_time = self.current_time.saturating_add(1),
+        }
+
+        events_processed
+    }
+
+    /// Pop and process exactly one event, for single-stepping causality
+    ///
+    /// Time moves to the event's timestamp but no further, so a following
+    /// `step` processes whatever else is due then. A breakpoint the event
+    /// fires stops there as it would `step`, and is reported both in the
+    /// result and by `breakpoint_hit`. Returns None when no events are
+    /// pending.
+    pub fn step_single_event(&mut self) -> Option<EventReport> {
+        if self.event_queue.is_empty() {
+            return None;
+        }
+        self.log_command(|| Command::StepSingleEvent);
+        if self.history.is_some() {
+            self.record_history();
+        }
+        self.last_step_truncated = false;
+        self.breakpoint_hit = None;
+
+        let event = self.event_queue.pop()?;
+        self.current_time = self.current_time.max(event.time);
+        self.check_assertions(self.current_time);
+        let evaluated = self.process_event(&event.gate_id, event.port_index);
+
+        if self.event_queue.is_empty() {
+            self.evaluation_counts.clear();
+        }
+        self.collect_glitches();
+        self.record_trace(self.current_time);
+
+        let to_u8 = |states: &[StateType]| states.iter().map(|s| s.to_u8()).collect::<Vec<u8>>();
+        let inputs = self.gates.get(&event.gate_id).map_or(Vec::new(), |gate| to_u8(gate.get_inputs()));
+        let (previous_outputs, outputs) = evaluated.clone().unwrap_or_default();
+        Some(EventReport {
+            time: event.time,
+            gate_id: event.gate_id,
+            evaluated: evaluated.is_some(),
+            inputs,
+            changed_outputs: (0..outputs.len() as u32)
+                .filter(|&port| previous_outputs.get(port as usize) != outputs.get(port as usize))
+                .collect(),
+            previous_outputs: to_u8(&previous_outputs),
+            outputs: to_u8(&outputs),
+            breakpoint_hit: self.breakpoint_hit.clone(),
+        })
+    }
+
+    /// Evaluate a gate for one dequeued event and propagate its outputs
+    ///
+    /// A port event instead delivers a self-loop wire's value to the input,
+    /// scheduling the evaluation for the same time. Returns the outputs
+    /// before and after, or None if nothing was evaluated (port events,
+    /// disabled or missing gates).
+    fn process_event(&mut self, gate_id: &str, port_index: i32) -> Option<(Vec<StateType>, Vec<StateType>)> {
+        if port_index >= 0 {
+            self.update_target_input(gate_id.to_string(), port_index as u32, self.current_time);
+            return None;
+        }
+        if self.disabled_gates.contains(gate_id) {
+            return None;
+        }
+
+        let gate = self.gates.get_mut(gate_id)?;
+
+        if gate.input_count() == 0 {
+            // Source gates (switches, clocks) inject new stimulus
+            self.evaluation_counts.clear();
+        } else {
+            let count = self.evaluation_counts.entry(gate_id.to_string()).or_insert(0);
+            *count += 1;
+            if *count > OSCILLATION_THRESHOLD {
+                self.unstable_gates.insert(gate_id.to_string());
+            }
+        }
+
+        // Wires held through time 0 cannot take the outputs yet, so the sync
+        // waits for the evaluation after the hold
+        let resync = if self.initial_hold && self.current_time < INITIAL_HOLD_TIME {
+            self.unsynced_gates.contains(gate_id)
+        } else {
+            self.unsynced_gates.remove(gate_id)
+        };
+
+        // Evaluate gate; sequential gates held in reset re-send their outputs
+        let previous_outputs: Vec<StateType> = gate.get_outputs().to_vec();
+        let held = gate.is_sequential() && self.power_on_reset_until.is_some_and(|until| self.current_time < until);
+        let mut result = if held {
+            GateResult { outputs: previous_outputs.clone(), delay: gate.delay() }
+        } else {
+            gate.tick(self.current_time);
+            gate.evaluate()
+        };
+        for (&port, &state) in self.forced_outputs.get(gate_id).into_iter().flatten() {
+            if port < result.outputs.len() {
+                result.outputs[port] = state;
+                gate.set_output(port, state);
+            }
+        }
+        let (rise_delay, fall_delay) = (gate.rise_delay(), gate.fall_delay());
+        let corner_delay = gate.corner_delay(self.delay_corner);
+        if result.outputs != previous_outputs {
+            self.dirty_gates.insert(gate_id.to_string());
+            let toggles = previous_outputs
+                .iter()
+                .zip(&result.outputs)
+                .filter(|(before, after)| {
+                    matches!(
+                        (before.to_strong(), after.to_strong()),
+                        (StateType::Zero, StateType::One) | (StateType::One, StateType::Zero)
+                    )
+                })
+                .count() as u64;
+            if toggles > 0 {
+                *self.gate_transition_counts.entry(gate_id.to_string()).or_insert(0) += toggles;
+            }
+        }
+
+        // Self-timed gates (clocks) schedule their own next evaluation
+        if let Some(wakeup_time) = gate.next_wakeup(self.current_time) {
+            self.event_queue.push(wakeup_time, gate_id.to_string(), -1, StateType::Unknown);
+        }
+
+        if self.glitch_detection {
+            self.record_output_transitions(gate_id, &previous_outputs, &result.outputs);
+        }
+
+        // Propagate changed outputs to connected wires. Until the gate is in
+        // sync every output is sent, so one that never changed (e.g. a switch
+        // starting at ZERO) still reaches wires holding a different state.
+        for (i, &new_state) in result.outputs.iter().enumerate() {
+            if !resync && previous_outputs.get(i) == Some(&new_state) {
+                continue;
+            }
+            let delay = match (corner_delay, new_state.to_strong()) {
+                (Some(delay), _) => delay,
+                (None, StateType::One) => rise_delay,
+                (None, StateType::Zero) => fall_delay,
+                (None, _) => result.delay,
+            };
+            self.propagate_output(gate_id, i, new_state, delay);
+        }
+
+        Some((previous_outputs, result.outputs))
+    }
+
+    /// Process every event due up to `current_time + time_units`, then leave
+    /// time at exactly that point; returns the number of events processed
+    ///
+    /// Stops early, at the time of the triggering event, if a breakpoint fires.
+    ///
+    /// Unlike calling `step` in a loop, the amount of simulated time covered
+    /// does not depend on how many events happen along the way.
+    pub fn advance_by(&mut self, time_units: u64) -> u32 {
+        self.log_command(|| Command::AdvanceBy { time_units });
+        let target = self.current_time.saturating_add(time_units);
+        let mut events_processed: u32 = 0;
+        while self.event_queue.peek().is_some_and(|event| event.time <= target) {
+            events_processed = events_processed.saturating_add(self.step_events());
+            if self.breakpoint_hit.is_some() {
+                return events_processed;
+            }
+        }
+        // step() may have jumped to a later event or ticked past an empty queue
+        self.current_time = target;
+        self.check_assertions(target.saturating_add(1));
+        events_processed
+    }
+
+    /// Check that every bit of a wire holds `expected` at `at_time`, once
+    /// all events up to that time have been processed
+    ///
+    /// Failures are collected for `get_assertion_failures`. Assertions are
+    /// dropped on initialize and reset.
+    pub fn add_assertion(&mut self, wire_id: String, expected: StateType, at_time: u64) -> Result<(), SimError> {
+        if !self.wires.contains_key(&wire_id) {
+            return Err(SimError::WireNotFound(wire_id));
+        }
+        if at_time < self.current_time {
+            return Err(SimError::TimeInPast { time: at_time, current_time: self.current_time });
+        }
+        self.assertions.push((wire_id, expected, at_time));
+        Ok(())
+    }
+
+    /// Assertions that have failed so far, in time order
+    pub fn get_assertion_failures(&self) -> &[AssertionFailure] {
+        &self.assertion_failures
+    }
+
+    /// Check the assertions due before `time` against the current wire
+    /// states, which no event has changed since they were due
+    fn check_assertions(&mut self, time: u64) {
+        if self.assertions.iter().all(|&(_, _, at_time)| at_time >= time) {
+            return;
+        }
+        let (mut due, pending): (Vec<_>, Vec<_>) =
+            std::mem::take(&mut self.assertions).into_iter().partition(|&(_, _, at_time)| at_time < time);
+        self.assertions = pending;
+        due.sort_by_key(|&(_, _, at_time)| at_time);
+
+        for (wire_id, expected, at_time) in due {
+            let states = self.wires.get(&wire_id).map(|wire| &wire.state);
+            if states.is_some_and(|states| states.iter().all(|&state| state == expected)) {
+                continue;
+            }
+            self.assertion_failures.push(AssertionFailure {
+                actual: states.map(|states| WireValue::from_states(states)),
+                wire_id,
+                time: at_time,
+                expected: expected.to_u8(),
+            });
+        }
+    }
+
+    /// Stop `step`/`advance_by` as soon as any bit of the wire makes the given
+    /// transition, replacing any breakpoint already set on it
+    ///
+    /// The step finishes the event that caused the transition and leaves time
+    /// where it fired; remaining due events run on the next step.
+    pub fn set_breakpoint(&mut self, wire_id: String, on: EdgeKind) -> Result<(), SimError> {
+        if !self.wires.contains_key(&wire_id) {
+            return Err(SimError::WireNotFound(wire_id));
+        }
+        self.breakpoints.insert(wire_id, on);
+        Ok(())
+    }
+
+    /// Remove the breakpoint on a wire, if any
+    pub fn clear_breakpoint(&mut self, wire_id: &str) {
+        self.breakpoints.remove(wire_id);
+    }
+
+    /// Wire whose breakpoint stopped the last `step`/`advance_by`, or fired
+    /// during the last `step_single_event`
+    pub fn breakpoint_hit(&self) -> Option<&str> {
+        self.breakpoint_hit.as_deref()
+    }
+
+    /// Limit the number of events a single step may process (at least 1)
+    pub fn set_max_events_per_step(&mut self, max_events: usize) {
+        self.max_events_per_step = max_events.max(1);
+    }
+
+    /// Number of events waiting in the queue
+    pub fn queue_length(&self) -> usize {
+        self.event_queue.len()
+    }
+
+    /// Up to `limit` queued events in the order they will be processed,
+    /// without disturbing the queue
+    pub fn peek_pending_events(&self, limit: usize) -> Vec<PendingEvent> {
+        self.event_queue
+            .pending_events()
+            .into_iter()
+            .take(limit)
+            .map(|event| PendingEvent {
+                time: event.time,
+                gate_id: event.gate_id,
+                port: u32::try_from(event.port_index).ok(),
+            })
+            .collect()
+    }
+
+    /// Whether the last step hit the event limit with events still due;
+    /// time is not advanced so the next step continues where it stopped
+    pub fn last_step_truncated(&self) -> bool {
+        self.last_step_truncated
+    }
+
+    /// Toggle an input gate
+    pub fn toggle_input(&mut self, gate_id: &str) -> Result<(), SimError> {
+        let gate = self
+            .gates
+            .get_mut(gate_id)
+            .ok_or_else(|| SimError::GateNotFound(gate_id.to_string()))?;
+        gate.toggle();
+        self.log_command(|| Command::ToggleInput { gate_id: gate_id.to_string() });
+        self.evaluation_counts.clear();
+        self.schedule_gate_evaluation(gate_id.to_string(), self.current_time);
+        Ok(())
+    }
+
+    /// Drive an input gate to an absolute level
+    pub fn set_input_level(&mut self, gate_id: &str, state: StateType) -> Result<(), SimError> {
+        let gate = self
+            .gates
+            .get_mut(gate_id)
+            .ok_or_else(|| SimError::GateNotFound(gate_id.to_string()))?;
+        gate.set_level(state);
+        self.log_command(|| Command::SetInputLevel { gate_id: gate_id.to_string(), state: state.to_u8() });
+        self.evaluation_counts.clear();
+        self.schedule_gate_evaluation(gate_id.to_string(), self.current_time);
+        Ok(())
+    }
+
+    /// Set several input gates to absolute levels at once
+    ///
+    /// All evaluations are scheduled for the current time, so no intermediate
+    /// combination of the new levels propagates. If any ID is unknown nothing
+    /// is changed.
+    pub fn set_inputs(&mut self, values: Vec<(String, bool)>) -> Result<(), SimError> {
+        if let Some((gate_id, _)) = values.iter().find(|(id, _)| !self.gates.contains_key(id)) {
+            return Err(SimError::GateNotFound(gate_id.clone()));
+        }
+
+        self.log_command(|| Command::SetInputs { values: values.clone() });
+        self.evaluation_counts.clear();
+        for (gate_id, high) in values {
+            let level = if high { StateType::One } else { StateType::Zero };
+            if let Some(gate) = self.gates.get_mut(&gate_id) {
+                gate.set_level(level);
+                self.schedule_gate_evaluation(gate_id, self.current_time);
+            }
+        }
+        Ok(())
+    }
+
+    /// Step until the event queue is empty (returns true) or `max_steps` steps
+    /// have run without settling (returns false, indicating possible oscillation)
+    pub fn run_until_stable(&mut self, max_steps: u64) -> bool {
+        self.log_command(|| Command::RunUntilStable { max_steps });
+        for _ in 0..max_steps {
+            if self.event_queue.is_empty() {
+                return true;
+            }
+            self.step_events();
+        }
+        self.event_queue.is_empty()
+    }
+
+    /// Generate a truth table by driving every combination of the given
+    /// TOGGLE gates and recording the settled outputs of the output gates
+    ///
+    /// Rows are ordered with the first input as the most significant bit.
+    /// Output gates contribute all their outputs, or their inputs if they
+    /// have none (e.g. LEDs). The engine is left as it was (see `probe`).
+    pub fn compute_truth_table(
+        &mut self,
+        input_gate_ids: Vec<String>,
+        output_gate_ids: Vec<String>,
+    ) -> Result<TruthTable, SimError> {
+        if input_gate_ids.len() > MAX_TRUTH_TABLE_INPUTS {
+            return Err(SimError::TooManyInputs {
+                max: MAX_TRUTH_TABLE_INPUTS,
+                got: input_gate_ids.len(),
+            });
+        }
+        for id in &input_gate_ids {
+            match self.gates.get(id) {
+                Some(gate) if gate.gate_type() == "TOGGLE" => {}
+                Some(_) => {
+                    return Err(SimError::UnexpectedGateType {
+                        gate_id: id.clone(),
+                        expected: "TOGGLE".to_string(),
+                    })
+                }
+                None => return Err(SimError::GateNotFound(id.clone())),
+            }
+        }
+        if let Some(id) = output_gate_ids.iter().find(|id| !self.gates.contains_key(*id)) {
+            return Err(SimError::GateNotFound(id.clone()));
+        }
+
+        let input_count = input_gate_ids.len();
+        let rows = self.probe(|engine, _| {
+            let mut rows = Vec::with_capacity(1 << input_count);
+            for combination in 0..(1u32 << input_count) {
+                let levels: Vec<StateType> = (0..input_count)
+                    .map(|i| {
+                        if combination & (1 << (input_count - 1 - i)) != 0 {
+                            StateType::One
+                        } else {
+                            StateType::Zero
+                        }
+                    })
+                    .collect();
+
+                for (id, &level) in input_gate_ids.iter().zip(&levels) {
+                    if engine.gates[id].get_outputs()[0] != level {
+                        engine.toggle_input(id)?;
+                    }
+                }
+                let stable = engine.run_until_stable(TRUTH_TABLE_SETTLE_STEPS);
+
+                let outputs = output_gate_ids
+                    .iter()
+                    .flat_map(|id| engine.observed_states(id))
+                    .map(|s| s.to_u8())
+                    .collect();
+
+                rows.push(TruthTableRow {
+                    inputs: levels.iter().map(|s| s.to_u8()).collect(),
+                    outputs,
+                    stable,
+                });
+            }
+            Ok(rows)
+        })?;
+
+        Ok(TruthTable {
+            input_ids: input_gate_ids,
+            output_ids: output_gate_ids,
+            rows,
+        })
+    }
+
+    /// Run `analysis` against the circuit, then put back the simulation
+    /// state and everything the run has recorded, so probing leaves no
+    /// trace in history, trace, counters, conflicts, glitches, assertions or
+    /// the command log
+    ///
+    /// `analysis` gets the state from before probing, for restarting from it.
+    /// Breakpoints and assertions are suspended meanwhile.
+    fn probe<T>(&mut self, analysis: impl FnOnce(&mut Self, &EngineSnapshot) -> T) -> T {
+        let saved = self.export_state();
+        let records = self.take_run_records();
+        let result = analysis(self, &saved);
+        self.import_state(saved);
+        self.restore_run_records(records);
+        result
+    }
+
+    /// Move the run's records out, leaving them empty
+    fn take_run_records(&mut self) -> RunRecords {
+        let wire_transitions = self
+            .wires
+            .iter_mut()
+            .map(|(id, wire)| {
+                let count = std::mem::take(&mut wire.transition_count);
+                (id.clone(), (count, std::mem::take(&mut wire.transition_times)))
+            })
+            .collect();
+        RunRecords {
+            command_log: self.command_log.take(),
+            history: self.history.take(),
+            traced_signals: std::mem::take(&mut self.traced_signals),
+            trace: std::mem::take(&mut self.trace),
+            gate_transition_counts: std::mem::take(&mut self.gate_transition_counts),
+            wire_transitions,
+            conflicts: std::mem::take(&mut self.conflicts),
+            unstable_gates: std::mem::take(&mut self.unstable_gates),
+            output_transitions: std::mem::take(&mut self.output_transitions),
+            glitches: std::mem::take(&mut self.glitches),
+            breakpoints: std::mem::take(&mut self.breakpoints),
+            breakpoint_hit: self.breakpoint_hit.take(),
+            assertions: std::mem::take(&mut self.assertions),
+            assertion_failures: std::mem::take(&mut self.assertion_failures),
+        }
+    }
+
+    /// Put back records taken by `take_run_records`
+    fn restore_run_records(&mut self, records: RunRecords) {
+        self.command_log = records.command_log;
+        self.history = records.history;
+        self.traced_signals = records.traced_signals;
+        self.trace = records.trace;
+        self.gate_transition_counts = records.gate_transition_counts;
+        for (id, (count, times)) in records.wire_transitions {
+            if let Some(wire) = self.wires.get_mut(&id) {
+                wire.transition_count = count;
+                wire.transition_times = times;
+            }
+        }
+        self.conflicts = records.conflicts;
+        self.unstable_gates = records.unstable_gates;
+        self.output_transitions = records.output_transitions;
+        self.glitches = records.glitches;
+        self.breakpoints = records.breakpoints;
+        self.breakpoint_hit = records.breakpoint_hit;
+        self.assertions = records.assertions;
+        self.assertion_failures = records.assertion_failures;
+    }
+
+    /// Number of wire bits driven by a gate output port
+    pub fn get_fanout(&self, gate_id: &str, port: u32) -> usize {
+        self.source_index.get(gate_id, port).len()
+    }
+
+    /// Set how many inputs one output may drive before it is reported by
+    /// `get_fanout_violations`
+    pub fn set_fanout_limit(&mut self, limit: usize) {
+        self.fanout_limit = limit;
+    }
+
+    /// (gate ID, output port, fan-out) for every output driving more inputs
+    /// than the fan-out limit, sorted by gate ID and port
+    pub fn get_fanout_violations(&self) -> Vec<(String, u32, usize)> {
+        let mut fanouts: HashMap<(&str, u32), usize> = HashMap::new();
+        for wire in self.wires.values().filter(|wire| !wire.bidirectional) {
+            for bit in 0..wire.width() {
+                *fanouts
+                    .entry((wire.source_gate_id.as_str(), wire.source_port_index.saturating_add(bit)))
+                    .or_insert(0) += 1;
+            }
+        }
+
+        let mut violations: Vec<(String, u32, usize)> = fanouts
+            .into_iter()
+            .filter(|&(_, count)| count > self.fanout_limit)
+            .map(|((gate_id, port), count)| (gate_id.to_string(), port, count))
+            .collect();
+        violations.sort();
+        violations
+    }
+
+    /// Worst-case delay of a gate's output at the current delay corner
+    fn worst_delay(&self, gate: &dyn Gate) -> u64 {
+        gate.corner_delay(self.delay_corner)
+            .unwrap_or_else(|| gate.rise_delay().max(gate.fall_delay()))
+            .max(1)
+    }
+
+    /// Longest-delay path from any `from` gate to any `to` gate
+    ///
+    /// A path accumulates the delay of every gate it leaves plus wire
+    /// delays, so the total is the time from a start gate switching until
+    /// the change reaches the end gate's input. Paths stop at sequential
+    /// gates (flip-flops, registers), which breaks feedback through them;
+    /// a loop of purely combinational gates is an error. Ties go to the
+    /// first end gate by ID.
+    pub fn compute_critical_path(&self, from: Vec<String>, to: Vec<String>) -> Result<PathReport, SimError> {
+        if let Some(id) = from.iter().chain(&to).find(|id| !self.gates.contains_key(*id)) {
+            return Err(SimError::GateNotFound(id.clone()));
+        }
+
+        // Fan-out of each gate as (target, wire delay), sorted so traversal
+        // order does not depend on hash order. Dangling wires (see
+        // `validate_wires`) are skipped.
+        let mut edges: HashMap<&str, Vec<(&str, u64)>> = HashMap::new();
+        let connected = |wire: &&Wire| {
+            !wire.bidirectional && self.gates.contains_key(&wire.source_gate_id) && self.gates.contains_key(&wire.target_gate_id)
+        };
+        for wire in self.wires.values().filter(connected) {
+            edges
+                .entry(wire.source_gate_id.as_str())
+                .or_default()
+                .push((wire.target_gate_id.as_str(), wire.delay));
+        }
+        for targets in edges.values_mut() {
+            targets.sort();
+        }
+        let is_boundary = |id: &str| self.gates.get(id).is_some_and(|gate| gate.is_sequential());
+
+        let mut starts: Vec<&str> = from.iter().map(String::as_str).collect();
+        starts.sort();
+        starts.dedup();
+
+        // Depth-first search from the start gates without entering sequential
+        // gates; finished gates are collected in post-order
+        let mut finished: Vec<&str> = Vec::new();
+        let mut done: HashMap<&str, bool> = HashMap::new();
+        for &start in &starts {
+            if done.contains_key(start) {
+                continue;
+            }
+            done.insert(start, false);
+            let mut stack: Vec<(&str, usize)> = vec![(start, 0)];
+            while let Some((gate_id, next)) = stack.pop() {
+                let Some(&(target, _)) = edges.get(gate_id).and_then(|targets| targets.get(next)) else {
+                    done.insert(gate_id, true);
+                    finished.push(gate_id);
+                    continue;
+                };
+                stack.push((gate_id, next + 1));
+                if is_boundary(target) {
+                    continue;
+                }
+                match done.get(target) {
+                    Some(true) => {}
+                    Some(false) => {
+                        let first = stack.iter().position(|&(id, _)| id == target).unwrap_or(0);
+                        return Err(SimError::CombinationalLoop(
+                            stack[first..].iter().map(|&(id, _)| id.to_string()).collect(),
+                        ));
+                    }
+                    None => {
+                        done.insert(target, false);
+                        stack.push((target, 0));
+                    }
+                }
+            }
+        }
+
+        // Relax edges in topological order. Sequential gates only collect
+        // arrivals as path ends, separately from their own start time.
+        let mut arrivals: HashMap<&str, (u64, Option<&str>)> =
+            starts.iter().map(|&start| (start, (0, None))).collect();
+        let mut boundary_arrivals: HashMap<&str, (u64, Option<&str>)> = HashMap::new();
+        for &gate_id in finished.iter().rev() {
+            let departure = arrivals[gate_id].0.saturating_add(self.worst_delay(self.gates[gate_id].as_ref()));
+            for &(target, wire_delay) in edges.get(gate_id).into_iter().flatten() {
+                let arrival = departure.saturating_add(wire_delay);
+                let slots = if is_boundary(target) { &mut boundary_arrivals } else { &mut arrivals };
+                if slots.get(target).is_none_or(|&(best, _)| arrival > best) {
+                    slots.insert(target, (arrival, Some(gate_id)));
+                }
+            }
+        }
+
+        let mut ends: Vec<&str> = to.iter().map(String::as_str).collect();
+        ends.sort();
+        ends.dedup();
+        let mut critical: Option<(&str, u64, Option<&str>)> = None;
+        for end in ends {
+            let reached = if is_boundary(end) {
+                boundary_arrivals.get(end).or_else(|| arrivals.get(end))
+            } else {
+                arrivals.get(end)
+            };
+            if let Some(&(arrival, previous)) = reached {
+                if critical.is_none_or(|(_, best, _)| arrival > best) {
+                    critical = Some((end, arrival, previous));
+                }
+            }
+        }
+
+        let mut report = PathReport { path: Vec::new(), arrival_times: Vec::new(), total_delay: 0 };
+        if let Some((end, total_delay, mut previous)) = critical {
+            report.path.push(end.to_string());
+            report.arrival_times.push(total_delay);
+            while let Some(gate_id) = previous {
+                let (arrival, before) = arrivals[gate_id];
+                report.path.push(gate_id.to_string());
+                report.arrival_times.push(arrival);
+                previous = before;
+            }
+            report.path.reverse();
+            report.arrival_times.reverse();
+            report.total_delay = total_delay;
+        }
+        Ok(report)
+    }
+
+    /// Disable a gate without removing it: its evaluation is skipped and its
+    /// outputs float at HiZ. Re-enabling schedules a fresh evaluation.
+    pub fn set_gate_enabled(&mut self, gate_id: &str, enabled: bool) -> Result<(), SimError> {
+        if !self.gates.contains_key(gate_id) {
+            return Err(SimError::GateNotFound(gate_id.to_string()));
+        }
+        self.log_command(|| Command::SetGateEnabled { gate_id: gate_id.to_string(), enabled });
+
+        if enabled {
+            if self.disabled_gates.remove(gate_id) {
+                self.evaluation_counts.clear();
+                self.schedule_gate_evaluation(gate_id.to_string(), self.current_time);
+            }
+        } else if self.disabled_gates.insert(gate_id.to_string()) {
+            self.float_outputs(gate_id);
+        }
+        Ok(())
+    }
+
+    /// Whether a gate is currently enabled (false for unknown IDs)
+    pub fn is_gate_enabled(&self, gate_id: &str) -> bool {
+        self.gates.contains_key(gate_id) && !self.disabled_gates.contains(gate_id)
+    }
+
+    /// Hold a gate output at `state` whatever the gate computes, e.g. to
+    /// inject a stuck-at fault; takes effect immediately
+    pub fn force_output(&mut self, gate_id: &str, port: usize, state: StateType) -> Result<(), SimError> {
+        let output_count = self
+            .gates
+            .get(gate_id)
+            .ok_or_else(|| SimError::GateNotFound(gate_id.to_string()))?
+            .output_count();
+        if port >= output_count {
+            return Err(SimError::OutputOutOfRange { gate_id: gate_id.to_string(), port, output_count });
+        }
+        self.log_command(|| Command::ForceOutput { gate_id: gate_id.to_string(), port, state: state.to_u8() });
+        self.forced_outputs.entry(gate_id.to_string()).or_default().insert(port, state);
+        self.drive_output(gate_id, port, state);
+        Ok(())
+    }
+
+    /// Return a forced output to the gate's own logic, re-evaluating the
+    /// gate now; no-op if the output is not forced
+    pub fn release_output(&mut self, gate_id: &str, port: usize) -> Result<(), SimError> {
+        if !self.gates.contains_key(gate_id) {
+            return Err(SimError::GateNotFound(gate_id.to_string()));
+        }
+        let forced = match self.forced_outputs.get_mut(gate_id) {
+            Some(forced) => forced,
+            None => return Ok(()),
+        };
+        if forced.remove(&port).is_none() {
+            return Ok(());
+        }
+        if forced.is_empty() {
+            self.forced_outputs.remove(gate_id);
+        }
+        self.log_command(|| Command::ReleaseOutput { gate_id: gate_id.to_string(), port });
+        self.evaluation_counts.clear();
+        self.schedule_gate_evaluation(gate_id.to_string(), self.current_time);
+        Ok(())
+    }
+
+    /// Hold a gate output stuck at ONE (`value`) or ZERO until released
+    pub fn inject_stuck_at(&mut self, gate_id: &str, port: usize, value: bool) -> Result<(), SimError> {
+        self.force_output(gate_id, port, if value { StateType::One } else { StateType::Zero })
+    }
+
+    /// Grade test vectors against every single stuck-at fault
+    ///
+    /// Each vector is a set of input levels (as for `set_inputs`) applied in
+    /// turn, letting the circuit settle after each. Every output port of
+    /// every gate is stuck at ZERO and then ONE, and a fault counts as
+    /// detected by the first vector after which the observed states of the
+    /// output gates differ from the fault-free run. Every run starts from
+    /// the current state with no outputs forced, and the engine is left as
+    /// it was (see `probe`).
+    pub fn run_fault_campaign(
+        &mut self,
+        test_vectors: Vec<Vec<(String, bool)>>,
+        output_gate_ids: Vec<String>,
+    ) -> Result<FaultReport, SimError> {
+        if let Some(id) = output_gate_ids.iter().find(|id| !self.gates.contains_key(*id)) {
+            return Err(SimError::GateNotFound(id.clone()));
+        }
+        if let Some((id, _)) = test_vectors.iter().flatten().find(|(id, _)| !self.gates.contains_key(id)) {
+            return Err(SimError::GateNotFound(id.clone()));
+        }
+
+        let mut sites: Vec<(String, usize)> = self
+            .gates
+            .iter()
+            .flat_map(|(id, gate)| (0..gate.output_count()).map(move |port| (id.clone(), port)))
+            .collect();
+        sites.sort();
+
+        let faults = self.probe(|engine, saved| {
+            engine.forced_outputs.clear();
+            let golden = engine.observe_vectors(&test_vectors, &output_gate_ids);
+
+            let mut faults = Vec::with_capacity(sites.len() * 2);
+            for (gate_id, port) in sites {
+                for stuck_at in [StateType::Zero, StateType::One] {
+                    engine.import_state(saved.clone());
+                    engine.forced_outputs = HashMap::from([(gate_id.clone(), HashMap::from([(port, stuck_at)]))]);
+                    engine.drive_output(&gate_id, port, stuck_at);
+                    let observed = engine.observe_vectors(&test_vectors, &output_gate_ids);
+                    let detected_by = golden.iter().zip(&observed).position(|(expected, actual)| expected != actual);
+                    faults.push(FaultResult { gate_id: gate_id.clone(), port, stuck_at: stuck_at.to_u8(), detected_by });
+                }
+            }
+            faults
+        });
+
+        let detected = faults.iter().filter(|fault| fault.detected_by.is_some()).count();
+        let coverage = if faults.is_empty() { 1.0 } else { detected as f64 / faults.len() as f64 };
+        Ok(FaultReport { faults, detected, coverage })
+    }
+
+    /// Apply each test vector in turn and record the settled states of the
+    /// output gates after each
+    fn observe_vectors(&mut self, test_vectors: &[Vec<(String, bool)>], output_gate_ids: &[String]) -> Vec<Vec<StateType>> {
+        test_vectors
+            .iter()
+            .map(|vector| {
+                // IDs were checked by the caller
+                let _ = self.set_inputs(vector.clone());
+                self.run_until_stable(TRUTH_TABLE_SETTLE_STEPS);
+                output_gate_ids.iter().flat_map(|id| self.observed_states(id)).collect()
+            })
+            .collect()
+    }
+
+    /// Drive every output of a gate to HiZ
+    fn float_outputs(&mut self, gate_id: &str) {
+        let output_count = self.gates.get(gate_id).map_or(0, |gate| gate.output_count());
+        for port in 0..output_count {
+            self.drive_output(gate_id, port, StateType::HiZ);
+        }
+    }
+
+    /// Drive a gate output to an arbitrary state and propagate it, without
+    /// evaluating the gate (used to feed subcircuit inputs)
+    pub(crate) fn drive_output(&mut self, gate_id: &str, port: usize, state: StateType) {
+        let delay = match self.gates.get_mut(gate_id) {
+            Some(gate) => {
+                gate.set_output(port, state);
+                gate.delay()
+            }
+            None => return,
+        };
+        self.evaluation_counts.clear();
+        self.dirty_gates.insert(gate_id.to_string());
+        self.propagate_output(gate_id, port, state, delay);
+    }
+
+    /// States observed on a gate: its outputs, or its inputs if it has none
+    /// (e.g. LEDs). Empty for unknown IDs.
+    pub(crate) fn observed_states(&self, gate_id: &str) -> Vec<StateType> {
+        match self.gates.get(gate_id) {
+            Some(gate) if gate.output_count() > 0 => gate.get_outputs().to_vec(),
+            Some(gate) => gate.get_inputs().to_vec(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Remove a gate at runtime, dropping its wires and pending events
+    ///
+    /// Gates that were driven by the removed gate have their inputs
+    /// re-resolved from any remaining drivers.
+    pub fn remove_gate(&mut self, gate_id: &str) -> Result<(), SimError> {
+        if self.gates.remove(gate_id).is_none() {
+            return Err(SimError::GateNotFound(gate_id.to_string()));
+        }
+        self.log_command(|| Command::RemoveGate { gate_id: gate_id.to_string() });
+        self.event_queue.remove_events_for_gate(gate_id);
+        self.evaluation_counts.remove(gate_id);
+        self.unstable_gates.remove(gate_id);
+        self.unsynced_gates.remove(gate_id);
+        self.gate_transition_counts.remove(gate_id);
+        self.conflicts.retain(|key, _| key.gate_id() != gate_id);
+        self.disabled_gates.remove(gate_id);
+        self.forced_outputs.remove(gate_id);
+        self.gate_groups.remove(gate_id);
+
+        let mut orphaned_ports: Vec<(String, u32)> = Vec::new();
+        let mut orphaned_pins: Vec<(String, u32)> = Vec::new();
+        let mut wire_ids: Vec<String> = [&self.source_index, &self.target_index, &self.pin_index]
+            .into_iter()
+            .flat_map(|index| index.ports_of(gate_id))
+            .flat_map(|(_, ids)| ids.iter().cloned())
+            .collect();
+        wire_ids.sort();
+        wire_ids.dedup();
+        for wire_id in wire_ids {
+            let wire = match self.wires.remove(&wire_id) {
+                Some(wire) => wire,
+                None => continue,
+            };
+            self.unindex_wire(&wire);
+            if wire.bidirectional {
+                for bit in 0..wire.width() as usize {
+                    orphaned_pins.extend(wire.pins(bit));
+                }
+            } else if wire.target_gate_id != gate_id {
+                for bit in 0..wire.width() {
+                    orphaned_ports.push((wire.target_gate_id.clone(), wire.target_port_index.saturating_add(bit)));
+                }
+            }
+        }
+
+        orphaned_ports.sort();
+        for (target_gate_id, port) in orphaned_ports {
+            self.update_target_input(target_gate_id, port, self.current_time);
+        }
+        self.resolve_pins(orphaned_pins);
+        Ok(())
+    }
+
+    /// Set the period of a clock gate (the gate clamps it to at least 1);
+    /// no-op for other gates
+    pub fn set_clock_period(&mut self, gate_id: &str, period: u64) -> Result<(), SimError> {
+        let gate = self
+            .gates
+            .get_mut(gate_id)
+            .ok_or_else(|| SimError::GateNotFound(gate_id.to_string()))?;
+        gate.set_period(period);
+        self.log_command(|| Command::SetClockPeriod { gate_id: gate_id.to_string(), period });
+        Ok(())
+    }
+
+    /// Press a PULSE button, holding its output high for `width` time units
+    ///
+    /// The gate evaluates now and again at `current_time + width`, when the
+    /// output returns low. Firing an active pulse restarts it.
+    pub fn fire_pulse(&mut self, gate_id: &str, width: u64) -> Result<(), SimError> {
+        let gate = self
+            .gates
+            .get_mut(gate_id)
+            .ok_or_else(|| SimError::GateNotFound(gate_id.to_string()))?;
+        if gate.gate_type() != "PULSE" {
+            return Err(SimError::UnexpectedGateType {
+                gate_id: gate_id.to_string(),
+                expected: "PULSE".to_string(),
+            });
+        }
+        let end_time = self.current_time.saturating_add(width.max(1));
+        gate.fire_pulse(end_time);
+        self.log_command(|| Command::FirePulse { gate_id: gate_id.to_string(), width });
+        self.evaluation_counts.clear();
+        self.schedule_gate_evaluation(gate_id.to_string(), self.current_time);
+        self.schedule_gate_evaluation(gate_id.to_string(), end_time);
+        Ok(())
+    }
+
+    /// Shift a clock gate's phase by `offset` time units
+    ///
+    /// A clock toggles every `period` units, so an offset of `period / 2`
+    /// gives a quadrature (90°) clock. Takes effect from the clock's next
+    /// evaluation.
+    pub fn set_clock_phase(&mut self, gate_id: &str, offset: u64) -> Result<(), SimError> {
+        let gate = self
+            .gates
+            .get_mut(gate_id)
+            .ok_or_else(|| SimError::GateNotFound(gate_id.to_string()))?;
+        gate.set_phase(offset);
+        self.log_command(|| Command::SetClockPhase { gate_id: gate_id.to_string(), offset });
+        Ok(())
+    }
+
+    /// Set every gate output and wire bit to a pseudo-random ZERO/ONE derived
+    /// from `seed`, then schedule all gates to settle from that power-on state
+    ///
+    /// The same seed always produces the same state. `reset` still restores
+    /// the gates' defined reset values.
+    pub fn randomize_initial_state(&mut self, seed: u64) {
+        self.log_command(|| Command::RandomizeInitialState { seed });
+        let mut rng = XorShift64::new(seed);
+        self.evaluation_counts.clear();
+        self.mark_all_dirty();
+
+        let mut gate_ids: Vec<String> = self.gates.keys().cloned().collect();
+        gate_ids.sort();
+        for gate_id in &gate_ids {
+            if let Some(gate) = self.gates.get_mut(gate_id) {
+                for i in 0..gate.output_count() {
+                    gate.set_output(i, rng.next_level());
+                }
+            }
+        }
+
+        let mut wire_ids: Vec<String> = self.wires.keys().cloned().collect();
+        wire_ids.sort();
+        let mut ports: Vec<(String, u32)> = Vec::new();
+        for wire_id in &wire_ids {
+            if let Some(wire) = self.wires.get_mut(wire_id) {
+                for bit in 0..wire.state.len() {
+                    wire.state[bit] = rng.next_level();
+                    if !wire.bidirectional {
+                        ports.push((wire.target_gate_id.clone(), wire.target_port_index.saturating_add(bit as u32)));
+                    }
+                }
+            }
+        }
+
+        ports.sort();
+        ports.dedup();
+        for (target_gate_id, port) in ports {
+            self.update_target_input(target_gate_id, port, self.current_time);
+        }
+        for gate_id in gate_ids {
+            self.schedule_gate_evaluation(gate_id, self.current_time);
+        }
+    }
+
+    /// Time of the earliest pending event
+    pub(crate) fn next_event_time(&self) -> Option<u64> {
+        self.event_queue.peek().map(|event| event.time)
+    }
+
+    /// Time of the earliest pending event other than a self-timed gate's
+    /// own wakeup (see `Gate::next_wakeup`), i.e. of activity still in flight
+    pub(crate) fn next_activity_time(&self) -> Option<u64> {
+        self.event_queue
+            .pending_events()
+            .into_iter()
+            .find(|event| {
+                let wakeup = event.port_index < 0
+                    && self
+                        .gates
+                        .get(&event.gate_id)
+                        .is_some_and(|gate| gate.next_wakeup(event.time.saturating_sub(1)) == Some(event.time));
+                !wakeup
+            })
+            .map(|event| event.time)
+    }
+
+    /// Whether any gate holds state (see `Gate::is_sequential`)
+    pub(crate) fn has_sequential_gates(&self) -> bool {
+        self.gates.values().any(|gate| gate.is_sequential())
+    }
+
+    /// Model a power-on reset circuit: return every sequential gate
+    /// (flip-flops, latches, registers, counters, and subcircuits containing
+    /// any) to its reset state and hold it there for `hold_cycles` time units
+    /// while combinational logic settles, then release it to follow its
+    /// inputs again
+    ///
+    /// Held gates ignore clock edges, and a clock that is already high at
+    /// release does not count as an edge.
+    pub fn power_on_reset(&mut self, hold_cycles: u64) {
+        self.log_command(|| Command::PowerOnReset { hold_cycles });
+        self.evaluation_counts.clear();
+
+        let mut held: Vec<String> = self
+            .gates
+            .iter()
+            .filter(|(_, gate)| gate.is_sequential())
+            .map(|(gate_id, _)| gate_id.clone())
+            .collect();
+        held.sort();
+        for gate_id in &held {
+            if let Some(gate) = self.gates.get_mut(gate_id) {
+                gate.reset_mode(ResetMode::Full);
+            }
+            self.dirty_gates.insert(gate_id.clone());
+            self.unsynced_gates.insert(gate_id.clone());
+        }
+
+        // The reset cleared the held gates' inputs; read them back from their wires
+        let mut ports: Vec<(String, u32)> = self
+            .wires
+            .values()
+            .filter(|w| !w.bidirectional && held.binary_search(&w.target_gate_id).is_ok())
+            .flat_map(|w| (0..w.width()).map(move |bit| (w.target_gate_id.clone(), w.target_port_index.saturating_add(bit))))
+            .collect();
+        ports.sort();
+        ports.dedup();
+        for (target_gate_id, port) in ports {
+            self.update_target_input(target_gate_id, port, self.current_time);
+        }
+        self.apply_policy_to_unconnected_inputs();
+
+        let release = self.current_time.saturating_add(hold_cycles);
+        self.power_on_reset_until = Some(release);
+        for gate_id in held {
+            self.schedule_gate_evaluation(gate_id.clone(), self.current_time);
+            self.schedule_gate_evaluation(gate_id, release);
+        }
+    }
+
+    /// Start recording external calls into a fresh command log, or stop and
+    /// discard it (see `export_command_log`)
+    pub fn set_command_logging(&mut self, enabled: bool) {
+        self.command_log = enabled.then(Vec::new);
+    }
+
+    /// Commands recorded since logging started or the circuit was loaded
+    pub fn command_log(&self) -> Option<&[LoggedCommand]> {
+        self.command_log.as_deref()
+    }
+
+    /// Append a command to the log, if logging; consecutive steps are merged
+    /// into one entry
+    fn log_command(&mut self, command: impl FnOnce() -> Command) {
+        let time = self.current_time;
+        let log = match self.command_log.as_mut() {
+            Some(log) => log,
+            None => return,
+        };
+        let command = command();
+        if let (Some(LoggedCommand { command: Command::Step { count }, .. }), Command::Step { count: more }) =
+            (log.last_mut(), &command)
+        {
+            *count += more;
+            return;
+        }
+        log.push(LoggedCommand { time, command });
+    }
+
+    /// Keep the last `capacity` pre-step states so `step_back` can undo steps
+    ///
+    /// Each entry is a full `export_state` snapshot (every gate, wire and
+    /// pending event), so memory grows with circuit size times capacity and
+    /// every step pays for one export. A capacity of 0 disables history and
+    /// frees the buffer; disabled history costs nothing per step.
+    pub fn enable_history(&mut self, capacity: usize) {
+        self.history_capacity = capacity;
+        if capacity == 0 {
+            self.history = None;
+            return;
+        }
+        let history = self.history.get_or_insert_with(VecDeque::new);
+        while history.len() > capacity {
+            history.pop_front();
+        }
+    }
+
+    /// Restore the state from before the most recent recorded step; returns
+    /// false if there is no history to go back to
+    pub fn step_back(&mut self) -> bool {
+        match self.history.as_mut().and_then(|history| history.pop_back()) {
+            Some(snapshot) => {
+                self.import_state(snapshot);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn record_history(&mut self) {
+        let snapshot = self.export_state();
+        if let Some(history) = self.history.as_mut() {
+            if history.len() == self.history_capacity {
+                history.pop_front();
+            }
+            history.push_back(snapshot);
+        }
+    }
+
+    /// Record gates (by their outputs) and wires to trace on every change,
+    /// replacing any previous selection and clearing recorded samples
+    ///
+    /// IDs matching neither a gate nor a wire are ignored. The current values
+    /// are recorded immediately as the first sample.
+    pub fn trace_signals(&mut self, ids: Vec<String>) {
+        self.traced_signals = ids;
+        self.trace.clear();
+        self.record_trace(self.current_time);
+    }
+
+    /// Samples recorded for each traced signal, keyed by ID
+    pub fn get_trace(&self) -> &HashMap<String, Vec<TraceSample>> {
+        &self.trace
+    }
+
+    /// Discard recorded samples, keeping the traced selection
+    pub fn clear_trace(&mut self) {
+        self.trace.clear();
+    }
+
+    /// Append a sample for every traced signal whose value differs from its
+    /// last recorded one; a later change at the same time replaces the sample
+    fn record_trace(&mut self, time: u64) {
+        for id in &self.traced_signals {
+            let values: Vec<u8> = if let Some(gate) = self.gates.get(id) {
+                gate.get_outputs().iter().map(|s| s.to_u8()).collect()
+            } else if let Some(wire) = self.wires.get(id) {
+                wire.state.iter().map(|s| s.to_u8()).collect()
+            } else {
+                continue;
+            };
+
+            let samples = self.trace.entry(id.clone()).or_default();
+            match samples.last_mut() {
+                Some(last) if last.values == values => {}
+                Some(last) if last.time == time => last.values = values,
+                _ => samples.push(TraceSample { time, values }),
+            }
+        }
+    }
+
+    fn clear_history(&mut self) {
+        if let Some(history) = self.history.as_mut() {
+            history.clear();
+        }
+    }
+
+    /// Choose how undriven (HiZ or unconnected) gate inputs are read
+    ///
+    /// Gates that resolve tri-state drivers themselves (BUS) always see HiZ.
+    /// Existing inputs are re-resolved under the new policy.
+    pub fn set_floating_input_policy(&mut self, policy: FloatingInputPolicy) {
+        self.floating_input_policy = policy;
+
+        let mut ports: Vec<(String, u32)> = self
+            .wires
+            .values()
+            .filter(|w| !w.bidirectional)
+            .flat_map(|w| (0..w.width()).map(move |bit| (w.target_gate_id.clone(), w.target_port_index.saturating_add(bit))))
+            .collect();
+        ports.sort();
+        ports.dedup();
+        for (target_gate_id, port) in ports {
+            self.update_target_input(target_gate_id, port, self.current_time);
+        }
+        let mut pins: Vec<(String, u32)> = Vec::new();
+        for (gate_id, gate) in &self.gates {
+            for output in 0..gate.output_count() {
+                if gate.inout_input(output).is_some() {
+                    pins.push((gate_id.clone(), output as u32));
+                }
+            }
+        }
+        self.resolve_pins(pins);
+        self.apply_policy_to_unconnected_inputs();
+    }
+
+    /// Choose how gate logic treats UNKNOWN inputs
+    ///
+    /// Every gate re-evaluates under the new policy at the current time.
+    pub fn set_unknown_policy(&mut self, policy: UnknownPolicy) {
+        self.unknown_policy = policy;
+        for gate in self.gates.values_mut() {
+            gate.set_unknown_policy(policy);
+        }
+        self.evaluation_counts.clear();
+        self.schedule_all_gates(self.current_time);
+    }
+
+    /// Choose which of each gate's min/typical/max delays output changes
+    /// are scheduled with from now on
+    pub fn set_delay_corner(&mut self, corner: DelayCorner) {
+        self.delay_corner = corner;
+    }
+
+    /// Drive input ports that no wire targets according to the policy
+    fn apply_policy_to_unconnected_inputs(&mut self) {
+        let mut gate_ids: Vec<String> = self.gates.keys().cloned().collect();
+        gate_ids.sort();
+        for gate_id in gate_ids {
+            self.apply_policy_to_floating_ports(&gate_id);
+        }
+    }
+
+    /// Drive one gate's input ports that no wire targets according to the
+    /// policy, scheduling the gate if it has any
+    fn apply_policy_to_floating_ports(&mut self, gate_id: &str) {
+        let level = match self.floating_input_policy {
+            FloatingInputPolicy::AsUnknown => StateType::Unknown,
+            FloatingInputPolicy::AsZero => StateType::Zero,
+            FloatingInputPolicy::AsOne => StateType::One,
+        };
+        let ports = self.unconnected_inputs(gate_id);
+        if ports.is_empty() {
+            return;
+        }
+        if let Some(gate) = self.gates.get_mut(gate_id) {
+            if !gate.passes_high_impedance() {
+                for port in ports {
+                    gate.set_input(port, level);
+                }
+                self.dirty_gates.insert(gate_id.to_string());
+            }
+        }
+        self.schedule_gate_evaluation(gate_id.to_string(), self.current_time);
+    }
+
+    /// Input ports of a gate that no wire targets; inout sense inputs always
+    /// follow their pin's net and are never floating
+    fn unconnected_inputs(&self, gate_id: &str) -> Vec<usize> {
+        let Some(gate) = self.gates.get(gate_id) else { return Vec::new() };
+        let sense_inputs: Vec<usize> =
+            (0..gate.output_count()).filter_map(|output| gate.inout_input(output)).collect();
+        (0..gate.input_count())
+            .filter(|port| !sense_inputs.contains(port))
+            .filter(|&port| self.target_index.get(gate_id, port as u32).is_empty())
+            .collect()
+    }
+
+    /// Set running state
+    pub fn set_running(&mut self, running: bool) {
+        self.running = running;
+    }
+
+    /// Check if simulation is running
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+
+    /// Get current simulation time
+    pub fn get_current_time(&self) -> u64 {
+        self.current_time
+    }
+
+    /// Gates that re-evaluated more than the oscillation threshold without
+    /// the circuit settling (sorted by ID)
+    pub fn get_unstable_gates(&self) -> Vec<String> {
+        let mut gate_ids: Vec<String> = self.unstable_gates.iter().cloned().collect();
+        gate_ids.sort();
+        gate_ids
+    }
+
+    /// Enable or disable glitch recording
+    pub fn set_glitch_detection(&mut self, enabled: bool) {
+        self.glitch_detection = enabled;
+        self.output_transitions.clear();
+    }
+
+    /// Glitches recorded so far, in the order the circuit settled
+    pub fn get_glitches(&self) -> &[GlitchReport] {
+        &self.glitches
+    }
+
+    /// Discard recorded glitches
+    pub fn clear_glitches(&mut self) {
+        self.glitches.clear();
+    }
+
+    /// Input ports that no wire drives, as (gate_id, port_index) sorted by gate
+    pub fn get_floating_inputs(&self) -> Vec<(String, usize)> {
+        let mut floating: Vec<(String, usize)> = self
+            .gates
+            .keys()
+            .flat_map(|gate_id| self.unconnected_inputs(gate_id).into_iter().map(move |port| (gate_id.clone(), port)))
+            .collect();
+        floating.sort();
+        floating
+    }
+
+    /// Current state of the wires carrying each requested label
+    ///
+    /// When several wires share a label they are treated as one net and
+    /// their states resolved together; bus wires contribute bit 0. Labels
+    /// that match no wire are omitted.
+    pub fn get_probe_values(&self, labels: Vec<String>) -> HashMap<String, u8> {
+        let mut values = HashMap::new();
+        for label in labels {
+            let states: Vec<StateType> = self
+                .wires
+                .values()
+                .filter(|w| w.label.as_deref() == Some(label.as_str()))
+                .map(|w| w.state[0])
+                .collect();
+            if !states.is_empty() {
+                values.insert(label, resolve_wire_state(&states).to_u8());
+            }
+        }
+        values
+    }
+
+    /// Number of state changes seen on each wire since initialize/reset
+    pub fn get_wire_transition_counts(&self) -> HashMap<String, u64> {
+        self.wires
+            .iter()
+            .map(|(id, wire)| (id.clone(), wire.transition_count))
+            .collect()
+    }
+
+    /// Output switching per gate since initialize/reset, busiest gate first
+    ///
+    /// Only toggles between ZERO and ONE are counted (settling out of
+    /// UNKNOWN or into HiZ draws no switching current), so the ranking
+    /// approximates where dynamic power is spent. Ties are ordered by ID.
+    pub fn get_activity_report(&self) -> ActivityReport {
+        let mut gates: Vec<GateActivity> = self
+            .gates
+            .iter()
+            .map(|(id, gate)| GateActivity {
+                gate_id: id.clone(),
+                gate_type: gate.gate_type().to_string(),
+                transitions: self.gate_transition_counts.get(id).copied().unwrap_or(0),
+            })
+            .collect();
+        gates.sort_by(|a, b| b.transitions.cmp(&a.transitions).then_with(|| a.gate_id.cmp(&b.gate_id)));
+
+        ActivityReport {
+            time: self.current_time,
+            total_transitions: gates.iter().map(|gate| gate.transitions).sum(),
+            gates,
+        }
+    }
+
+    /// Gate counts by type, wire count and overall health of the circuit
+    pub fn get_summary(&self) -> CircuitSummary {
+        let mut gate_counts: BTreeMap<String, usize> = BTreeMap::new();
+        for gate in self.gates.values() {
+            *gate_counts.entry(gate.gate_type().to_string()).or_insert(0) += 1;
+        }
+        let count_of = |types: &[&str]| types.iter().filter_map(|t| gate_counts.get(*t)).sum();
+
+        CircuitSummary {
+            gate_count: self.gates.len(),
+            wire_count: self.wires.len(),
+            input_count: count_of(&["TOGGLE", "CLOCK", "PULSE"]),
+            output_count: count_of(&["LED"]),
+            has_conflicts: !self.conflicts.is_empty(),
+            has_floating_inputs: !self.get_floating_inputs().is_empty(),
+            gate_counts,
+        }
+    }
+
+    /// Average frequency of a wire over the last `window` time units, in
+    /// cycles (two transitions) per time unit
+    ///
+    /// Only the most recent transitions are remembered per wire, so very long
+    /// windows on fast signals under-count. Returns None for an unknown wire
+    /// or a zero window.
+    pub fn measure_frequency(&self, wire_id: &str, window: u64) -> Option<f64> {
+        let wire = self.wires.get(wire_id)?;
+        if window == 0 {
+            return None;
+        }
+        let start = self.current_time.saturating_sub(window);
+        let transitions = wire.transition_times.iter().filter(|&&time| time > start).count();
+        Some(transitions as f64 / 2.0 / window as f64)
+    }
+
+    /// Reset simulation; `ResetMode::StateOnly` keeps memory contents
+    /// (RAM words) while clearing everything else
+    pub fn reset(&mut self, mode: ResetMode) {
+        self.log_command(|| Command::Reset { mode: mode as u8 });
+        self.clear_history();
+        self.current_time = 0;
+        self.breakpoint_hit = None;
+        self.power_on_reset_until = None;
+        self.assertions.clear();
+        self.assertion_failures.clear();
+        self.event_queue.clear();
+        self.evaluation_counts.clear();
+        self.unstable_gates.clear();
+        self.gate_transition_counts.clear();
+        self.conflicts.clear();
+        self.forced_outputs.clear();
+
+        for gate in self.gates.values_mut() {
+            gate.reset_mode(mode);
+        }
+
+        for wire in self.wires.values_mut() {
+            wire.state.fill(StateType::Unknown);
+            wire.transition_count = 0;
+            wire.transition_times.clear();
+        }
+
+        self.mark_all_dirty();
+        self.apply_policy_to_unconnected_inputs();
+        self.schedule_initial_evaluations();
+        let mut disabled: Vec<String> = self.disabled_gates.iter().cloned().collect();
+        disabled.sort();
+        for gate_id in disabled {
+            self.float_outputs(&gate_id);
+        }
+        self.trace.clear();
+        self.record_trace(0);
+    }
+
+    /// Fingerprint of the current time and every gate's inputs/outputs and
+    /// wire's state; engines in equal states hash equally on any platform
+    ///
+    /// Internal gate state (flip-flop edge history, memory) is not included.
+    pub fn state_hash(&self) -> u64 {
+        let mut hasher = Fnv1a::new();
+        hasher.write_u64(self.current_time);
+
+        let mut gate_ids: Vec<&String> = self.gates.keys().collect();
+        gate_ids.sort();
+        hasher.write_u64(gate_ids.len() as u64);
+        for id in gate_ids {
+            let gate = &self.gates[id];
+            hasher.write_field(id.as_bytes());
+            let inputs: Vec<u8> = gate.get_inputs().iter().map(|s| s.to_u8()).collect();
+            let outputs: Vec<u8> = gate.get_outputs().iter().map(|s| s.to_u8()).collect();
+            hasher.write_field(&inputs);
+            hasher.write_field(&outputs);
+        }
+
+        let mut wire_ids: Vec<&String> = self.wires.keys().collect();
+        wire_ids.sort();
+        hasher.write_u64(wire_ids.len() as u64);
+        for id in wire_ids {
+            hasher.write_field(id.as_bytes());
+            let state: Vec<u8> = self.wires[id].state.iter().map(|s| s.to_u8()).collect();
+            hasher.write_field(&state);
+        }
+
+        hasher.0
+    }
+
+    /// Get current state snapshot, with gates and wires sorted by ID
+    pub fn get_snapshot(&self) -> SimulationSnapshot {
+        self.snapshot_of(|_| true)
+    }
+
+    /// Current state of one gate, including its type and delays
+    ///
+    /// The delays are the ones scheduled with at the active delay corner: a
+    /// gate with a delay for that corner reports it for both edges.
+    pub fn get_gate_state(&self, gate_id: &str) -> Option<GateState> {
+        let gate = self.gates.get(gate_id)?;
+        let mut state = gate_snapshot(gate_id, gate.as_ref(), self.gate_groups.get(gate_id));
+        if let Some(delay) = gate.corner_delay(self.delay_corner) {
+            state.delay = Some(delay);
+            return Some(state);
+        }
+        let delay = gate.delay();
+        state.delay = Some(delay);
+        state.rise_delay = Some(gate.rise_delay()).filter(|&rise| rise != delay);
+        state.fall_delay = Some(gate.fall_delay()).filter(|&fall| fall != delay);
+        Some(state)
+    }
+
+    /// Wire carrying output `source_port` of `source_gate` to input
+    /// `target_port` of `target_gate`, through the same bit for buses
+    ///
+    /// If parallel wires connect the same ports, the one with the lowest ID
+    /// is returned. Bidirectional wires are not matched.
+    pub fn get_wire_between(
+        &self,
+        source_gate: &str,
+        source_port: u32,
+        target_gate: &str,
+        target_port: u32,
+    ) -> Option<WireState> {
+        self.source_index
+            .get(source_gate, source_port)
+            .iter()
+            .filter_map(|id| self.wires.get(id))
+            .filter(|wire| {
+                let bit = wire.source_bit(source_gate, source_port);
+                bit.is_some() && bit == wire.target_bit(target_gate, target_port)
+            })
+            .min_by(|a, b| a.id.cmp(&b.id))
+            .map(wire_snapshot)
+    }
+
+    /// Snapshot of the gates tagged with `group` and every wire with at least
+    /// one end in it, sorted by ID
+    ///
+    /// Wires crossing the group boundary are included so the module's I/O
+    /// shows up as connected; their far-end gates are not.
+    pub fn get_group_snapshot(&self, group: &str) -> SimulationSnapshot {
+        self.snapshot_of(|gate_id| self.gate_groups.get(gate_id).is_some_and(|g| g == group))
+    }
+
+    /// Snapshot of the gates selected by `include` and the wires touching them
+    fn snapshot_of(&self, include: impl Fn(&str) -> bool) -> SimulationSnapshot {
+        let mut gates: Vec<GateState> = self
+            .gates
+            .iter()
+            .filter(|(id, _)| include(id))
+            .map(|(id, gate)| gate_snapshot(id, gate.as_ref(), self.gate_groups.get(id)))
+            .collect();
+        gates.sort_by(|a, b| a.id.cmp(&b.id));
+
+        let mut wires: Vec<WireState> = self
+            .wires
+            .values()
+            .filter(|wire| include(&wire.source_gate_id) || include(&wire.target_gate_id))
+            .map(wire_snapshot)
+            .collect();
+        wires.sort_by(|a, b| a.id.cmp(&b.id));
+
+        SimulationSnapshot {
+            time: self.current_time,
+            gates,
+            wires,
+        }
+    }
+
+    /// Mark every gate and wire as changed, and every gate as needing to
+    /// resend its outputs (after structural resets)
+    fn mark_all_dirty(&mut self) {
+        self.dirty_gates.extend(self.gates.keys().cloned());
+        self.dirty_wires.extend(self.wires.keys().cloned());
+        self.unsynced_gates.extend(self.gates.keys().cloned());
+    }
+
+    /// Get only the gates and wires that changed since the previous delta,
+    /// then clear the change tracking
+    pub fn take_state_delta(&mut self) -> SimulationSnapshot {
+        let gates: Vec<GateState> = self
+            .dirty_gates
+            .drain()
+            .filter_map(|id| {
+                let gate = self.gates.get(&id)?;
+                Some(gate_snapshot(&id, gate.as_ref(), self.gate_groups.get(&id)))
+            })
+            .collect();
+
+        let wires: Vec<WireState> = self
+            .dirty_wires
+            .drain()
+            .filter_map(|id| self.wires.get(&id).map(wire_snapshot))
+            .collect();
+
+        SimulationSnapshot {
+            time: self.current_time,
+            gates,
+            wires,
+        }
+    }
+
+    /// Export the simulation state, including internal gate state
+    /// (flip-flop edge history, clock phase), pending events, forced outputs
+    /// and disabled gates (see `EngineSnapshot` for what is left out)
+    pub fn export_state(&self) -> EngineSnapshot {
+        let mut gates: Vec<GateSnapshot> = self
+            .gates
+            .iter()
+            .map(|(id, gate)| GateSnapshot {
+                id: id.clone(),
+                input_states: gate.get_inputs().iter().map(|s| s.to_u8()).collect(),
+                output_states: gate.get_outputs().iter().map(|s| s.to_u8()).collect(),
+                internal_state: gate.save_state(),
+            })
+            .collect();
+        gates.sort_by(|a, b| a.id.cmp(&b.id));
+
+        let mut wires = self.get_snapshot().wires;
+        wires.sort_by(|a, b| a.id.cmp(&b.id));
+
+        let events = self
+            .event_queue
+            .pending_events()
+            .into_iter()
+            .map(|e| EventSnapshot {
+                time: e.time,
+                gate_id: e.gate_id,
+                port_index: e.port_index,
+                new_state: e.new_state.to_u8(),
+            })
+            .collect();
+
+        let mut forced_outputs: Vec<(String, usize, u8)> = self
+            .forced_outputs
+            .iter()
+            .flat_map(|(id, ports)| ports.iter().map(move |(&port, state)| (id.clone(), port, state.to_u8())))
+            .collect();
+        forced_outputs.sort();
+        let mut disabled_gates: Vec<String> = self.disabled_gates.iter().cloned().collect();
+        disabled_gates.sort();
+
+        EngineSnapshot {
+            time: self.current_time,
+            gates,
+            wires,
+            events,
+            forced_outputs,
+            disabled_gates,
+            power_on_reset_until: self.power_on_reset_until,
+        }
+    }
+
+    /// Restore engine state exported by `export_state`
+    ///
+    /// The snapshot is applied onto the currently loaded circuit: gates and
+    /// wires are matched by ID and entries for unknown IDs are ignored.
+    pub fn import_state(&mut self, snapshot: EngineSnapshot) {
+        self.current_time = snapshot.time;
+        self.evaluation_counts.clear();
+        self.unstable_gates.clear();
+        self.output_transitions.clear();
+        self.mark_all_dirty();
+
+        for gate_snapshot in &snapshot.gates {
+            if let Some(gate) = self.gates.get_mut(&gate_snapshot.id) {
+                // Saved inputs already carry inversion bubbles, so undo them
+                // before set_input applies them again
+                let inverted: Vec<bool> = gate.inverted_inputs().map(|m| m.to_vec()).unwrap_or_default();
+                for (i, &state) in gate_snapshot.input_states.iter().enumerate() {
+                    let state = StateType::from_u8(state);
+                    let state = if inverted.get(i).copied().unwrap_or(false) { state.not() } else { state };
+                    gate.set_input(i, state);
+                }
+                for (i, &state) in gate_snapshot.output_states.iter().enumerate() {
+                    gate.set_output(i, StateType::from_u8(state));
+                }
+                gate.load_state(&gate_snapshot.internal_state);
+            }
+        }
+
+        for wire_state in &snapshot.wires {
+            if let Some(wire) = self.wires.get_mut(&wire_state.id) {
+                let width = wire.state.len();
+                wire.state = wire_state.state.to_states(width);
+            }
+        }
+
+        // Events are stored in pop order, so re-pushing preserves their ordering
+        self.event_queue.clear();
+        for event in snapshot.events {
+            self.event_queue.push(
+                event.time,
+                event.gate_id,
+                event.port_index,
+                StateType::from_u8(event.new_state),
+            );
+        }
+
+        // Outputs were restored above, so forces and disables need no re-drive
+        self.forced_outputs.clear();
+        for (gate_id, port, state) in snapshot.forced_outputs {
+            if self.gates.contains_key(&gate_id) {
+                self.forced_outputs.entry(gate_id).or_default().insert(port, StateType::from_u8(state));
+            }
+        }
+        self.disabled_gates = snapshot.disabled_gates.into_iter().filter(|id| self.gates.contains_key(id)).collect();
+        self.power_on_reset_until = snapshot.power_on_reset_until;
+    }
+}
+
+impl Default for SimulationEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{gate, wire};
+    use crate::SubcircuitDefinition;
+
+    fn run_steps(engine: &mut SimulationEngine, count: usize) {
+        for _ in 0..count {
+            engine.step();
+        }
+    }
+
+    fn outputs(engine: &SimulationEngine, gate_id: &str) -> Vec<StateType> {
+        engine.gates[gate_id].get_outputs().to_vec()
+    }
+
+    fn inputs(engine: &SimulationEngine, gate_id: &str) -> Vec<StateType> {
+        engine.gates[gate_id].get_inputs().to_vec()
+    }
+
+    #[test]
+    fn test_bus_wire_maps_bits_to_consecutive_ports() {
+        let mut engine = SimulationEngine::new();
+        let mut bus = wire("bus", "dff", 0, "and", 0);
+        bus.width = 2;
+        engine.initialize(
+            vec![gate("d", "TOGGLE", 0), gate("clk", "TOGGLE", 0), gate("dff", "DFF", 2), gate("and", "AND", 2)],
+            vec![wire("w_d", "d", 0, "dff", 0), wire("w_clk", "clk", 0, "dff", 1), bus],
+        );
+        run_steps(&mut engine, 5);
+
+        engine.toggle_input("d").unwrap();
+        for _ in 0..3 {
+            engine.toggle_input("clk").unwrap();
+            run_steps(&mut engine, 5);
+        }
+
+        assert_eq!(outputs(&engine, "dff"), vec![StateType::One, StateType::Zero]);
+        assert_eq!(inputs(&engine, "and"), vec![StateType::One, StateType::Zero]);
+        assert_eq!(engine.wires["bus"].state, vec![StateType::One, StateType::Zero]);
+    }
+
+    #[test]
+    fn test_inverter_loop_reported_unstable() {
+        let mut engine = SimulationEngine::new();
+        engine.initialize(
+            vec![gate("en", "TOGGLE", 0), gate("osc", "NOR", 2)],
+            vec![wire("w_en", "en", 0, "osc", 0), wire("w_fb", "osc", 0, "osc", 1)],
+        );
+        engine.toggle_input("en").unwrap();
+        run_steps(&mut engine, 10);
+        assert!(engine.get_unstable_gates().is_empty());
+
+        // Releasing the NOR's forcing input turns it into a ring oscillator
+        engine.toggle_input("en").unwrap();
+        run_steps(&mut engine, 3000);
+        assert_eq!(engine.get_unstable_gates(), vec!["osc".to_string()]);
+
+        engine.reset(ResetMode::Full);
+        assert!(engine.get_unstable_gates().is_empty());
+    }
+
+    #[test]
+    fn test_clock_period_sets_output_pattern() {
+        let mut engine = SimulationEngine::new();
+        engine.initialize(vec![gate("clk", "CLOCK", 0)], vec![]);
+        engine.set_clock_period("clk", 3).unwrap();
+
+        let mut transitions = Vec::new();
+        let mut last = outputs(&engine, "clk")[0];
+        for _ in 0..10 {
+            let time = engine.get_current_time();
+            engine.step();
+            let current = outputs(&engine, "clk")[0];
+            if current != last {
+                transitions.push((time, current));
+                last = current;
+            }
+        }
+
+        assert!(transitions.len() >= 3);
+        for pair in transitions.windows(2) {
+            assert_eq!(pair[1].0 - pair[0].0, 3);
+            assert_eq!(pair[1].1, pair[0].1.not());
+        }
+    }
+
+    #[test]
+    fn test_bidirectional_bus_between_transceivers() {
+        let mut bus = wire("bus", "x", 0, "y", 0);
+        bus.bidirectional = true;
+        let mut engine = SimulationEngine::new();
+        let errors = engine.initialize(
+            vec![
+                gate("a", "TOGGLE", 0),
+                gate("oe_a", "TOGGLE", 0),
+                gate("b", "TOGGLE", 0),
+                gate("oe_b", "TOGGLE", 0),
+                gate("x", "TRANSCEIVER", 3),
+                gate("y", "TRANSCEIVER", 3),
+            ],
+            vec![
+                wire("w_a", "a", 0, "x", 0),
+                wire("w_oe_a", "oe_a", 0, "x", 1),
+                wire("w_b", "b", 0, "y", 0),
+                wire("w_oe_b", "oe_b", 0, "y", 1),
+                bus,
+            ],
+        );
+        assert!(errors.is_empty());
+        assert!(engine.get_floating_inputs().is_empty());
+        let bus_state = |engine: &SimulationEngine| engine.wires["bus"].state[0];
+
+        // x drives, both ends read the bus
+        engine.set_inputs(vec![("a".to_string(), true), ("oe_a".to_string(), true)]).unwrap();
+        engine.run_until_stable(20);
+        assert_eq!(bus_state(&engine), StateType::One);
+        assert_eq!(outputs(&engine, "x")[1], StateType::One);
+        assert_eq!(outputs(&engine, "y")[1], StateType::One);
+
+        // Turn the bus around: y drives, x listens
+        engine
+            .set_inputs(vec![("oe_a".to_string(), false), ("oe_b".to_string(), true)])
+            .unwrap();
+        engine.run_until_stable(20);
+        assert_eq!(bus_state(&engine), StateType::Zero);
+        assert_eq!(outputs(&engine, "x")[1], StateType::Zero);
+
+        // Both driving opposite levels
+        engine.set_inputs(vec![("oe_a".to_string(), true)]).unwrap();
+        engine.run_until_stable(20);
+        assert_eq!(bus_state(&engine), StateType::Conflict);
+
+        // Releasing the bus leaves it floating
+        engine
+            .set_inputs(vec![("oe_a".to_string(), false), ("oe_b".to_string(), false)])
+            .unwrap();
+        engine.run_until_stable(20);
+        assert_eq!(bus_state(&engine), StateType::HiZ);
+    }
+
+    #[test]
+    fn test_initial_hold_seeds_latch_state() {
+        let latch = || {
+            let mut q = wire("w_q", "n1", 0, "n2", 1);
+            q.state = WireValue::Scalar(StateType::One.to_u8());
+            let mut qn = wire("w_qn", "n2", 0, "n1", 1);
+            qn.state = WireValue::Scalar(StateType::Zero.to_u8());
+            (
+                vec![gate("r", "TOGGLE", 0), gate("s", "TOGGLE", 0), gate("n1", "NOR", 2), gate("n2", "NOR", 2)],
+                vec![wire("w_r", "r", 0, "n1", 0), wire("w_s", "s", 0, "n2", 0), q, qn],
+            )
+        };
+
+        // Without the hold, the first evaluations see UNKNOWN feedback
+        let mut engine = SimulationEngine::new();
+        let (gates, wires) = latch();
+        engine.initialize(gates, wires);
+        engine.run_until_stable(20);
+        assert_eq!(outputs(&engine, "n1"), vec![StateType::Unknown]);
+
+        let mut engine = SimulationEngine::new();
+        engine.set_initial_hold(true);
+        let (gates, wires) = latch();
+        engine.initialize(gates, wires);
+        assert_eq!(engine.wires["w_q"].state, vec![StateType::One]);
+        engine.step();
+        assert_eq!(engine.get_current_time(), 1);
+        assert_eq!(engine.wires["w_q"].state, vec![StateType::One]);
+        engine.run_until_stable(20);
+        assert_eq!(outputs(&engine, "n1"), vec![StateType::One]);
+        assert_eq!(outputs(&engine, "n2"), vec![StateType::Zero]);
+
+        // Clear the latch, then reset back to the seeded state
+        engine.toggle_input("r").unwrap();
+        engine.run_until_stable(20);
+        engine.toggle_input("r").unwrap();
+        engine.run_until_stable(20);
+        assert_eq!(outputs(&engine, "n1"), vec![StateType::Zero]);
+        engine.reset(ResetMode::Full);
+        engine.run_until_stable(20);
+        assert_eq!(outputs(&engine, "n1"), vec![StateType::One]);
+    }
+
+    #[test]
+    fn test_conflicts_list_contending_drivers() {
+        let mut engine = SimulationEngine::new();
+        engine.initialize(
+            vec![
+                gate("a", "TOGGLE", 0),
+                gate("b", "TOGGLE", 0),
+                gate("buf", "BUFFER", 1),
+                gate("inv", "NOT", 1),
+                gate("out", "LED", 1),
+            ],
+            vec![
+                wire("w_a", "a", 0, "buf", 0),
+                wire("w_b", "b", 0, "buf", 0),
+                wire("w_inv", "buf", 0, "inv", 0),
+                wire("w_out", "inv", 0, "out", 0),
+            ],
+        );
+        engine.run_until_stable(20);
+        assert!(engine.get_conflicts().is_empty());
+
+        engine.toggle_input("a").unwrap();
+        engine.run_until_stable(20);
+        let conflicts = engine.get_conflicts();
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].wire_ids, vec!["w_a".to_string(), "w_b".to_string()]);
+        let drivers: Vec<(&str, u8)> = conflicts[0].drivers.iter().map(|d| (d.gate_id.as_str(), d.state)).collect();
+        assert_eq!(drivers, vec![("a", StateType::One.to_u8()), ("b", StateType::Zero.to_u8())]);
+        // Only the contended net is reported, not the CONFLICT passed downstream
+        assert_eq!(outputs(&engine, "inv"), vec![StateType::Conflict]);
+
+        engine.toggle_input("b").unwrap();
+        engine.run_until_stable(20);
+        assert!(engine.get_conflicts().is_empty());
+    }
+
+    #[test]
+    fn test_wired_nets_combine_drivers() {
+        let net = |resolution: WireResolution| {
+            let mut w_a = wire("w_a", "a", 0, "led", 0);
+            w_a.resolution = resolution;
+            let mut engine = SimulationEngine::new();
+            engine.initialize(
+                vec![gate("a", "TOGGLE", 0), gate("b", "TOGGLE", 0), gate("led", "LED", 1)],
+                vec![w_a, wire("w_b", "b", 0, "led", 0)],
+            );
+            engine.run_until_stable(20);
+            let mut levels = vec![inputs(&engine, "led")[0]];
+            engine.toggle_input("a").unwrap();
+            engine.run_until_stable(20);
+            levels.push(inputs(&engine, "led")[0]);
+            engine.toggle_input("b").unwrap();
+            engine.run_until_stable(20);
+            levels.push(inputs(&engine, "led")[0]);
+            (levels, engine.get_conflicts().len())
+        };
+
+        use StateType::*;
+        // Inputs (a, b): (0, 0), (1, 0), (1, 1)
+        assert_eq!(net(WireResolution::Standard), (vec![Zero, Conflict, One], 0));
+        assert_eq!(net(WireResolution::WiredOr), (vec![Zero, One, One], 0));
+        assert_eq!(net(WireResolution::WiredAnd), (vec![Zero, Zero, One], 0));
+    }
+
+    #[test]
+    fn test_assertions_check_wire_state_at_time() {
+        let mut engine = SimulationEngine::new();
+        engine.initialize(
+            vec![gate("a", "TOGGLE", 0), gate("inv", "NOT", 1), gate("led", "LED", 1)],
+            vec![wire("w1", "a", 0, "inv", 0), wire("w2", "inv", 0, "led", 0)],
+        );
+        engine.add_assertion("w2".to_string(), StateType::One, 5).unwrap();
+        engine.add_assertion("w2".to_string(), StateType::One, 20).unwrap();
+        engine.add_assertion("w2".to_string(), StateType::Zero, 40).unwrap();
+        engine.add_assertion("w1".to_string(), StateType::One, 10).unwrap();
+        engine.advance_by(10);
+        // Due at exactly the advanced-to time
+        assert_eq!(
+            engine.get_assertion_failures(),
+            &[AssertionFailure {
+                wire_id: "w1".to_string(),
+                time: 10,
+                expected: StateType::One.to_u8(),
+                actual: Some(WireValue::Scalar(StateType::Zero.to_u8())),
+            }]
+        );
+
+        engine.toggle_input("a").unwrap();
+        while engine.get_current_time() < 50 {
+            engine.step();
+        }
+        let failed: Vec<(&str, u64)> =
+            engine.get_assertion_failures().iter().map(|f| (f.wire_id.as_str(), f.time)).collect();
+        assert_eq!(failed, vec![("w1", 10), ("w2", 20)]);
+
+        assert_eq!(
+            engine.add_assertion("w2".to_string(), StateType::One, 3),
+            Err(SimError::TimeInPast { time: 3, current_time: 50 })
+        );
+        assert!(engine.add_assertion("missing".to_string(), StateType::One, 60).is_err());
+    }
+
+    #[test]
+    fn test_power_on_reset_holds_sequential_gates() {
+        let mut engine = SimulationEngine::new();
+        engine.initialize(
+            vec![gate("d", "TOGGLE", 0), gate("clk", "CLOCK", 0), gate("dff", "DFF", 2), gate("inv", "NOT", 1)],
+            vec![wire("w1", "d", 0, "dff", 0), wire("w2", "clk", 0, "dff", 1), wire("w3", "dff", 0, "inv", 0)],
+        );
+        engine.randomize_initial_state(7);
+        engine.toggle_input("d").unwrap();
+        engine.advance_by(3);
+
+        engine.power_on_reset(25);
+        let release = engine.get_current_time() + 25;
+        engine.advance_by(2);
+        assert_eq!(outputs(&engine, "dff"), vec![StateType::Zero, StateType::One]);
+        assert_eq!(outputs(&engine, "inv"), vec![StateType::One]);
+
+        // Clock edges during the hold are ignored
+        while engine.get_current_time() < release - 1 {
+            engine.step();
+            assert_eq!(outputs(&engine, "dff")[0], StateType::Zero);
+        }
+
+        // The first rising edge after release captures D
+        engine.advance_by(20);
+        assert_eq!(outputs(&engine, "dff"), vec![StateType::One, StateType::Zero]);
+        assert_eq!(outputs(&engine, "inv"), vec![StateType::Zero]);
+    }
+
+    #[test]
+    fn test_power_on_reset_reaches_subcircuits_and_unwinds() {
+        let mut register = gate("sub", "SUBCIRCUIT", 2);
+        register.subcircuit = Some(SubcircuitDefinition {
+            gates: vec![gate("d", "TOGGLE", 0), gate("c", "TOGGLE", 0), gate("dff", "DFF", 2), gate("q", "LED", 1)],
+            wires: vec![wire("i1", "d", 0, "dff", 0), wire("i2", "c", 0, "dff", 1), wire("i3", "dff", 0, "q", 0)],
+            input_ids: vec!["d".to_string(), "c".to_string()],
+            output_ids: vec!["q".to_string()],
+        });
+        let mut engine = SimulationEngine::new();
+        engine.initialize(
+            vec![gate("d", "TOGGLE", 0), gate("c", "TOGGLE", 0), register, gate("led", "LED", 1)],
+            vec![wire("w1", "d", 0, "sub", 0), wire("w2", "c", 0, "sub", 1), wire("w3", "sub", 0, "led", 0)],
+        );
+        engine.toggle_input("d").unwrap();
+        assert!(engine.run_until_stable(100));
+        engine.toggle_input("c").unwrap();
+        assert!(engine.run_until_stable(100));
+        assert_eq!(inputs(&engine, "led"), vec![StateType::One]);
+
+        // The flip-flop inside the subcircuit is reset and held too
+        engine.enable_history(10);
+        engine.step();
+        engine.power_on_reset(10);
+        engine.advance_by(3);
+        assert_eq!(inputs(&engine, "led"), vec![StateType::Zero]);
+        assert!(engine.power_on_reset_until.is_some());
+
+        // Stepping back past the reset also undoes the hold
+        while engine.step_back() {}
+        assert_eq!(engine.power_on_reset_until, None);
+        assert_eq!(inputs(&engine, "led"), vec![StateType::One]);
+    }
+
+    #[test]
+    fn test_large_circuit_evaluates_each_gate_once() {
+        // A chain of buffers, each also driving an LED: 5000 wires
+        let length = 2500;
+        let mut gates = vec![gate("src", "TOGGLE", 0)];
+        let mut wires = Vec::new();
+        for i in 0..length {
+            let driver = if i == 0 { "src".to_string() } else { format!("b{}", i - 1) };
+            gates.push(gate(&format!("b{}", i), "BUFFER", 1));
+            gates.push(gate(&format!("l{}", i), "LED", 1));
+            wires.push(wire(&format!("c{}", i), &driver, 0, &format!("b{}", i), 0));
+            wires.push(wire(&format!("o{}", i), &format!("b{}", i), 0, &format!("l{}", i), 0));
+        }
+
+        let mut engine = SimulationEngine::new();
+        assert!(engine.initialize(gates, wires).is_empty());
+        assert!(engine.get_floating_inputs().is_empty());
+        assert!(engine.run_until_stable(4 * length as u64));
+
+        // The toggle, then one evaluation per buffer and LED it reaches
+        engine.toggle_input("src").unwrap();
+        let mut events = 0;
+        for _ in 0..4 * length {
+            if engine.event_queue.is_empty() {
+                break;
+            }
+            events += engine.step();
+        }
+        assert!(engine.event_queue.is_empty());
+        assert_eq!(events, 1 + 2 * length);
+        assert_eq!(inputs(&engine, &format!("l{}", length - 1)), vec![StateType::One]);
+    }
+
+    #[test]
+    fn test_fire_pulse_returns_low_after_width() {
+        let mut engine = SimulationEngine::new();
+        engine.initialize(
+            vec![gate("btn", "PULSE", 0), gate("led", "LED", 1)],
+            vec![wire("w", "btn", 0, "led", 0)],
+        );
+        engine.run_until_stable(10);
+        assert_eq!(engine.fire_pulse("led", 5), Err(SimError::UnexpectedGateType {
+            gate_id: "led".to_string(),
+            expected: "PULSE".to_string(),
+        }));
+
+        engine.fire_pulse("btn", 5).unwrap();
+        engine.advance_by(2);
+        assert_eq!(outputs(&engine, "btn"), vec![StateType::One]);
+        assert_eq!(engine.gates["led"].get_inputs(), &[StateType::One]);
+
+        engine.advance_by(5);
+        assert_eq!(outputs(&engine, "btn"), vec![StateType::Zero]);
+        assert_eq!(engine.gates["led"].get_inputs(), &[StateType::Zero]);
+
+        // A width past the end of time holds the pulse instead of overflowing
+        engine.fire_pulse("btn", u64::MAX).unwrap();
+        engine.advance_by(100);
+        assert_eq!(outputs(&engine, "btn"), vec![StateType::One]);
+    }
+
+    #[test]
+    fn test_stepping_to_the_end_of_time_does_not_overflow() {
+        let mut engine = SimulationEngine::new();
+        engine.initialize(
+            vec![gate("btn", "PULSE", 0), gate("clk", "CLOCK", 0), gate("led", "LED", 2)],
+            vec![wire("w1", "btn", 0, "led", 0), wire("w2", "clk", 0, "led", 1)],
+        );
+        engine.set_clock_period("clk", u64::MAX).unwrap();
+        engine.run_until_stable(10);
+
+        engine.fire_pulse("btn", u64::MAX).unwrap();
+        engine.step();
+        engine.step();
+        assert_eq!(engine.get_current_time(), u64::MAX);
+        assert_eq!(outputs(&engine, "clk"), vec![StateType::One]);
+    }
+
+    #[test]
+    fn test_step_single_event_follows_causality() {
+        let mut engine = SimulationEngine::new();
+        engine.initialize(
+            vec![gate("a", "TOGGLE", 0), gate("inv", "NOT", 1), gate("led", "LED", 1)],
+            vec![wire("w1", "a", 0, "inv", 0), wire("w2", "inv", 0, "led", 0)],
+        );
+        engine.run_until_stable(10);
+        engine.set_breakpoint("w2".to_string(), EdgeKind::Falling).unwrap();
+        engine.set_inputs(vec![("a".to_string(), true)]).unwrap();
+
+        let first = engine.step_single_event().unwrap();
+        assert_eq!(first.gate_id, "a");
+        assert_eq!(first.breakpoint_hit, None);
+        assert_eq!(first.changed_outputs, vec![0]);
+        assert_eq!(first.outputs, vec![StateType::One.to_u8()]);
+        // Only that one event ran: the inverter has seen its input but not reacted
+        assert_eq!(outputs(&engine, "inv"), vec![StateType::One]);
+
+        let second = engine.step_single_event().unwrap();
+        assert_eq!(second.gate_id, "inv");
+        assert!(second.time > first.time);
+        assert_eq!(second.inputs, vec![StateType::One.to_u8()]);
+        assert_eq!(second.previous_outputs, vec![StateType::One.to_u8()]);
+        assert_eq!(second.outputs, vec![StateType::Zero.to_u8()]);
+        assert_eq!(engine.get_current_time(), second.time);
+        assert_eq!(second.breakpoint_hit.as_deref(), Some("w2"));
+        assert_eq!(engine.breakpoint_hit(), Some("w2"));
+
+        let third = engine.step_single_event().unwrap();
+        assert_eq!((third.gate_id.as_str(), third.changed_outputs.len()), ("led", 0));
+        assert_eq!(engine.breakpoint_hit(), None);
+        assert_eq!(engine.step_single_event(), None);
+    }
+
+    #[test]
+    fn test_oneshot_schedules_its_own_fall() {
+        let mut oneshot = gate("os", "ONESHOT", 1);
+        oneshot.pulse_width = Some(6);
+        let mut engine = SimulationEngine::new();
+        engine.initialize(
+            vec![gate("t", "TOGGLE", 0), oneshot, gate("led", "LED", 1)],
+            vec![wire("w_t", "t", 0, "os", 0), wire("w_q", "os", 0, "led", 0)],
+        );
+        engine.run_until_stable(20);
+
+        engine.toggle_input("t").unwrap();
+        let start = engine.get_current_time();
+        engine.advance_by(3);
+        assert_eq!(outputs(&engine, "os"), vec![StateType::One]);
+
+        // No further stimulus: the gate's own wakeup ends the pulse
+        engine.advance_by(10);
+        assert_eq!(outputs(&engine, "os"), vec![StateType::Zero]);
+        assert_eq!(engine.get_current_time(), start + 13);
+    }
+
+    #[test]
+    fn test_advance_by_covers_fixed_time_span() {
+        let mut engine = SimulationEngine::new();
+        engine.initialize(vec![gate("clk", "CLOCK", 0), gate("led", "LED", 1)], vec![wire("w", "clk", 0, "led", 0)]);
+        engine.set_clock_period("clk", 4).unwrap();
+
+        let events = engine.advance_by(10);
+        assert_eq!(engine.get_current_time(), 10);
+        assert!(events > 0);
+        assert!(engine.event_queue.peek().is_some_and(|event| event.time > 10));
+
+        // Nothing due in the next unit still moves time forward
+        assert_eq!(engine.advance_by(1), 0);
+        assert_eq!(engine.get_current_time(), 11);
+    }
+
+    #[test]
+    fn test_trace_records_value_changes() {
+        let mut engine = SimulationEngine::new();
+        engine.initialize(
+            vec![gate("a", "TOGGLE", 0), gate("inv", "NOT", 1)],
+            vec![wire("w", "a", 0, "inv", 0)],
+        );
+        engine.trace_signals(vec!["inv".to_string(), "w".to_string(), "missing".to_string()]);
+        engine.run_until_stable(10);
+        engine.toggle_input("a").unwrap();
+        engine.run_until_stable(10);
+
+        // The power-on value is replaced by the first evaluation at time 0
+        let (zero, one) = (StateType::Zero.to_u8(), StateType::One.to_u8());
+        let values = |id: &str| -> Vec<Vec<u8>> {
+            engine.get_trace()[id].iter().map(|sample| sample.values.clone()).collect()
+        };
+        assert_eq!(values("w"), vec![vec![zero], vec![one]]);
+        assert_eq!(values("inv"), vec![vec![one], vec![zero]]);
+        assert!(!engine.get_trace().contains_key("missing"));
+        let times: Vec<u64> = engine.get_trace()["inv"].iter().map(|sample| sample.time).collect();
+        assert!(times.windows(2).all(|pair| pair[0] < pair[1]));
+
+        engine.reset(ResetMode::Full);
+        assert_eq!(engine.get_trace()["w"].len(), 1);
+        engine.clear_trace();
+        assert!(engine.get_trace().is_empty());
+    }
+
+    #[test]
+    fn test_disabled_gate_floats_outputs() {
+        let mut engine = SimulationEngine::new();
+        engine.initialize(
+            vec![gate("a", "TOGGLE", 0), gate("inv", "NOT", 1), gate("led", "LED", 1)],
+            vec![wire("w1", "a", 0, "inv", 0), wire("w2", "inv", 0, "led", 0)],
+        );
+        engine.run_until_stable(10);
+        assert_eq!(outputs(&engine, "inv"), vec![StateType::One]);
+
+        engine.set_gate_enabled("inv", false).unwrap();
+        engine.toggle_input("a").unwrap();
+        engine.run_until_stable(10);
+        assert!(!engine.is_gate_enabled("inv"));
+        assert_eq!(outputs(&engine, "inv"), vec![StateType::HiZ]);
+        assert_eq!(engine.wires["w2"].state, vec![StateType::HiZ]);
+
+        engine.set_gate_enabled("inv", true).unwrap();
+        engine.run_until_stable(10);
+        assert_eq!(outputs(&engine, "inv"), vec![StateType::Zero]);
+        assert_eq!(engine.set_gate_enabled("nope", false), Err(SimError::GateNotFound("nope".to_string())));
+    }
+
+    #[test]
+    fn test_clocks_in_quadrature() {
+        let mut engine = SimulationEngine::new();
+        engine.initialize(vec![gate("i", "CLOCK", 0), gate("q", "CLOCK", 0), gate("far", "CLOCK", 0)], vec![]);
+        engine.set_clock_period("i", 4).unwrap();
+        engine.set_clock_period("q", 4).unwrap();
+        engine.set_clock_period("far", 4).unwrap();
+        engine.set_clock_phase("q", 2).unwrap();
+        // u64::MAX - 5 is 2 modulo the 8-unit cycle, so it must not overflow
+        engine.set_clock_phase("far", u64::MAX - 5).unwrap();
+
+        let mut samples = Vec::new();
+        for _ in 0..16 {
+            engine.advance_by(1);
+            samples.push((outputs(&engine, "i")[0], outputs(&engine, "q")[0]));
+            assert_eq!(outputs(&engine, "far"), outputs(&engine, "q"));
+        }
+
+        // Sampled at t = 1..=16: Q leads I by a quarter of the 8-unit cycle
+        let (zero, one) = (StateType::Zero, StateType::One);
+        let cycle = [
+            (zero, zero), (zero, one), (zero, one), (one, one),
+            (one, one), (one, zero), (one, zero), (zero, zero),
+        ];
+        assert_eq!(samples, cycle.repeat(2));
+    }
+
+    #[test]
+    fn test_wide_gate_evaluates_once_per_timestamp() {
+        let input_ids: Vec<String> = (0..8).map(|i| format!("in{}", i)).collect();
+        let mut gates: Vec<GateState> = input_ids.iter().map(|id| gate(id, "TOGGLE", 0)).collect();
+        gates.push(gate("and", "AND", 8));
+        let wires = input_ids
+            .iter()
+            .enumerate()
+            .map(|(i, id)| wire(&format!("w{}", i), id, 0, "and", i as u32))
+            .collect();
+
+        let mut engine = SimulationEngine::new();
+        engine.initialize(gates, wires);
+        engine.run_until_stable(10);
+
+        engine.set_inputs(input_ids.into_iter().map(|id| (id, true)).collect()).unwrap();
+        assert_eq!(engine.step(), 8);
+        assert_eq!(engine.step(), 1);
+        assert_eq!(outputs(&engine, "and"), vec![StateType::One]);
+    }
+
+    #[test]
+    fn test_breakpoint_stops_on_edge() {
+        let mut engine = SimulationEngine::new();
+        engine.initialize(
+            vec![gate("clk", "CLOCK", 0), gate("led", "LED", 1)],
+            vec![wire("w", "clk", 0, "led", 0)],
+        );
+        engine.set_clock_period("clk", 5).unwrap();
+        engine.set_breakpoint("w".to_string(), EdgeKind::Falling).unwrap();
+
+        engine.advance_by(100);
+        assert_eq!(engine.breakpoint_hit(), Some("w"));
+        assert_eq!(engine.get_current_time(), 10);
+        assert_eq!(outputs(&engine, "clk"), vec![StateType::Zero]);
+
+        // Resuming runs on to the next falling edge
+        engine.advance_by(100);
+        assert_eq!(engine.get_current_time(), 20);
+
+        engine.clear_breakpoint("w");
+        engine.advance_by(100);
+        assert_eq!(engine.breakpoint_hit(), None);
+        assert_eq!(engine.get_current_time(), 120);
+        assert!(engine.set_breakpoint("nope".to_string(), EdgeKind::Any).is_err());
+    }
+
+    #[test]
+    fn test_fanout_violations() {
+        let mut gates = vec![gate("a", "TOGGLE", 0), gate("b", "TOGGLE", 0)];
+        let mut wires = vec![wire("wb", "b", 0, "led_b", 0)];
+        gates.push(gate("led_b", "LED", 1));
+        for i in 0..3 {
+            let led = format!("led{}", i);
+            wires.push(wire(&format!("w{}", i), "a", 0, &led, 0));
+            gates.push(gate(&led, "LED", 1));
+        }
+
+        let mut engine = SimulationEngine::new();
+        engine.initialize(gates, wires);
+        assert_eq!(engine.get_fanout("a", 0), 3);
+        assert_eq!(engine.get_fanout("a", 1), 0);
+        assert!(engine.get_fanout_violations().is_empty());
+
+        engine.set_fanout_limit(2);
+        assert_eq!(engine.get_fanout_violations(), vec![("a".to_string(), 0, 3)]);
+    }
+
+    #[test]
+    fn test_rise_slower_than_fall() {
+        let mut buffer = gate("buf", "BUFFER", 1);
+        buffer.rise_delay = Some(6);
+        buffer.fall_delay = Some(2);
+        let mut engine = SimulationEngine::new();
+        engine.initialize(
+            vec![gate("a", "TOGGLE", 0), buffer, gate("inv", "NOT", 1)],
+            vec![wire("w1", "a", 0, "buf", 0), wire("w2", "buf", 0, "inv", 0)],
+        );
+        engine.run_until_stable(20);
+
+        // Time from toggling until the inverter downstream of the buffer reacts
+        let latency = |engine: &mut SimulationEngine| {
+            let start = engine.get_current_time();
+            let before = outputs(engine, "inv");
+            engine.toggle_input("a").unwrap();
+            while outputs(engine, "inv") == before {
+                engine.step();
+            }
+            engine.get_current_time() - start
+        };
+        let rising = latency(&mut engine);
+        let falling = latency(&mut engine);
+        assert_eq!(rising - falling, 4);
+    }
+
+    #[test]
+    fn test_delay_corner_selects_min_or_max_delay() {
+        let mut buffer = gate("buf", "BUFFER", 1);
+        buffer.delay = Some(3);
+        buffer.min_delay = Some(1);
+        buffer.max_delay = Some(8);
+        let mut engine = SimulationEngine::new();
+        engine.initialize(
+            vec![gate("a", "TOGGLE", 0), buffer, gate("led", "LED", 1)],
+            vec![wire("w1", "a", 0, "buf", 0), wire("w2", "buf", 0, "led", 0)],
+        );
+        engine.run_until_stable(20);
+
+        let latency = |engine: &mut SimulationEngine| {
+            let start = engine.get_current_time();
+            let before = inputs(engine, "led");
+            engine.toggle_input("a").unwrap();
+            while inputs(engine, "led") == before {
+                engine.step();
+            }
+            engine.get_current_time() - start
+        };
+        let typical = latency(&mut engine);
+        engine.set_delay_corner(DelayCorner::Min);
+        let fast = latency(&mut engine);
+        engine.set_delay_corner(DelayCorner::Max);
+        let slow = latency(&mut engine);
+        assert_eq!(typical - fast, 2);
+        assert_eq!(slow - typical, 5);
+
+        let reported = |engine: &SimulationEngine| engine.get_gate_state("buf").unwrap().delay;
+        assert_eq!(reported(&engine), Some(8));
+        engine.set_delay_corner(DelayCorner::Min);
+        assert_eq!(reported(&engine), Some(1));
+        engine.set_delay_corner(DelayCorner::Typical);
+        assert_eq!(reported(&engine), Some(3));
+    }
+
+    #[test]
+    fn test_critical_path_follows_slowest_branch() {
+        let mut slow = gate("slow", "BUFFER", 1);
+        slow.delay = Some(4);
+        let mut fast = gate("fast", "BUFFER", 1);
+        fast.delay = Some(2);
+        let mut w3 = wire("w3", "slow", 0, "and", 0);
+        w3.delay = 1;
+        let mut engine = SimulationEngine::new();
+        engine.initialize(
+            vec![gate("a", "TOGGLE", 0), slow, fast, gate("and", "AND", 2), gate("led", "LED", 1)],
+            vec![
+                wire("w1", "a", 0, "slow", 0),
+                wire("w2", "a", 0, "fast", 0),
+                w3,
+                wire("w4", "fast", 0, "and", 1),
+                wire("w5", "and", 0, "led", 0),
+                // Reported by validate_wires, skipped by path analysis
+                wire("w_dangling", "and", 0, "ghost", 0),
+            ],
+        );
+
+        let report = engine.compute_critical_path(vec!["a".to_string()], vec!["led".to_string()]).unwrap();
+        assert_eq!(report.path, vec!["a", "slow", "and", "led"]);
+        assert_eq!(report.arrival_times, vec![0, 1, 6, 7]);
+        assert_eq!(report.total_delay, 7);
+
+        assert!(matches!(
+            engine.compute_critical_path(vec!["missing".to_string()], vec![]),
+            Err(SimError::GateNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_critical_path_breaks_feedback_at_flip_flops() {
+        // Q_bar feeds back to D through an inverter pair
+        let mut engine = SimulationEngine::new();
+        engine.initialize(
+            vec![gate("clk", "CLOCK", 0), gate("dff", "DFF", 2), gate("n1", "NOT", 1), gate("n2", "NOT", 1)],
+            vec![
+                wire("w1", "clk", 0, "dff", 1),
+                wire("w2", "dff", 1, "n1", 0),
+                wire("w3", "n1", 0, "n2", 0),
+                wire("w4", "n2", 0, "dff", 0),
+            ],
+        );
+        let report = engine.compute_critical_path(vec!["dff".to_string()], vec!["dff".to_string()]).unwrap();
+        assert_eq!(report.path, vec!["dff", "n1", "n2", "dff"]);
+        assert_eq!(report.total_delay, 3);
+
+        // Stateful timing gates break loops the same way
+        let mut engine = SimulationEngine::new();
+        engine.initialize(
+            vec![gate("pulse", "ONESHOT", 1), gate("n1", "NOT", 1)],
+            vec![wire("w1", "pulse", 0, "n1", 0), wire("w2", "n1", 0, "pulse", 0)],
+        );
+        let report = engine.compute_critical_path(vec!["pulse".to_string()], vec!["pulse".to_string()]).unwrap();
+        assert_eq!(report.path, vec!["pulse", "n1", "pulse"]);
+
+        // Without the flip-flop the same loop is purely combinational
+        let mut engine = SimulationEngine::new();
+        engine.initialize(
+            vec![gate("a", "TOGGLE", 0), gate("or", "OR", 2), gate("n1", "NOT", 1), gate("n2", "NOT", 1)],
+            vec![
+                wire("w1", "a", 0, "or", 0),
+                wire("w2", "or", 0, "n1", 0),
+                wire("w3", "n1", 0, "n2", 0),
+                wire("w4", "n2", 0, "or", 1),
+            ],
+        );
+        assert_eq!(
+            engine.compute_critical_path(vec!["a".to_string()], vec!["n2".to_string()]),
+            Err(SimError::CombinationalLoop(vec!["or".to_string(), "n1".to_string(), "n2".to_string()]))
+        );
+    }
+
+    #[test]
+    fn test_state_hash_matches_for_equal_states() {
+        let (gates, wires) = counter_circuit();
+        let mut a = SimulationEngine::new();
+        a.initialize(gates.clone(), wires.clone());
+        let mut b = SimulationEngine::new();
+        let (mut reversed_gates, mut reversed_wires) = (gates, wires);
+        reversed_gates.reverse();
+        reversed_wires.reverse();
+        b.initialize(reversed_gates, reversed_wires);
+        assert_eq!(a.state_hash(), b.state_hash());
+
+        run_steps(&mut a, 5);
+        assert_ne!(a.state_hash(), b.state_hash());
+        run_steps(&mut b, 5);
+        assert_eq!(a.state_hash(), b.state_hash());
+
+        a.toggle_input("t").unwrap();
+        a.run_until_stable(10);
+        b.run_until_stable(10);
+        assert_ne!(a.state_hash(), b.state_hash());
+    }
+
+    #[test]
+    fn test_clock_period_zero_is_clamped() {
+        let mut engine = SimulationEngine::new();
+        engine.initialize(vec![gate("clk", "CLOCK", 0), gate("and", "AND", 2)], vec![]);
+        engine.set_clock_period("clk", 0).unwrap();
+        engine.set_clock_period("and", 5).unwrap();
+
+        // A period of 1 toggles the clock every time unit
+        let mut samples = Vec::new();
+        for _ in 0..5 {
+            engine.step();
+            samples.push(outputs(&engine, "clk")[0]);
+        }
+        let (zero, one) = (StateType::Zero, StateType::One);
+        assert_eq!(samples, vec![zero, one, zero, one, zero]);
+        assert_eq!(engine.gates["clk"].save_state()[1], 1);
+        assert!(engine.get_unstable_gates().is_empty());
+    }
+
+    fn counter_circuit() -> (Vec<GateState>, Vec<WireState>) {
+        (
+            vec![gate("t", "TOGGLE", 0), gate("clk", "CLOCK", 0), gate("tff", "TFF", 2)],
+            vec![wire("w_t", "t", 0, "tff", 0), wire("w_clk", "clk", 0, "tff", 1)],
+        )
+    }
+
+    #[test]
+    fn test_export_import_state_round_trip() {
+        let (gates, wires) = counter_circuit();
+        let mut original = SimulationEngine::new();
+        original.initialize(gates.clone(), wires.clone());
+        original.set_clock_period("clk", 2).unwrap();
+        original.toggle_input("t").unwrap();
+        run_steps(&mut original, 7);
+
+        let saved = original.export_state();
+
+        let mut restored = SimulationEngine::new();
+        restored.initialize(gates, wires);
+        restored.import_state(saved.clone());
+        assert_eq!(restored.export_state(), saved);
+
+        run_steps(&mut original, 9);
+        run_steps(&mut restored, 9);
+        assert_eq!(restored.export_state(), original.export_state());
+    }
+
+    #[test]
+    fn test_idle_step_at_the_end_of_time_stays_there() {
+        let mut engine = SimulationEngine::new();
+        engine.initialize(vec![gate("a", "TOGGLE", 0)], vec![]);
+        engine.run_until_stable(10);
+        let mut saved = engine.export_state();
+        saved.time = u64::MAX;
+        engine.import_state(saved);
+
+        engine.step();
+        engine.step();
+        assert_eq!(engine.get_current_time(), u64::MAX);
+    }
+
+    #[test]
+    fn test_initialization_is_reproducible() {
+        let (mut gates, mut wires) = counter_circuit();
+        gates.extend([gate("s", "TOGGLE", 0), gate("n1", "NOR", 2), gate("n2", "NOR", 2)]);
+        wires.extend([
+            wire("w_s", "s", 0, "n1", 0),
+            wire("w_tff", "tff", 0, "n2", 0),
+            wire("w_q", "n1", 0, "n2", 1),
+            wire("w_qn", "n2", 0, "n1", 1),
+        ]);
+
+        let run = |gates: Vec<GateState>, wires: Vec<WireState>| {
+            let mut engine = SimulationEngine::new();
+            engine.initialize(gates, wires);
+            engine.set_clock_period("clk", 3).unwrap();
+            (0..20)
+                .map(|_| {
+                    engine.step();
+                    engine.get_snapshot()
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let first = run(gates.clone(), wires.clone());
+        for _ in 0..5 {
+            assert_eq!(run(gates.clone(), wires.clone()), first);
+        }
+    }
+
+    #[test]
+    fn test_snapshot_diff_lists_changed_gates_and_wires() {
+        let mut engine = SimulationEngine::new();
+        engine.initialize(
+            vec![gate("a", "TOGGLE", 0), gate("inv", "NOT", 1), gate("led", "LED", 1)],
+            vec![wire("w1", "a", 0, "inv", 0), wire("w2", "inv", 0, "led", 0)],
+        );
+        engine.run_until_stable(10);
+        let before = engine.get_snapshot();
+        assert!(before.diff(&before).is_empty());
+
+        engine.toggle_input("a").unwrap();
+        engine.run_until_stable(10);
+        let after = engine.get_snapshot();
+        let diff = before.diff(&after);
+
+        assert_eq!(diff.time, Some((before.time, after.time)));
+        let gate_ids: Vec<&str> = diff.gates.iter().map(|g| g.id.as_str()).collect();
+        assert_eq!(gate_ids, vec!["a", "inv", "led"]);
+        let wire_ids: Vec<&str> = diff.wires.iter().map(|w| w.id.as_str()).collect();
+        assert_eq!(wire_ids, vec!["w1", "w2"]);
+
+        let mut removed = after.clone();
+        removed.gates.retain(|g| g.id != "led");
+        let diff = after.diff(&removed);
+        assert_eq!(diff.gates.len(), 1);
+        assert_eq!(diff.gates[0].after, None);
+    }
+
+    #[test]
+    fn test_get_gate_state() {
+        let mut and = gate("and", "AND", 2);
+        and.delay = Some(3);
+        and.rise_delay = Some(5);
+        let mut engine = SimulationEngine::new();
+        engine.initialize(
+            vec![gate("a", "TOGGLE", 0), gate("b", "TOGGLE", 0), and],
+            vec![wire("w1", "a", 0, "and", 0), wire("w2", "b", 0, "and", 1)],
+        );
+        engine.toggle_input("a").unwrap();
+        engine.run_until_stable(20);
+
+        let state = engine.get_gate_state("and").unwrap();
+        assert_eq!(state.gate_type, "AND");
+        assert_eq!(state.input_states, vec![StateType::One.to_u8(), StateType::Zero.to_u8()]);
+        assert_eq!(state.output_states, vec![StateType::Zero.to_u8()]);
+        assert_eq!((state.delay, state.rise_delay, state.fall_delay), (Some(3), Some(5), None));
+        assert_eq!(engine.get_gate_state("missing"), None);
+    }
+
+    #[test]
+    fn test_get_wire_between_ports() {
+        let mut bus = wire("bus", "reg", 0, "led", 0);
+        bus.width = 2;
+        bus.state = WireValue::Bus(vec![StateType::Unknown.to_u8(); 2]);
+        let mut engine = SimulationEngine::new();
+        engine.initialize(
+            vec![gate("a", "TOGGLE", 0), gate("reg", "REGISTER", 4), gate("led", "LED", 2), gate("and", "AND", 2)],
+            vec![wire("w2", "a", 0, "and", 0), wire("w1", "a", 0, "and", 0), wire("w3", "a", 0, "and", 1), bus],
+        );
+
+        // Parallel wires resolve to the lowest ID
+        assert_eq!(engine.get_wire_between("a", 0, "and", 0).map(|w| w.id), Some("w1".to_string()));
+        assert_eq!(engine.get_wire_between("a", 0, "and", 1).map(|w| w.id), Some("w3".to_string()));
+        // Bus bits pair up one to one
+        assert_eq!(engine.get_wire_between("reg", 1, "led", 1).map(|w| w.id), Some("bus".to_string()));
+        assert_eq!(engine.get_wire_between("reg", 0, "led", 1), None);
+        assert_eq!(engine.get_wire_between("a", 0, "led", 0), None);
+    }
+
+    #[test]
+    fn test_group_snapshot_selects_tagged_gates() {
+        let grouped = |id: &str, gate_type: &str, input_count: usize, group: &str| {
+            let mut state = gate(id, gate_type, input_count);
+            state.group = Some(group.to_string());
+            state
+        };
+        let mut engine = SimulationEngine::new();
+        engine.initialize(
+            vec![
+                grouped("a", "TOGGLE", 0, "control"),
+                grouped("inv", "NOT", 1, "alu"),
+                grouped("and", "AND", 2, "alu"),
+                gate("led", "LED", 1),
+            ],
+            vec![
+                wire("w1", "a", 0, "inv", 0),
+                wire("w2", "inv", 0, "and", 0),
+                wire("w3", "and", 0, "led", 0),
+            ],
+        );
+        engine.run_until_stable(10);
+
+        let alu = engine.get_group_snapshot("alu");
+        let gate_ids: Vec<&str> = alu.gates.iter().map(|g| g.id.as_str()).collect();
+        assert_eq!(gate_ids, vec!["and", "inv"]);
+        assert_eq!(alu.gates[0].group.as_deref(), Some("alu"));
+        let wire_ids: Vec<&str> = alu.wires.iter().map(|w| w.id.as_str()).collect();
+        assert_eq!(wire_ids, vec!["w1", "w2", "w3"]);
+
+        engine.add_gate(grouped("or", "OR", 2, "alu"));
+        engine.remove_gate("inv").unwrap();
+        let gate_ids: Vec<String> = engine.get_group_snapshot("alu").gates.into_iter().map(|g| g.id).collect();
+        assert_eq!(gate_ids, vec!["and", "or"]);
+        assert!(engine.get_group_snapshot("missing").gates.is_empty());
+    }
+
+    #[test]
+    fn test_per_gate_delay_orders_downstream_events() {
+        let mut slow = gate("slow", "AND", 2);
+        slow.delay = Some(5);
+        let mut engine = SimulationEngine::new();
+        engine.initialize(
+            vec![gate("in", "TOGGLE", 0), gate("fast", "AND", 2), slow, gate("fast_out", "BUFFER", 1), gate("slow_out", "BUFFER", 1)],
+            vec![
+                wire("w1", "in", 0, "fast", 0),
+                wire("w2", "in", 0, "fast", 1),
+                wire("w3", "in", 0, "slow", 0),
+                wire("w4", "in", 0, "slow", 1),
+                wire("w5", "fast", 0, "fast_out", 0),
+                wire("w6", "slow", 0, "slow_out", 0),
+            ],
+        );
+        run_steps(&mut engine, 10);
+        engine.toggle_input("in").unwrap();
+        while outputs(&engine, "slow")[0] != StateType::One {
+            engine.step();
+        }
+
+        let scheduled = |id: &str| {
+            engine.event_queue.pending_events().iter().find(|e| e.gate_id == id).map(|e| e.time)
+        };
+        assert_eq!(outputs(&engine, "fast")[0], StateType::One);
+        assert_eq!(scheduled("slow_out").unwrap() - scheduled("fast_out").unwrap(), 4);
+    }
+
+    #[test]
+    fn test_activity_report_ranks_busiest_gates() {
+        let mut engine = SimulationEngine::new();
+        engine.initialize(
+            vec![gate("clk", "CLOCK", 0), gate("inv", "NOT", 1), gate("en", "TOGGLE", 0), gate("led", "LED", 1)],
+            vec![wire("w1", "clk", 0, "inv", 0), wire("w2", "en", 0, "led", 0)],
+        );
+        engine.set_clock_period("clk", 2).unwrap();
+        engine.advance_by(21);
+        engine.toggle_input("en").unwrap();
+        engine.advance_by(2);
+
+        let report = engine.get_activity_report();
+        let ranking: Vec<(&str, u64)> =
+            report.gates.iter().map(|g| (g.gate_id.as_str(), g.transitions)).collect();
+        assert_eq!(ranking[2..], [("en", 1), ("led", 0)]);
+        assert!(ranking[0].1 >= 10 && ranking[1].1 >= 10);
+        assert_eq!(report.total_transitions, ranking.iter().map(|(_, t)| t).sum::<u64>());
+
+        engine.reset(ResetMode::Full);
+        assert_eq!(engine.get_activity_report().total_transitions, 0);
+    }
+
+    #[test]
+    fn test_summary_counts_gates_and_health() {
+        let mut engine = SimulationEngine::new();
+        engine.initialize(
+            vec![gate("a", "TOGGLE", 0), gate("clk", "CLOCK", 0), gate("and", "AND", 2), gate("led", "LED", 1)],
+            vec![wire("w1", "a", 0, "and", 0), wire("w2", "and", 0, "led", 0)],
+        );
+
+        let summary = engine.get_summary();
+        assert_eq!((summary.gate_count, summary.wire_count), (4, 2));
+        assert_eq!((summary.input_count, summary.output_count), (2, 1));
+        assert_eq!(summary.gate_counts.get("AND"), Some(&1));
+        assert!(summary.has_floating_inputs);
+        assert!(!summary.has_conflicts);
+
+        engine.add_wire(wire("w3", "clk", 0, "and", 1));
+        assert!(!engine.get_summary().has_floating_inputs);
+    }
+
+    #[test]
+    fn test_wire_transition_counts() {
+        let mut engine = SimulationEngine::new();
+        engine.initialize(
+            vec![gate("in", "TOGGLE", 0), gate("inv", "NOT", 1), gate("led", "LED", 1)],
+            vec![wire("w_in", "in", 0, "inv", 0), wire("w_out", "inv", 0, "led", 0)],
+        );
+        for _ in 0..3 {
+            engine.toggle_input("in").unwrap();
+            run_steps(&mut engine, 5);
+        }
+
+        let counts = engine.get_wire_transition_counts();
+        assert_eq!(counts["w_in"], 3);
+        assert_eq!(counts["w_out"], 3);
+
+        engine.reset(ResetMode::Full);
+        assert!(engine.get_wire_transition_counts().values().all(|&c| c == 0));
+    }
+
+    #[test]
+    fn test_remove_gate_drops_wires_and_events() {
+        let mut engine = SimulationEngine::new();
+        engine.initialize(
+            vec![gate("a", "TOGGLE", 0), gate("b", "TOGGLE", 0), gate("and", "AND", 2)],
+            vec![wire("w_a", "a", 0, "and", 0), wire("w_b", "b", 0, "and", 1)],
+        );
+        engine.toggle_input("a").unwrap();
+        engine.toggle_input("b").unwrap();
+        run_steps(&mut engine, 5);
+        assert_eq!(outputs(&engine, "and")[0], StateType::One);
+
+        engine.toggle_input("a").unwrap();
+        engine.remove_gate("a").unwrap();
+        assert!(!engine.gates.contains_key("a"));
+        assert!(!engine.wires.contains_key("w_a"));
+        assert!(engine.wires.contains_key("w_b"));
+        assert!(engine.event_queue.pending_events().iter().all(|e| e.gate_id != "a"));
+        assert_eq!(inputs(&engine, "and")[0], StateType::HiZ);
+
+        run_steps(&mut engine, 5);
+        assert_eq!(outputs(&engine, "and")[0], StateType::Unknown);
+    }
+
+    #[test]
+    fn test_incremental_add_gate_and_wire() {
+        let mut engine = SimulationEngine::new();
+        engine.initialize(vec![], vec![]);
+        engine.add_gate(gate("in", "TOGGLE", 0));
+        engine.add_gate(gate("inv", "NOT", 1));
+        engine.add_wire(wire("w", "in", 0, "inv", 0));
+        run_steps(&mut engine, 5);
+        assert_eq!(outputs(&engine, "inv")[0], StateType::One);
+
+        engine.toggle_input("in").unwrap();
+        run_steps(&mut engine, 5);
+        assert_eq!(outputs(&engine, "inv")[0], StateType::Zero);
+
+        // Duplicate IDs replace rather than duplicate
+        engine.add_gate(gate("inv", "BUFFER", 1));
+        engine.add_wire(wire("w", "in", 0, "inv", 0));
+        run_steps(&mut engine, 5);
+        assert_eq!(engine.gates.len(), 2);
+        assert_eq!(engine.wires.len(), 1);
+        assert_eq!(engine.gates["inv"].gate_type(), "BUFFER");
+        assert_eq!(outputs(&engine, "inv")[0], StateType::One);
+    }
+
+    #[test]
+    fn test_fanout_reaches_every_target_after_rewiring() {
+        let leds: Vec<String> = (0..20).map(|i| format!("led{:02}", i)).collect();
+        let mut gates = vec![gate("a", "TOGGLE", 0), gate("b", "TOGGLE", 0)];
+        gates.extend(leds.iter().map(|id| gate(id, "LED", 1)));
+        let wires = leds.iter().map(|id| wire(&format!("w_{}", id), "a", 0, id, 0)).collect();
+        let mut engine = SimulationEngine::new();
+        engine.initialize(gates, wires);
+        engine.toggle_input("a").unwrap();
+        engine.run_until_stable(20);
+        assert_eq!(engine.get_fanout("a", 0), 20);
+        assert!(leds.iter().all(|id| inputs(&engine, id) == vec![StateType::One]));
+
+        // Move one wire to another source and drop a load
+        engine.add_wire(wire("w_led00", "b", 0, "led00", 0));
+        engine.remove_gate("led19").unwrap();
+        engine.toggle_input("a").unwrap();
+        engine.run_until_stable(20);
+        assert_eq!(engine.get_fanout("a", 0), 18);
+        assert_eq!(engine.get_fanout("b", 0), 1);
+        assert_eq!(inputs(&engine, "led00"), vec![StateType::Zero]);
+        assert!(leds[1..19].iter().all(|id| inputs(&engine, id) == vec![StateType::Zero]));
+
+        engine.toggle_input("b").unwrap();
+        engine.run_until_stable(20);
+        assert_eq!(inputs(&engine, "led00"), vec![StateType::One]);
+        assert_eq!(inputs(&engine, "led01"), vec![StateType::Zero]);
+    }
+
+    #[test]
+    fn test_truth_table_for_xor() {
+        let mut engine = SimulationEngine::new();
+        engine.initialize(
+            vec![gate("a", "TOGGLE", 0), gate("b", "TOGGLE", 0), gate("xor", "XOR", 2), gate("led", "LED", 1)],
+            vec![wire("w_a", "a", 0, "xor", 0), wire("w_b", "b", 0, "xor", 1), wire("w_out", "xor", 0, "led", 0)],
+        );
+        let table = engine
+            .compute_truth_table(vec!["a".into(), "b".into()], vec!["led".into()])
+            .unwrap();
+
+        let rows: Vec<(Vec<u8>, Vec<u8>, bool)> =
+            table.rows.into_iter().map(|r| (r.inputs, r.outputs, r.stable)).collect();
+        assert_eq!(
+            rows,
+            vec![
+                (vec![0, 0], vec![0], true),
+                (vec![0, 1], vec![1], true),
+                (vec![1, 0], vec![1], true),
+                (vec![1, 1], vec![0], true),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_truth_table_leaves_run_records_untouched() {
+        let mut engine = SimulationEngine::new();
+        engine.initialize(
+            vec![gate("a", "TOGGLE", 0), gate("b", "TOGGLE", 0), gate("and", "AND", 2), gate("led", "LED", 1)],
+            vec![wire("w_a", "a", 0, "and", 0), wire("w_b", "b", 0, "and", 1), wire("w_out", "and", 0, "led", 0)],
+        );
+        engine.enable_history(100);
+        engine.trace_signals(vec!["w_out".to_string()]);
+        engine.run_until_stable(10);
+        engine.set_command_logging(true);
+
+        let state = engine.export_state();
+        let activity = engine.get_activity_report();
+        let wire_transitions = engine.get_wire_transition_counts();
+        let trace = engine.get_trace().clone();
+        let history_len = engine.history.as_ref().map_or(0, VecDeque::len);
+
+        engine.compute_truth_table(vec!["a".into(), "b".into()], vec!["led".into()]).unwrap();
+        assert_eq!(engine.export_state(), state);
+        assert_eq!(engine.get_activity_report(), activity);
+        assert_eq!(engine.get_wire_transition_counts(), wire_transitions);
+        assert_eq!(engine.get_trace(), &trace);
+        assert_eq!(engine.history.as_ref().map_or(0, VecDeque::len), history_len);
+        assert_eq!(engine.command_log(), Some(&[][..]));
+    }
+
+    #[test]
+    fn test_truth_table_rejects_too_many_inputs() {
+        let mut engine = SimulationEngine::new();
+        let ids: Vec<String> = (0..17).map(|i| format!("in{}", i)).collect();
+        engine.initialize(ids.iter().map(|id| gate(id, "TOGGLE", 0)).collect(), vec![]);
+        assert!(engine.compute_truth_table(ids, vec![]).is_err());
+    }
+
+    #[test]
+    fn test_run_until_stable() {
+        let mut engine = SimulationEngine::new();
+        engine.initialize(
+            vec![gate("en", "TOGGLE", 0), gate("osc", "NOR", 2), gate("inv", "NOT", 1)],
+            vec![wire("w_en", "en", 0, "osc", 0), wire("w_fb", "osc", 0, "osc", 1), wire("w_inv", "en", 0, "inv", 0)],
+        );
+        engine.toggle_input("en").unwrap();
+        assert!(engine.run_until_stable(100));
+        assert_eq!(outputs(&engine, "inv")[0], StateType::Zero);
+
+        engine.toggle_input("en").unwrap();
+        assert!(!engine.run_until_stable(100));
+    }
+
+    #[test]
+    fn test_force_output_stuck_at_one() {
+        let mut engine = SimulationEngine::new();
+        engine.initialize(
+            vec![gate("a", "TOGGLE", 0), gate("b", "TOGGLE", 0), gate("and", "AND", 2), gate("led", "LED", 1)],
+            vec![wire("w1", "a", 0, "and", 0), wire("w2", "b", 0, "and", 1), wire("w3", "and", 0, "led", 0)],
+        );
+        assert!(engine.run_until_stable(100));
+        assert_eq!(engine.gates["led"].get_inputs(), &[StateType::Zero]);
+
+        engine.force_output("and", 0, StateType::One).unwrap();
+        assert!(engine.run_until_stable(100));
+        assert_eq!(engine.gates["led"].get_inputs(), &[StateType::One]);
+
+        // Re-evaluations keep the fault
+        engine.toggle_input("a").unwrap();
+        assert!(engine.run_until_stable(100));
+        assert_eq!(engine.gates["led"].get_inputs(), &[StateType::One]);
+
+        engine.release_output("and", 0).unwrap();
+        assert!(engine.run_until_stable(100));
+        assert_eq!(engine.gates["led"].get_inputs(), &[StateType::Zero]);
+
+        assert!(matches!(engine.force_output("and", 1, StateType::One), Err(SimError::OutputOutOfRange { .. })));
+        engine.force_output("and", 0, StateType::One).unwrap();
+        engine.reset(ResetMode::Full);
+        assert!(engine.run_until_stable(100));
+        assert_eq!(engine.gates["led"].get_inputs(), &[StateType::Zero]);
+    }
+
+    #[test]
+    fn test_fault_campaign_grades_vectors() {
+        let mut engine = SimulationEngine::new();
+        engine.initialize(
+            vec![gate("a", "TOGGLE", 0), gate("b", "TOGGLE", 0), gate("and", "AND", 2), gate("led", "LED", 1)],
+            vec![wire("w1", "a", 0, "and", 0), wire("w2", "b", 0, "and", 1), wire("w3", "and", 0, "led", 0)],
+        );
+        engine.enable_history(100);
+        assert!(engine.run_until_stable(100));
+        let history_len = engine.history.as_ref().map_or(0, VecDeque::len);
+        let activity = engine.get_activity_report();
+        let vector = |a: bool, b: bool| vec![("a".to_string(), a), ("b".to_string(), b)];
+
+        // 11 only detects stuck-at-0 faults
+        let report = engine.run_fault_campaign(vec![vector(true, true)], vec!["led".to_string()]).unwrap();
+        let detected: Vec<(&str, u8)> = report
+            .faults
+            .iter()
+            .filter(|fault| fault.detected_by.is_some())
+            .map(|fault| (fault.gate_id.as_str(), fault.stuck_at))
+            .collect();
+        assert_eq!(detected, vec![("a", 0), ("and", 0), ("b", 0)]);
+        assert_eq!((report.faults.len(), report.detected), (6, 3));
+
+        // The classic complete set for a 2-input AND: 11, 01, 10
+        let vectors = vec![vector(true, true), vector(false, true), vector(true, false)];
+        let report = engine.run_fault_campaign(vectors, vec!["led".to_string()]).unwrap();
+        assert_eq!(report.coverage, 1.0);
+        let and_stuck_high = report.faults.iter().find(|f| f.gate_id == "and" && f.stuck_at == 1).unwrap();
+        assert_eq!(and_stuck_high.detected_by, Some(1));
+
+        // The engine is left as it was, with no record of the probing runs
+        assert_eq!(engine.gates["led"].get_inputs(), &[StateType::Zero]);
+        assert_eq!(outputs(&engine, "a"), vec![StateType::Zero]);
+        assert_eq!(engine.history.as_ref().map_or(0, VecDeque::len), history_len);
+        assert_eq!(engine.get_activity_report(), activity);
+        assert!(engine.get_conflicts().is_empty());
+    }
+
+    #[test]
+    fn test_peek_pending_events_in_processing_order() {
+        let mut slow = gate("slow", "NOT", 1);
+        slow.delay = Some(5);
+        let mut engine = SimulationEngine::new();
+        engine.initialize(
+            vec![gate("in", "TOGGLE", 0), slow, gate("fast", "NOT", 1), gate("a", "LED", 1), gate("b", "LED", 1)],
+            vec![
+                wire("w1", "in", 0, "slow", 0),
+                wire("w2", "in", 0, "fast", 0),
+                wire("w3", "slow", 0, "a", 0),
+                wire("w4", "fast", 0, "b", 0),
+            ],
+        );
+        assert!(engine.run_until_stable(100));
+        engine.toggle_input("in").unwrap();
+        engine.step();
+        engine.step();
+
+        let pending = engine.peek_pending_events(10);
+        let summary: Vec<(&str, u64)> = pending.iter().map(|e| (e.gate_id.as_str(), e.time)).collect();
+        let now = engine.get_current_time();
+        assert_eq!(summary, vec![("b", now), ("a", now + 4)]);
+        assert!(pending.iter().all(|e| e.port.is_none()));
+        assert_eq!(engine.peek_pending_events(1).len(), 1);
+        assert_eq!(engine.queue_length(), 2);
+    }
+
+    #[test]
+    fn test_step_jumps_to_delayed_event() {
+        let mut slow = gate("slow", "NOT", 1);
+        slow.delay = Some(50);
+        let mut engine = SimulationEngine::new();
+        engine.initialize(
+            vec![gate("in", "TOGGLE", 0), slow, gate("out", "BUFFER", 1)],
+            vec![wire("w_in", "in", 0, "slow", 0), wire("w_out", "slow", 0, "out", 0)],
+        );
+        assert!(engine.run_until_stable(1000));
+
+        engine.toggle_input("in").unwrap();
+        let mut evaluated_at = engine.get_current_time();
+        while outputs(&engine, "slow")[0] != StateType::Zero {
+            evaluated_at = engine.get_current_time();
+            engine.step();
+        }
+        assert_eq!(outputs(&engine, "out")[0], StateType::One);
+        assert_eq!(engine.get_current_time(), evaluated_at + 50);
+
+        // A single step reaches the delayed event
+        engine.step();
+        assert_eq!(outputs(&engine, "out")[0], StateType::Zero);
+    }
+
+    #[test]
+    fn test_wire_delay_postpones_arrival() {
+        let mut long_trace = wire("w_long", "in", 0, "and", 0);
+        long_trace.delay = 20;
+        let mut engine = SimulationEngine::new();
+        engine.initialize(
+            vec![gate("in", "TOGGLE", 0), gate("en", "TOGGLE", 0), gate("and", "AND", 2)],
+            vec![long_trace, wire("w_en", "en", 0, "and", 1)],
+        );
+        engine.toggle_input("en").unwrap();
+        assert!(engine.run_until_stable(1000));
+        assert_eq!(outputs(&engine, "and"), vec![StateType::Zero]);
+
+        engine.toggle_input("in").unwrap();
+        let toggled_at = engine.get_current_time();
+        engine.advance_by(5);
+        // Re-evaluations through the undelayed input must not see the
+        // delayed value before it arrives
+        engine.toggle_input("en").unwrap();
+        engine.advance_by(2);
+        engine.toggle_input("en").unwrap();
+        engine.advance_by(2);
+        assert_eq!(engine.gates["and"].get_inputs(), &[StateType::Zero, StateType::One]);
+        assert_eq!(outputs(&engine, "and"), vec![StateType::Zero]);
+
+        engine.advance_by(11);
+        assert_eq!(outputs(&engine, "and"), vec![StateType::Zero]);
+        // Source gate delay of 1 plus the wire's 20
+        engine.advance_by(1);
+        assert_eq!(outputs(&engine, "and"), vec![StateType::One]);
+        assert_eq!(engine.get_current_time(), toggled_at + 21);
+        assert_eq!(engine.get_snapshot().wires.iter().find(|w| w.id == "w_long").unwrap().delay, 20);
+    }
+
+    #[test]
+    fn test_self_loop_feedback_divides_clock() {
+        let mut engine = SimulationEngine::new();
+        engine.initialize(
+            vec![gate("clk", "CLOCK", 0), gate("tff", "DFF", 2), gate("led", "LED", 1)],
+            vec![
+                wire("w_clk", "clk", 0, "tff", 1),
+                // Q_bar back into D: toggles on every rising clock edge
+                wire("w_fb", "tff", 1, "tff", 0),
+                wire("w_q", "tff", 0, "led", 0),
+            ],
+        );
+        engine.set_clock_period("clk", 5).unwrap();
+
+        // Sample mid-way between edges, once per clock cycle
+        engine.advance_by(13);
+        let mut samples = Vec::new();
+        for _ in 0..6 {
+            samples.push(outputs(&engine, "tff")[0]);
+            engine.advance_by(10);
+        }
+        assert!(samples.windows(2).all(|pair| pair[0] != pair[1]), "{:?}", samples);
+        assert!(!samples.contains(&StateType::Unknown));
+        assert!(engine.get_unstable_gates().is_empty());
+
+        // The new Q_bar reaches D only after the gate's delay
+        loop {
+            let event = engine.step_single_event().unwrap();
+            if event.gate_id == "tff" && !event.changed_outputs.is_empty() {
+                break;
+            }
+        }
+        let toggled_at = engine.get_current_time();
+        assert_ne!(engine.gates["tff"].get_inputs()[0], outputs(&engine, "tff")[1]);
+        engine.advance_by(1);
+        assert_eq!(engine.gates["tff"].get_inputs()[0], outputs(&engine, "tff")[1]);
+        assert_eq!(engine.get_current_time(), toggled_at + 1);
+    }
+
+    #[test]
+    fn test_snapshot_exposes_segments() {
+        let mut engine = SimulationEngine::new();
+        engine.initialize(
+            vec![gate("b0", "TOGGLE", 0), gate("b1", "TOGGLE", 0), gate("b2", "TOGGLE", 0), gate("b3", "TOGGLE", 0), gate("seg", "SEVEN_SEG", 4)],
+            (0..4).map(|i| wire(&format!("w{}", i), &format!("b{}", i), 0, "seg", i)).collect(),
+        );
+        engine.toggle_input("b0").unwrap();
+        engine.toggle_input("b1").unwrap();
+        assert!(engine.run_until_stable(100));
+
+        let snapshot = engine.get_snapshot();
+        let seg = snapshot.gates.iter().find(|g| g.id == "seg").unwrap();
+        assert_eq!(seg.segments, Some(vec![1, 1, 1, 1, 0, 0, 1]));
+        assert!(snapshot.gates.iter().filter(|g| g.id != "seg").all(|g| g.segments.is_none()));
+    }
+
+    #[test]
+    fn test_pull_up_overridden_by_strong_driver() {
+        let mut engine = SimulationEngine::new();
+        engine.initialize(
+            vec![gate("data", "TOGGLE", 0), gate("en", "TOGGLE", 0), gate("buf", "TRI_BUFFER", 2), gate("pu", "PULLUP", 0), gate("led", "LED", 1)],
+            vec![
+                wire("w_data", "data", 0, "buf", 0),
+                wire("w_en", "en", 0, "buf", 1),
+                wire("w_buf", "buf", 0, "led", 0),
+                wire("w_pu", "pu", 0, "led", 0),
+            ],
+        );
+        assert!(engine.run_until_stable(100));
+        assert_eq!(engine.wires["w_pu"].state[0], StateType::WeakOne);
+        assert_eq!(inputs(&engine, "led")[0], StateType::One);
+
+        engine.toggle_input("en").unwrap();
+        assert!(engine.run_until_stable(100));
+        assert_eq!(inputs(&engine, "led")[0], StateType::Zero);
+    }
+
+    #[test]
+    fn test_state_delta_reports_only_changes() {
+        let mut engine = SimulationEngine::new();
+        engine.initialize(
+            vec![gate("a", "TOGGLE", 0), gate("inv", "NOT", 1), gate("b", "TOGGLE", 0), gate("led", "LED", 1)],
+            vec![wire("w_a", "a", 0, "inv", 0), wire("w_b", "b", 0, "led", 0)],
+        );
+        assert!(engine.run_until_stable(100));
+        assert_eq!(engine.take_state_delta().gates.len(), 4);
+        assert!(engine.take_state_delta().gates.is_empty());
+
+        engine.toggle_input("a").unwrap();
+        assert!(engine.run_until_stable(100));
+        let delta = engine.take_state_delta();
+        let mut gate_ids: Vec<String> = delta.gates.into_iter().map(|g| g.id).collect();
+        gate_ids.sort();
+        assert_eq!(gate_ids, vec!["a", "inv"]);
+        assert_eq!(delta.wires.into_iter().map(|w| w.id).collect::<Vec<_>>(), vec!["w_a"]);
+        assert_eq!(delta.time, engine.get_current_time());
+    }
+
+    #[test]
+    fn test_floating_inputs() {
+        let mut engine = SimulationEngine::new();
+        engine.initialize(
+            vec![gate("a", "TOGGLE", 0), gate("and", "AND", 2), gate("led", "LED", 1)],
+            vec![wire("w1", "a", 0, "and", 1), wire("w2", "and", 0, "led", 0)],
+        );
+
+        assert_eq!(engine.get_floating_inputs(), vec![("and".to_string(), 0)]);
+    }
+
+    #[test]
+    fn test_multi_output_gate_drives_each_port() {
+        let mut engine = SimulationEngine::new();
+        engine.initialize(
+            vec![
+                gate("d", "TOGGLE", 0),
+                gate("clk", "TOGGLE", 0),
+                gate("dff", "DFF", 2),
+                gate("q", "LED", 1),
+                gate("q_bar", "LED", 1),
+            ],
+            vec![
+                wire("w_d", "d", 0, "dff", 0),
+                wire("w_clk", "clk", 0, "dff", 1),
+                wire("w_q", "dff", 0, "q", 0),
+                wire("w_q_bar", "dff", 1, "q_bar", 0),
+            ],
+        );
+        assert!(engine.run_until_stable(100));
+
+        engine.toggle_input("d").unwrap();
+        engine.toggle_input("clk").unwrap();
+        assert!(engine.run_until_stable(100));
+        assert_eq!(inputs(&engine, "q"), vec![StateType::One]);
+        assert_eq!(inputs(&engine, "q_bar"), vec![StateType::Zero]);
+
+        engine.toggle_input("d").unwrap();
+        engine.toggle_input("clk").unwrap();
+        assert!(engine.run_until_stable(100));
+        engine.toggle_input("clk").unwrap();
+        assert!(engine.run_until_stable(100));
+        assert_eq!(inputs(&engine, "q"), vec![StateType::Zero]);
+        assert_eq!(inputs(&engine, "q_bar"), vec![StateType::One]);
+    }
+
+    #[test]
+    fn test_glitch_detection_reports_static_hazard() {
+        let mut slow_buffer = gate("buf", "BUFFER", 1);
+        slow_buffer.delay = Some(3);
+
+        let mut engine = SimulationEngine::new();
+        engine.set_glitch_detection(true);
+        engine.initialize(
+            vec![gate("a", "TOGGLE", 0), slow_buffer, gate("inv", "NOT", 1), gate("and", "AND", 2)],
+            vec![
+                wire("w_a", "a", 0, "and", 0),
+                wire("w_a_buf", "a", 0, "buf", 0),
+                wire("w_buf", "buf", 0, "inv", 0),
+                wire("w_inv", "inv", 0, "and", 1),
+            ],
+        );
+        assert!(engine.run_until_stable(100));
+        engine.clear_glitches();
+
+        // a rises while the slow inverting path still outputs ONE: AND pulses high
+        engine.toggle_input("a").unwrap();
+        assert!(engine.run_until_stable(100));
+        assert_eq!(outputs(&engine, "and"), vec![StateType::Zero]);
+
+        let glitches = engine.get_glitches();
+        assert_eq!(glitches.len(), 1);
+        assert_eq!(glitches[0].gate_id, "and");
+        assert_eq!(glitches[0].port_index, 0);
+        assert_eq!(glitches[0].values, vec![0, 1, 0]);
+        assert!(glitches[0].end_time > glitches[0].start_time);
+    }
+
+    #[test]
+    fn test_glitch_detection_with_free_running_clock() {
+        let mut slow_buffer = gate("buf", "BUFFER", 1);
+        slow_buffer.delay = Some(3);
+
+        let mut engine = SimulationEngine::new();
+        engine.set_glitch_detection(true);
+        engine.initialize(
+            vec![gate("clk", "CLOCK", 0), gate("a", "TOGGLE", 0), slow_buffer, gate("inv", "NOT", 1), gate("and", "AND", 2)],
+            vec![
+                wire("w_a", "a", 0, "and", 0),
+                wire("w_a_buf", "a", 0, "buf", 0),
+                wire("w_buf", "buf", 0, "inv", 0),
+                wire("w_inv", "inv", 0, "and", 1),
+            ],
+        );
+        engine.advance_by(20);
+        engine.clear_glitches();
+
+        // The clock keeps the queue busy, but the hazard's cone settles
+        engine.toggle_input("a").unwrap();
+        engine.advance_by(20);
+        assert!(!engine.event_queue.is_empty());
+        assert_eq!(outputs(&engine, "and"), vec![StateType::Zero]);
+
+        let glitches = engine.get_glitches();
+        assert_eq!(glitches.len(), 1);
+        assert_eq!(glitches[0].gate_id, "and");
+        assert_eq!(glitches[0].values, vec![0, 1, 0]);
+        assert!(engine.output_transitions.is_empty());
+    }
+
+    #[test]
+    fn test_bus_node_conflict_between_enabled_drivers() {
+        let mut engine = SimulationEngine::new();
+        engine.initialize(
+            vec![
+                gate("low", "CONST_LOW", 0),
+                gate("high", "CONST_HIGH", 0),
+                gate("en", "TOGGLE", 0),
+                gate("tb0", "TRI_BUFFER", 2),
+                gate("tb1", "TRI_BUFFER", 2),
+                gate("bus", "BUS", 2),
+            ],
+            vec![
+                wire("w_d0", "low", 0, "tb0", 0),
+                wire("w_d1", "high", 0, "tb1", 0),
+                wire("w_en0", "en", 0, "tb0", 1),
+                wire("w_en1", "en", 0, "tb1", 1),
+                wire("w_b0", "tb0", 0, "bus", 0),
+                wire("w_b1", "tb1", 0, "bus", 1),
+            ],
+        );
+        assert!(engine.run_until_stable(100));
+        assert_eq!(outputs(&engine, "bus"), vec![StateType::HiZ]);
+
+        engine.toggle_input("en").unwrap();
+        assert!(engine.run_until_stable(100));
+        assert_eq!(outputs(&engine, "bus"), vec![StateType::Conflict]);
+    }
+
+    #[test]
+    fn test_max_events_per_step_truncation() {
+        let mut engine = SimulationEngine::new();
+        engine.initialize(
+            vec![gate("a", "TOGGLE", 0), gate("b", "TOGGLE", 0), gate("c", "TOGGLE", 0)],
+            vec![],
+        );
+        engine.set_max_events_per_step(2);
+        assert_eq!(engine.queue_length(), 3);
+
+        assert_eq!(engine.step(), 2);
+        assert!(engine.last_step_truncated());
+        assert_eq!(engine.get_current_time(), 0);
+        assert_eq!(engine.queue_length(), 1);
+
+        assert_eq!(engine.step(), 1);
+        assert!(!engine.last_step_truncated());
+        assert_eq!(engine.step(), 0);
+        assert!(engine.run_until_stable(10));
+    }
+
+    #[test]
+    fn test_inverted_input_survives_state_round_trip() {
+        let mut and = gate("and", "AND", 2);
+        and.inverted_inputs = Some(vec![false, true]);
+
+        let mut engine = SimulationEngine::new();
+        engine.initialize(
+            vec![gate("a", "TOGGLE", 0), gate("b", "TOGGLE", 0), and],
+            vec![wire("w_a", "a", 0, "and", 0), wire("w_b", "b", 0, "and", 1)],
+        );
+        engine.toggle_input("a").unwrap();
+        assert!(engine.run_until_stable(100));
+        assert_eq!(outputs(&engine, "and"), vec![StateType::One]);
+        assert_eq!(inputs(&engine, "and"), vec![StateType::One, StateType::One]);
+
+        let saved = engine.export_state();
+        engine.import_state(saved.clone());
+        assert_eq!(engine.export_state(), saved);
+    }
+
+    #[test]
+    fn test_randomize_initial_state_is_seeded() {
+        let gates = vec![
+            gate("d", "TOGGLE", 0),
+            gate("clk", "TOGGLE", 0),
+            gate("r0", "DFF", 2),
+            gate("r1", "DFF", 2),
+            gate("r2", "DFF", 2),
+            gate("r3", "DFF", 2),
+        ];
+        let mut wires = Vec::new();
+        for r in ["r0", "r1", "r2", "r3"] {
+            wires.push(wire(&format!("w_d_{}", r), "d", 0, r, 0));
+            wires.push(wire(&format!("w_clk_{}", r), "clk", 0, r, 1));
+        }
+
+        let mut engine = SimulationEngine::new();
+        engine.initialize(gates, wires);
+        assert!(engine.run_until_stable(100));
+
+        engine.randomize_initial_state(42);
+        let first = engine.export_state();
+        engine.randomize_initial_state(42);
+        let again = engine.export_state();
+        assert_eq!((&again.gates, &again.wires), (&first.gates, &first.wires));
+        engine.randomize_initial_state(7);
+        let other = engine.export_state();
+        assert_ne!((&other.gates, &other.wires), (&first.gates, &first.wires));
+
+        // Clocking in a defined value converges regardless of power-on state
+        assert!(engine.run_until_stable(100));
+        engine.toggle_input("clk").unwrap();
+        assert!(engine.run_until_stable(100));
+        for r in ["r0", "r1", "r2", "r3"] {
+            assert_eq!(outputs(&engine, r), vec![StateType::Zero, StateType::One]);
+        }
+
+        engine.reset(ResetMode::Full);
+        assert!(engine.run_until_stable(100));
+        assert_eq!(outputs(&engine, "r0"), vec![StateType::Zero, StateType::One]);
+    }
+
+    #[test]
+    fn test_subcircuit_gate_in_circuit() {
+        let mut inverter = gate("sub", "SUBCIRCUIT", 1);
+        inverter.subcircuit = Some(SubcircuitDefinition {
+            gates: vec![gate("in", "TOGGLE", 0), gate("not", "NOT", 1), gate("out", "LED", 1)],
+            wires: vec![wire("i1", "in", 0, "not", 0), wire("i2", "not", 0, "out", 0)],
+            input_ids: vec!["in".to_string()],
+            output_ids: vec!["out".to_string()],
+        });
+
+        let mut engine = SimulationEngine::new();
+        engine.initialize(
+            vec![gate("a", "TOGGLE", 0), inverter, gate("led", "LED", 1)],
+            vec![wire("w1", "a", 0, "sub", 0), wire("w2", "sub", 0, "led", 0)],
+        );
+        assert!(engine.run_until_stable(100));
+        assert_eq!(inputs(&engine, "led"), vec![StateType::One]);
+
+        engine.toggle_input("a").unwrap();
+        assert!(engine.run_until_stable(100));
+        assert_eq!(inputs(&engine, "led"), vec![StateType::Zero]);
+    }
+
+    #[test]
+    fn test_subcircuit_forwards_inner_clock() {
+        let mut oscillator = gate("sub", "SUBCIRCUIT", 0);
+        oscillator.subcircuit = Some(SubcircuitDefinition {
+            gates: vec![gate("clk", "CLOCK", 0), gate("out", "LED", 1)],
+            wires: vec![wire("i1", "clk", 0, "out", 0)],
+            input_ids: vec![],
+            output_ids: vec!["out".to_string()],
+        });
+
+        let mut engine = SimulationEngine::new();
+        engine.initialize(vec![oscillator, gate("led", "LED", 1)], vec![wire("w1", "sub", 0, "led", 0)]);
+
+        // The inner clock toggles every 10 time units, seen one delay later
+        engine.advance_by(5);
+        let mut samples = Vec::new();
+        for _ in 0..4 {
+            samples.push(inputs(&engine, "led")[0]);
+            engine.advance_by(10);
+        }
+        assert_eq!(samples, vec![StateType::Zero, StateType::One, StateType::Zero, StateType::One]);
+    }
+
+    #[test]
+    fn test_step_back_restores_previous_states() {
+        let (gates, wires) = counter_circuit();
+        let mut engine = SimulationEngine::new();
+        engine.initialize(gates, wires);
+        engine.set_clock_period("clk", 2).unwrap();
+        engine.toggle_input("t").unwrap();
+        engine.enable_history(3);
+        assert!(!engine.step_back());
+
+        let mut states = Vec::new();
+        for _ in 0..5 {
+            states.push(engine.export_state());
+            engine.step();
+        }
+
+        // Only the last three steps are kept
+        for expected in states.iter().rev().take(3) {
+            assert!(engine.step_back());
+            assert_eq!(&engine.export_state(), expected);
+        }
+        assert!(!engine.step_back());
+
+        engine.enable_history(0);
+        engine.step();
+        assert!(!engine.step_back());
+    }
+
+    #[test]
+    fn test_step_back_undoes_forces_and_disables() {
+        let mut engine = SimulationEngine::new();
+        engine.initialize(
+            vec![gate("a", "TOGGLE", 0), gate("inv", "NOT", 1), gate("buf", "BUFFER", 1), gate("led", "LED", 1)],
+            vec![wire("w1", "a", 0, "inv", 0), wire("w2", "inv", 0, "buf", 0), wire("w3", "buf", 0, "led", 0)],
+        );
+        engine.run_until_stable(10);
+        engine.enable_history(4);
+
+        engine.step();
+        engine.force_output("inv", 0, StateType::Zero).unwrap();
+        engine.set_gate_enabled("buf", false).unwrap();
+        engine.step();
+        let saved = engine.export_state();
+        assert_eq!(saved.forced_outputs, vec![("inv".to_string(), 0, StateType::Zero.to_u8())]);
+        assert_eq!(saved.disabled_gates, vec!["buf".to_string()]);
+
+        // Back to before the force and disable were applied
+        assert!(engine.step_back());
+        assert!(engine.step_back());
+        assert!(engine.is_gate_enabled("buf"));
+        engine.toggle_input("a").unwrap();
+        engine.run_until_stable(10);
+        assert_eq!(outputs(&engine, "inv"), vec![StateType::Zero]);
+        engine.toggle_input("a").unwrap();
+        engine.run_until_stable(10);
+        assert_eq!(outputs(&engine, "inv"), vec![StateType::One]);
+        assert_eq!(inputs(&engine, "led"), vec![StateType::One]);
+
+        engine.import_state(saved);
+        assert!(!engine.is_gate_enabled("buf"));
+        engine.toggle_input("a").unwrap();
+        engine.run_until_stable(10);
+        assert_eq!(outputs(&engine, "inv"), vec![StateType::Zero]);
+    }
+
+    #[test]
+    fn test_floating_input_policy() {
+        let mut engine = SimulationEngine::new();
+        engine.initialize(
+            vec![
+                gate("a", "TOGGLE", 0),
+                gate("en", "TOGGLE", 0),
+                gate("tb", "TRI_BUFFER", 2),
+                gate("and", "AND", 2),
+                gate("bus", "BUS", 2),
+            ],
+            vec![
+                wire("w_a", "a", 0, "tb", 0),
+                wire("w_en", "en", 0, "tb", 1),
+                wire("w_tb", "tb", 0, "and", 0),
+                wire("w_bus", "tb", 0, "bus", 0),
+            ],
+        );
+        engine.toggle_input("a").unwrap();
+        assert!(engine.run_until_stable(100));
+        assert_eq!(inputs(&engine, "and"), vec![StateType::HiZ, StateType::Unknown]);
+        assert_eq!(outputs(&engine, "and"), vec![StateType::Unknown]);
+
+        engine.set_floating_input_policy(FloatingInputPolicy::AsOne);
+        assert!(engine.run_until_stable(100));
+        assert_eq!(inputs(&engine, "and"), vec![StateType::One, StateType::One]);
+        assert_eq!(outputs(&engine, "and"), vec![StateType::One]);
+        assert_eq!(inputs(&engine, "bus"), vec![StateType::HiZ, StateType::HiZ]);
+
+        engine.set_floating_input_policy(FloatingInputPolicy::AsZero);
+        assert!(engine.run_until_stable(100));
+        assert_eq!(outputs(&engine, "and"), vec![StateType::Zero]);
+    }
+
+    #[test]
+    fn test_floating_input_policy_applies_to_edits() {
+        let mut engine = SimulationEngine::new();
+        engine.set_floating_input_policy(FloatingInputPolicy::AsOne);
+        engine.initialize(vec![gate("a", "TOGGLE", 0)], vec![]);
+        engine.toggle_input("a").unwrap();
+
+        engine.add_gate(gate("and", "AND", 2));
+        engine.add_wire(wire("w1", "a", 0, "and", 0));
+        assert!(engine.run_until_stable(100));
+        assert_eq!(inputs(&engine, "and"), vec![StateType::One, StateType::One]);
+        assert_eq!(outputs(&engine, "and"), vec![StateType::One]);
+
+        // Moving the wire to port 1 leaves port 0 floating
+        engine.add_wire(wire("w1", "a", 0, "and", 1));
+        engine.toggle_input("a").unwrap();
+        assert!(engine.run_until_stable(100));
+        assert_eq!(inputs(&engine, "and"), vec![StateType::One, StateType::Zero]);
+
+        engine.remove_gate("a").unwrap();
+        assert!(engine.run_until_stable(100));
+        assert_eq!(inputs(&engine, "and"), vec![StateType::One, StateType::One]);
+        assert_eq!(outputs(&engine, "and"), vec![StateType::One]);
+    }
+
+    #[test]
+    fn test_unknown_policy() {
+        let mut engine = SimulationEngine::new();
+        engine.initialize(
+            vec![
+                gate("a", "TOGGLE", 0),
+                gate("xor", "XOR", 2),
+                gate("xnor", "XNOR", 2),
+                gate("and", "AND", 2),
+                gate("nor", "NOR", 2),
+                gate("not", "NOT", 1),
+            ],
+            vec![
+                wire("w1", "a", 0, "xor", 0),
+                wire("w2", "a", 0, "xnor", 0),
+                wire("w4", "a", 0, "and", 0),
+                wire("w5", "a", 0, "nor", 0),
+            ],
+        );
+        engine.toggle_input("a").unwrap();
+        assert!(engine.run_until_stable(100));
+        assert_eq!(outputs(&engine, "xor"), vec![StateType::Unknown]);
+        assert_eq!(outputs(&engine, "xnor"), vec![StateType::Unknown]);
+        assert_eq!(outputs(&engine, "and"), vec![StateType::Unknown]);
+        assert_eq!(outputs(&engine, "nor"), vec![StateType::Zero]);
+        assert_eq!(outputs(&engine, "not"), vec![StateType::Unknown]);
+
+        // Every logic op reads the floating operands as ZERO
+        engine.set_unknown_policy(UnknownPolicy::Optimistic);
+        assert!(engine.run_until_stable(100));
+        assert_eq!(outputs(&engine, "xor"), vec![StateType::One]);
+        assert_eq!(outputs(&engine, "xnor"), vec![StateType::Zero]);
+        assert_eq!(outputs(&engine, "and"), vec![StateType::Zero]);
+        assert_eq!(outputs(&engine, "nor"), vec![StateType::Zero]);
+        assert_eq!(outputs(&engine, "not"), vec![StateType::One]);
+
+        // Gates added later follow the engine's policy
+        engine.add_gate(gate("late", "XOR", 2));
+        engine.add_wire(wire("w3", "a", 0, "late", 0));
+        assert!(engine.run_until_stable(100));
+        assert_eq!(outputs(&engine, "late"), vec![StateType::One]);
+
+        engine.set_unknown_policy(UnknownPolicy::Pessimistic);
+        assert!(engine.run_until_stable(100));
+        assert_eq!(outputs(&engine, "xor"), vec![StateType::Unknown]);
+    }
+
+    #[test]
+    fn test_probe_values_by_label() {
+        let mut w_a = wire("w_a", "a", 0, "led_a", 0);
+        w_a.label = Some("A".to_string());
+        let mut w_b = wire("w_b", "b", 0, "led_b", 0);
+        w_b.label = Some("B".to_string());
+
+        let mut engine = SimulationEngine::new();
+        engine.initialize(
+            vec![gate("a", "TOGGLE", 0), gate("b", "TOGGLE", 0), gate("led_a", "LED", 1), gate("led_b", "LED", 1)],
+            vec![w_a, w_b],
+        );
+        engine.toggle_input("a").unwrap();
+        assert!(engine.run_until_stable(100));
+
+        let values = engine.get_probe_values(vec!["A".to_string(), "B".to_string(), "C".to_string()]);
+        assert_eq!(values.get("A"), Some(&StateType::One.to_u8()));
+        assert_eq!(values.get("B"), Some(&StateType::Zero.to_u8()));
+        assert!(!values.contains_key("C"));
+        assert_eq!(engine.get_snapshot().wires.iter().filter(|w| w.label.is_some()).count(), 2);
+    }
+
+    #[test]
+    fn test_set_inputs_applies_levels_together() {
+        let mut engine = SimulationEngine::new();
+        engine.set_glitch_detection(true);
+        engine.initialize(
+            vec![gate("a", "TOGGLE", 0), gate("b", "TOGGLE", 0), gate("xor", "XOR", 2)],
+            vec![wire("w_a", "a", 0, "xor", 0), wire("w_b", "b", 0, "xor", 1)],
+        );
+        assert!(engine.run_until_stable(100));
+
+        engine.set_inputs(vec![("a".to_string(), true), ("b".to_string(), true)]).unwrap();
+        assert!(engine.run_until_stable(100));
+        assert_eq!(outputs(&engine, "a"), vec![StateType::One]);
+        assert_eq!(outputs(&engine, "xor"), vec![StateType::Zero]);
+        assert!(engine.get_glitches().iter().all(|g| g.gate_id != "xor"));
+
+        // Levels are absolute: setting again does not toggle back
+        engine.set_inputs(vec![("a".to_string(), true), ("b".to_string(), false)]).unwrap();
+        assert!(engine.run_until_stable(100));
+        assert_eq!(outputs(&engine, "xor"), vec![StateType::One]);
+    }
+
+    #[test]
+    fn test_set_input_level_is_idempotent() {
+        let mut engine = SimulationEngine::new();
+        engine.initialize(
+            vec![gate("a", "TOGGLE", 0), gate("led", "LED", 1)],
+            vec![wire("w", "a", 0, "led", 0)],
+        );
+
+        // Repeated requests for the same level (e.g. double clicks) don't flip it
+        engine.set_input_level("a", StateType::One).unwrap();
+        engine.set_input_level("a", StateType::One).unwrap();
+        assert!(engine.run_until_stable(100));
+        assert_eq!(inputs(&engine, "led"), vec![StateType::One]);
+
+        engine.set_input_level("a", StateType::Zero).unwrap();
+        assert!(engine.run_until_stable(100));
+        assert_eq!(inputs(&engine, "led"), vec![StateType::Zero]);
+    }
+
+    #[test]
+    fn test_unknown_gate_ids_are_reported() {
+        let mut engine = SimulationEngine::new();
+        engine.initialize(vec![gate("a", "TOGGLE", 0)], vec![]);
+
+        let missing = Err(SimError::GateNotFound("nope".to_string()));
+        assert_eq!(engine.toggle_input("nope"), missing);
+        assert_eq!(engine.set_clock_period("nope", 4), missing);
+        assert_eq!(engine.remove_gate("nope"), missing);
+        assert_eq!(engine.set_inputs(vec![("a".to_string(), true), ("nope".to_string(), true)]), missing);
+
+        // A rejected batch leaves the valid entries untouched
+        assert!(engine.run_until_stable(100));
+        assert_eq!(outputs(&engine, "a"), vec![StateType::Zero]);
+        assert_eq!(engine.toggle_input("a"), Ok(()));
+    }
+
+    #[test]
+    fn test_initialize_reports_wiring_errors() {
+        // Ports past the largest port number must not overflow
+        let mut w_edge = wire("w_edge", "a", 0, "and", u32::MAX);
+        w_edge.width = 2;
+        // Both ends of a bidirectional wire are output pins
+        let mut w_pin = wire("w_pin", "a", 0, "and", 1);
+        w_pin.bidirectional = true;
+        let mut engine = SimulationEngine::new();
+        let errors = engine.initialize(
+            vec![gate("a", "TOGGLE", 0), gate("and", "AND", 2)],
+            vec![
+                wire("ok", "a", 0, "and", 0),
+                w_edge,
+                wire("w_high", "a", 0, "and", 5),
+                w_pin,
+                wire("w_src", "a", 1, "and", 1),
+                wire("w_ghost", "ghost", 0, "and", 1),
+            ],
+        );
+
+        assert_eq!(
+            errors,
+            vec![
+                WireError::SourcePortOutOfRange {
+                    wire_id: "w_edge".to_string(),
+                    gate_id: "a".to_string(),
+                    port_index: 1,
+                    output_count: 1,
+                },
+                WireError::TargetPortOutOfRange {
+                    wire_id: "w_edge".to_string(),
+                    gate_id: "and".to_string(),
+                    port_index: u32::MAX,
+                    input_count: 2,
+                },
+                WireError::MissingGate { wire_id: "w_ghost".to_string(), gate_id: "ghost".to_string() },
+                WireError::TargetPortOutOfRange {
+                    wire_id: "w_high".to_string(),
+                    gate_id: "and".to_string(),
+                    port_index: 5,
+                    input_count: 2,
+                },
+                WireError::TargetPinOutOfRange {
+                    wire_id: "w_pin".to_string(),
+                    gate_id: "and".to_string(),
+                    port_index: 1,
+                    output_count: 1,
+                },
+                WireError::SourcePortOutOfRange {
+                    wire_id: "w_src".to_string(),
+                    gate_id: "a".to_string(),
+                    port_index: 1,
+                    output_count: 1,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_measure_frequency_halves_through_flip_flop() {
+        let (mut gates, mut wires) = counter_circuit();
+        gates.push(gate("led", "LED", 1));
+        wires.push(wire("w_q", "tff", 0, "led", 0));
+
+        let mut engine = SimulationEngine::new();
+        engine.initialize(gates, wires);
+        engine.set_clock_period("clk", 2).unwrap();
+        engine.toggle_input("t").unwrap();
+        while engine.get_current_time() < 100 {
+            engine.step();
+        }
+
+        let clock = engine.measure_frequency("w_clk", 80).unwrap();
+        let q = engine.measure_frequency("w_q", 80).unwrap();
+        assert!((clock - 0.25).abs() < 0.01, "clock frequency {}", clock);
+        assert!((q - 0.125).abs() < 0.01, "divided frequency {}", q);
+
+        assert_eq!(engine.measure_frequency("w_clk", 0), None);
+        assert_eq!(engine.measure_frequency("missing", 10), None);
     }
 }
@@ -0,0 +1,110 @@
+//! Simulation errors
+
+use std::fmt;
+
+use serde::Serialize;
+
+/// Error returned by engine operations that reference gates by ID
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SimError {
+    /// No gate with the given ID is loaded
+    GateNotFound(String),
+    /// No wire with the given ID is loaded
+    WireNotFound(String),
+    /// The gate exists but has the wrong type for the operation
+    UnexpectedGateType { gate_id: String, expected: String },
+    /// More inputs than the operation supports
+    TooManyInputs { max: usize, got: usize },
+    /// A netlist could not be parsed
+    InvalidNetlist(String),
+    /// Netlist wires reference gates that are not defined
+    MissingGates(Vec<String>),
+    /// The gate has no output with the given index
+    OutputOutOfRange { gate_id: String, port: usize, output_count: usize },
+    /// A command log could not be parsed
+    InvalidCommandLog(String),
+    /// A replayed command found the engine at a different time than when it
+    /// was recorded
+    ReplayDiverged { index: usize, expected_time: u64, actual_time: u64 },
+    /// A loop of gates with no sequential gate to break it
+    CombinationalLoop(Vec<String>),
+    /// The requested time has already been simulated
+    TimeInPast { time: u64, current_time: u64 },
+}
+
+impl fmt::Display for SimError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SimError::GateNotFound(id) => write!(f, "Gate {} not found", id),
+            SimError::WireNotFound(id) => write!(f, "Wire {} not found", id),
+            SimError::UnexpectedGateType { gate_id, expected } => {
+                write!(f, "Gate {} is not a {} gate", gate_id, expected)
+            }
+            SimError::TooManyInputs { max, got } => {
+                write!(f, "At most {} inputs are supported, got {}", max, got)
+            }
+            SimError::InvalidNetlist(reason) => write!(f, "Invalid netlist: {}", reason),
+            SimError::MissingGates(ids) => {
+                write!(f, "Netlist wires reference missing gates: {}", ids.join(", "))
+            }
+            SimError::OutputOutOfRange { gate_id, port, output_count } => {
+                write!(f, "Gate {} has {} outputs, no output {}", gate_id, output_count, port)
+            }
+            SimError::InvalidCommandLog(reason) => write!(f, "Invalid command log: {}", reason),
+            SimError::ReplayDiverged { index, expected_time, actual_time } => write!(
+                f,
+                "Replay diverged at command {}: recorded at time {}, replayed at time {}",
+                index, expected_time, actual_time
+            ),
+            SimError::CombinationalLoop(ids) => {
+                write!(f, "Combinational loop through gates: {}", ids.join(" -> "))
+            }
+            SimError::TimeInPast { time, current_time } => {
+                write!(f, "Time {} has already passed (now {})", time, current_time)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SimError {}
+
+/// Wiring problem found while validating a circuit
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+#[serde(tag = "kind")]
+pub enum WireError {
+    /// The wire references a gate that is not loaded
+    MissingGate { wire_id: String, gate_id: String },
+    /// The wire's source ports exceed the source gate's outputs
+    SourcePortOutOfRange { wire_id: String, gate_id: String, port_index: u32, output_count: usize },
+    /// The wire's target ports exceed the target gate's inputs
+    TargetPortOutOfRange { wire_id: String, gate_id: String, port_index: u32, input_count: usize },
+    /// The bidirectional wire's target pins exceed the target gate's outputs
+    TargetPinOutOfRange { wire_id: String, gate_id: String, port_index: u32, output_count: usize },
+}
+
+impl fmt::Display for WireError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WireError::MissingGate { wire_id, gate_id } => {
+                write!(f, "Wire {} references missing gate {}", wire_id, gate_id)
+            }
+            WireError::SourcePortOutOfRange { wire_id, gate_id, port_index, output_count } => write!(
+                f,
+                "Wire {} reads output {} of gate {}, which has {} outputs",
+                wire_id, port_index, gate_id, output_count
+            ),
+            WireError::TargetPortOutOfRange { wire_id, gate_id, port_index, input_count } => write!(
+                f,
+                "Wire {} drives input {} of gate {}, which has {} inputs",
+                wire_id, port_index, gate_id, input_count
+            ),
+            WireError::TargetPinOutOfRange { wire_id, gate_id, port_index, output_count } => write!(
+                f,
+                "Wire {} joins pin {} of gate {}, which has {} outputs",
+                wire_id, port_index, gate_id, output_count
+            ),
+        }
+    }
+}
+
+impl std::error::Error for WireError {}
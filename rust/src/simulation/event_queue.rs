@@ -1,7 +1,7 @@
 //! Priority queue for simulation events
 
 use std::cmp::Ordering;
-use std::collections::BinaryHeap;
+use std::collections::{BinaryHeap, HashSet};
 
 use crate::gates::state::StateType;
 
@@ -30,9 +30,14 @@ impl PartialOrd for SimulationEvent {
 }
 
 /// Event queue using a binary heap
+///
+/// Full gate evaluations are coalesced: at most one is queued per
+/// (gate, time), since a single evaluation sees every input change made
+/// before it runs.
 pub struct EventQueue {
     heap: BinaryHeap<SimulationEvent>,
     creation_counter: u64,
+    pending_evaluations: HashSet<(String, u64)>,
 }
 
 impl EventQueue {
@@ -40,11 +45,16 @@ impl EventQueue {
         Self {
             heap: BinaryHeap::new(),
             creation_counter: 0,
+            pending_evaluations: HashSet::new(),
         }
     }
 
-    /// Add an event to the queue
+    /// Add an event to the queue (a full evaluation already queued for the
+    /// same gate and time is not duplicated)
     pub fn push(&mut self, time: u64, gate_id: String, port_index: i32, new_state: StateType) {
+        if port_index < 0 && !self.pending_evaluations.insert((gate_id.clone(), time)) {
+            return;
+        }
         let event = SimulationEvent {
             time,
             creation_time: self.creation_counter,
@@ -58,7 +68,11 @@ impl EventQueue {
 
     /// Remove and return the earliest event
     pub fn pop(&mut self) -> Option<SimulationEvent> {
-        self.heap.pop()
+        let event = self.heap.pop()?;
+        if event.port_index < 0 {
+            self.pending_evaluations.remove(&(event.gate_id.clone(), event.time));
+        }
+        Some(event)
     }
 
     /// Look at the earliest event without removing it
@@ -76,14 +90,28 @@ impl EventQueue {
         self.heap.len()
     }
 
+    /// Get a copy of all pending events in the order they would be popped
+    pub fn pending_events(&self) -> Vec<SimulationEvent> {
+        let mut events = self.heap.clone().into_sorted_vec();
+        events.reverse();
+        events
+    }
+
+    /// IDs of the gates with at least one pending event, in no particular order
+    pub fn pending_gate_ids(&self) -> impl Iterator<Item = &str> {
+        self.heap.iter().map(|event| event.gate_id.as_str())
+    }
+
     /// Clear all events
     pub fn clear(&mut self) {
         self.heap.clear();
         self.creation_counter = 0;
+        self.pending_evaluations.clear();
     }
 
     /// Remove all events for a specific gate
     pub fn remove_events_for_gate(&mut self, gate_id: &str) {
+        self.pending_evaluations.retain(|(id, _)| id != gate_id);
         let filtered: Vec<_> = self.heap.drain().filter(|e| e.gate_id != gate_id).collect();
         for event in filtered {
             self.heap.push(event);
@@ -127,4 +155,33 @@ mod tests {
         assert_eq!(queue.pop().unwrap().gate_id, "gate2");
         assert_eq!(queue.pop().unwrap().gate_id, "gate3");
     }
+
+    #[test]
+    fn test_full_evaluations_are_coalesced() {
+        let mut queue = EventQueue::new();
+
+        queue.push(10, "gate1".to_string(), -1, StateType::Unknown);
+        queue.push(10, "gate1".to_string(), -1, StateType::Unknown);
+        queue.push(11, "gate1".to_string(), -1, StateType::Unknown);
+        queue.push(10, "gate1".to_string(), 0, StateType::One);
+        assert_eq!(queue.len(), 3);
+
+        // Once popped, the same evaluation may be queued again
+        assert_eq!(queue.pop().unwrap().port_index, -1);
+        queue.push(10, "gate1".to_string(), -1, StateType::Unknown);
+        assert_eq!(queue.len(), 3);
+    }
+
+    #[test]
+    fn test_pending_events_in_pop_order() {
+        let mut queue = EventQueue::new();
+
+        queue.push(10, "gate1".to_string(), 0, StateType::One);
+        queue.push(5, "gate2".to_string(), 0, StateType::Zero);
+        queue.push(10, "gate3".to_string(), 0, StateType::One);
+
+        let ids: Vec<String> = queue.pending_events().into_iter().map(|e| e.gate_id).collect();
+        assert_eq!(ids, vec!["gate2", "gate1", "gate3"]);
+        assert_eq!(queue.len(), 3);
+    }
 }
@@ -0,0 +1,289 @@
+//! Structural Verilog export
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+use super::engine::SimulationEngine;
+use crate::GateState;
+
+/// Verilog gate primitive for a MetaLogic gate type
+fn primitive(gate_type: &str) -> Option<&'static str> {
+    match gate_type {
+        "AND" => Some("and"),
+        "OR" => Some("or"),
+        "NOT" => Some("not"),
+        "NAND" => Some("nand"),
+        "NOR" => Some("nor"),
+        "XOR" => Some("xor"),
+        "XNOR" => Some("xnor"),
+        "BUFFER" => Some("buf"),
+        "TRI_BUFFER" => Some("bufif1"),
+        _ => None,
+    }
+}
+
+/// Verilog keywords that sanitized IDs must not collide with
+const KEYWORDS: &[&str] = &[
+    "always", "and", "assign", "begin", "buf", "bufif0", "bufif1", "case", "default", "else", "end", "endcase",
+    "endmodule", "for", "if", "initial", "inout", "input", "integer", "module", "nand", "nor", "not", "or",
+    "output", "reg", "supply0", "supply1", "tri", "wire", "xnor", "xor",
+];
+
+/// Turn an ID into a plain Verilog identifier (lossy: see `Names`)
+fn identifier(id: &str) -> String {
+    let mut name: String = id
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '_' { c } else { '_' })
+        .collect();
+    if !name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+        name.insert(0, '_');
+    }
+    name
+}
+
+/// Identifiers handed out so far in one module
+///
+/// Sanitizing can map distinct IDs to the same identifier (`g-1` and `g_1`),
+/// so a taken name gets the first free `_2`, `_3`, ... suffix instead of
+/// silently merging nets.
+struct Names {
+    used: BTreeSet<String>,
+}
+
+impl Names {
+    fn new() -> Self {
+        Self { used: KEYWORDS.iter().map(|keyword| keyword.to_string()).collect() }
+    }
+
+    fn claim(&mut self, base: String) -> String {
+        if self.used.insert(base.clone()) {
+            return base;
+        }
+        let name = (2..).map(|n| format!("{}_{}", base, n)).find(|name| !self.used.contains(name)).unwrap();
+        self.used.insert(name.clone());
+        name
+    }
+}
+
+impl SimulationEngine {
+    /// Emit the loaded circuit as a structural Verilog module
+    ///
+    /// TOGGLE gates become module inputs and LED gates module outputs; every
+    /// other gate output that is driven or read is one declared net. Gate
+    /// types without a Verilog primitive are emitted as comments so the rest
+    /// of the netlist stays usable, and undriven inputs are tied to `1'bx`.
+    /// IDs that sanitize to the same identifier get numbered suffixes.
+    pub fn export_verilog(&self, module_name: &str) -> String {
+        let snapshot = self.get_snapshot();
+        let gates: BTreeMap<&str, &GateState> =
+            snapshot.gates.iter().map(|gate| (gate.id.as_str(), gate)).collect();
+
+        // (target gate, input port) -> (source gate, output port), per wire bit
+        let mut drivers: BTreeMap<(&str, u32), (&str, u32)> = BTreeMap::new();
+        for wire in &snapshot.wires {
+            if wire.bidirectional || !gates.contains_key(wire.source_gate_id.as_str()) {
+                continue;
+            }
+            // Bits past the largest port number stay unconnected
+            for bit in 0..wire.width.max(1) {
+                let (Some(target_port), Some(source_port)) =
+                    (wire.target_port_index.checked_add(bit), wire.source_port_index.checked_add(bit))
+                else {
+                    continue;
+                };
+                drivers.insert(
+                    (wire.target_gate_id.as_str(), target_port),
+                    (wire.source_gate_id.as_str(), source_port),
+                );
+            }
+        }
+
+        let is_input = |id: &str| gates.get(id).is_some_and(|g| g.gate_type == "TOGGLE");
+        let drives_net = |gate: &GateState| {
+            matches!(gate.gate_type.as_str(), "CONST_HIGH" | "CONST_LOW") || primitive(&gate.gate_type).is_some()
+        };
+
+        // Claim every name up front, module ports first, so none collide
+        let mut names = Names::new();
+        let module = names.claim(identifier(module_name));
+        let ports: BTreeMap<&str, String> = gates
+            .values()
+            .filter(|g| g.gate_type == "TOGGLE" || g.gate_type == "LED")
+            .map(|g| (g.id.as_str(), names.claim(identifier(&g.id))))
+            .collect();
+        let driven: BTreeSet<(&str, u32)> = drivers
+            .values()
+            .copied()
+            .filter(|&(id, _)| !is_input(id))
+            .chain(gates.values().filter(|g| drives_net(g)).map(|g| (g.id.as_str(), 0)))
+            .collect();
+        let nets: BTreeMap<(&str, u32), String> = driven
+            .into_iter()
+            .map(|(id, port)| ((id, port), names.claim(format!("n_{}_{}", identifier(id), port))))
+            .collect();
+        let instances: BTreeMap<&str, String> = gates
+            .values()
+            .filter(|g| primitive(&g.gate_type).is_some())
+            .map(|g| (g.id.as_str(), names.claim(format!("g_{}", identifier(&g.id)))))
+            .collect();
+
+        let net_name = |source: (&str, u32)| match ports.get(source.0) {
+            Some(port) if is_input(source.0) => port.clone(),
+            _ => nets[&source].clone(),
+        };
+        let input_expr = |gate: &GateState, port: usize| {
+            let inverted = gate
+                .inverted_inputs
+                .as_ref()
+                .is_some_and(|mask| mask.get(port).copied().unwrap_or(false));
+            match drivers.get(&(gate.id.as_str(), port as u32)) {
+                Some(&source) if inverted => format!("~{}", net_name(source)),
+                Some(&source) => net_name(source),
+                None => "1'bx".to_string(),
+            }
+        };
+
+        let inputs: Vec<&str> = gates
+            .values()
+            .filter(|g| g.gate_type == "TOGGLE")
+            .map(|g| ports[g.id.as_str()].as_str())
+            .collect();
+        let outputs: Vec<&str> = gates
+            .values()
+            .filter(|g| g.gate_type == "LED")
+            .map(|g| ports[g.id.as_str()].as_str())
+            .collect();
+
+        let mut out = String::new();
+        let port_list: Vec<&str> = inputs.iter().chain(&outputs).copied().collect();
+        let _ = writeln!(out, "module {} ({});", module, port_list.join(", "));
+        for name in &inputs {
+            let _ = writeln!(out, "  input {};", name);
+        }
+        for name in &outputs {
+            let _ = writeln!(out, "  output {};", name);
+        }
+        if !nets.is_empty() {
+            out.push('\n');
+        }
+        for name in nets.values() {
+            let _ = writeln!(out, "  wire {};", name);
+        }
+        out.push('\n');
+
+        for gate in gates.values() {
+            match gate.gate_type.as_str() {
+                "TOGGLE" => {}
+                "LED" => {
+                    let _ = writeln!(out, "  assign {} = {};", ports[gate.id.as_str()], input_expr(gate, 0));
+                }
+                "CONST_HIGH" => {
+                    let _ = writeln!(out, "  assign {} = 1'b1;", net_name((gate.id.as_str(), 0)));
+                }
+                "CONST_LOW" => {
+                    let _ = writeln!(out, "  assign {} = 1'b0;", net_name((gate.id.as_str(), 0)));
+                }
+                gate_type => match primitive(gate_type) {
+                    Some(primitive) => {
+                        let terminals: Vec<String> = std::iter::once(net_name((gate.id.as_str(), 0)))
+                            .chain((0..gate.input_states.len()).map(|port| input_expr(gate, port)))
+                            .collect();
+                        let _ = writeln!(
+                            out,
+                            "  {} {} ({});",
+                            primitive,
+                            instances[gate.id.as_str()],
+                            terminals.join(", ")
+                        );
+                    }
+                    None => {
+                        // IDs are free text; keep them on the comment line
+                        let _ = writeln!(
+                            out,
+                            "  // unsupported gate: {} {}",
+                            gate_type.escape_debug(),
+                            gate.id.escape_debug()
+                        );
+                    }
+                },
+            }
+        }
+
+        out.push_str("endmodule\n");
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{gate, wire};
+
+    #[test]
+    fn test_export_verilog_structural_module() {
+        let mut nand = gate("g-1", "AND", 2);
+        nand.inverted_inputs = Some(vec![false, true]);
+        let mut engine = SimulationEngine::new();
+        engine.initialize(
+            vec![gate("a", "TOGGLE", 0), gate("b", "TOGGLE", 0), nand, gate("ff", "DFF", 2), gate("out", "LED", 1)],
+            vec![
+                wire("w1", "a", 0, "g-1", 0),
+                wire("w2", "b", 0, "g-1", 1),
+                wire("w3", "g-1", 0, "out", 0),
+                wire("w4", "g-1", 0, "ff", 0),
+            ],
+        );
+
+        let verilog = engine.export_verilog("top");
+        assert!(verilog.starts_with("module top (a, b, out);\n"));
+        assert!(verilog.contains("  input a;\n  input b;\n  output out;\n"));
+        assert!(verilog.contains("  wire n_g_1_0;\n"));
+        assert!(verilog.contains("  and g_g_1 (n_g_1_0, a, ~b);\n"));
+        assert!(verilog.contains("  // unsupported gate: DFF ff\n"));
+        assert!(verilog.contains("  assign out = n_g_1_0;\n"));
+        assert!(verilog.ends_with("endmodule\n"));
+    }
+
+    #[test]
+    fn test_export_verilog_keeps_colliding_ids_apart() {
+        let mut engine = SimulationEngine::new();
+        engine.initialize(
+            vec![
+                gate("a", "TOGGLE", 0),
+                gate("g-1", "NOT", 1),
+                gate("g_1", "BUFFER", 1),
+                gate("and", "AND", 2),
+                gate("bad\nid", "DFF", 2),
+                gate("y", "LED", 1),
+            ],
+            vec![
+                wire("w1", "a", 0, "g-1", 0),
+                wire("w2", "a", 0, "g_1", 0),
+                wire("w3", "g-1", 0, "and", 0),
+                wire("w4", "g_1", 0, "and", 1),
+                wire("w5", "g-1", 0, "y", 0),
+            ],
+        );
+
+        let verilog = engine.export_verilog("top");
+        assert!(verilog.contains("  wire n_g_1_0;\n  wire n_g_1_0_2;\n"));
+        assert!(verilog.contains("  not g_g_1 (n_g_1_0, a);\n"));
+        assert!(verilog.contains("  buf g_g_1_2 (n_g_1_0_2, a);\n"));
+        // Keywords are renamed, and outputs nothing reads are still declared
+        assert!(verilog.contains("  wire n_and_0;\n"));
+        assert!(verilog.contains("  and g_and (n_and_0, n_g_1_0, n_g_1_0_2);\n"));
+        assert!(verilog.contains("  // unsupported gate: DFF bad\\nid\n"));
+        assert!(verilog.contains("  assign y = n_g_1_0;\n"));
+    }
+
+    #[test]
+    fn test_export_verilog_skips_bits_past_the_last_port() {
+        let mut bus = wire("bus", "a", 0, "inv", u32::MAX);
+        bus.width = 2;
+        let mut engine = SimulationEngine::new();
+        engine.initialize(vec![gate("a", "TOGGLE", 0), gate("inv", "NOT", 1)], vec![bus]);
+
+        let verilog = engine.export_verilog("top");
+        assert!(verilog.contains("  not g_inv (n_inv_0, 1'bx);\n"));
+    }
+}
@@ -1,2 +1,6 @@
 pub mod engine;
 pub mod event_queue;
+pub mod error;
+pub mod export;
+pub mod netlist;
+pub mod replay;
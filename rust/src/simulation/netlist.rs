@@ -0,0 +1,240 @@
+//! JSON netlist import
+//!
+//! A netlist is a JSON object with a `gates` and a `wires` array:
+//!
+//! ```json
+//! {
+//!   "gates": [
+//!     { "id": "a", "type": "TOGGLE" },
+//!     { "id": "and1", "type": "AND", "delay": 2, "params": { "inputs": 3 } },
+//!     { "id": "rom", "type": "ROM", "params": { "inputs": 2, "outputs": 4, "init_data": [1, 2, 4, 8] } }
+//!   ],
+//!   "wires": [
+//!     { "from": { "gate": "a", "port": 0 }, "to": { "gate": "and1", "port": 0 } },
+//!     { "id": "bus", "from": { "gate": "rom" }, "to": { "gate": "led" }, "width": 4, "label": "data" }
+//!   ]
+//! }
+//! ```
+//!
+//! Gates may also set `rise_delay`/`fall_delay`, `min_delay`/`max_delay`
+//! and a `group` name (see `get_group_snapshot`).
+//! `params` may set `inputs`, `outputs`, `inverted_inputs`, `modulus`,
+//! `init_data` and `pulse_width`, with the same meaning as the matching
+//! `GateState` fields.
+//! Wires may set `"bidirectional": true` to join two inout pins, a `delay`
+//! added to their source gate's, or a `resolution` of `"wired_or"` or
+//! `"wired_and"` for their target net.
+//! Ports default to 0, widths to 1, and wire IDs to `w<index>`.
+
+use serde::Deserialize;
+
+use super::engine::SimulationEngine;
+use super::error::{SimError, WireError};
+use crate::gates::state::{StateType, WireResolution};
+use crate::{GateState, WireState, WireValue};
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Netlist {
+    #[serde(default)]
+    gates: Vec<NetlistGate>,
+    #[serde(default)]
+    wires: Vec<NetlistWire>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct NetlistGate {
+    id: String,
+    #[serde(rename = "type")]
+    gate_type: String,
+    delay: Option<u64>,
+    rise_delay: Option<u64>,
+    fall_delay: Option<u64>,
+    min_delay: Option<u64>,
+    max_delay: Option<u64>,
+    group: Option<String>,
+    #[serde(default)]
+    params: NetlistParams,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+struct NetlistParams {
+    inputs: Option<usize>,
+    outputs: Option<usize>,
+    inverted_inputs: Option<Vec<bool>>,
+    modulus: Option<u64>,
+    init_data: Option<Vec<u32>>,
+    pulse_width: Option<u64>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct NetlistWire {
+    id: Option<String>,
+    from: Endpoint,
+    to: Endpoint,
+    width: Option<u32>,
+    label: Option<String>,
+    #[serde(default)]
+    bidirectional: bool,
+    #[serde(default)]
+    delay: u64,
+    #[serde(default)]
+    resolution: WireResolution,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Endpoint {
+    gate: String,
+    #[serde(default)]
+    port: u32,
+}
+
+impl From<NetlistGate> for GateState {
+    fn from(gate: NetlistGate) -> Self {
+        let unknown = StateType::Unknown.to_u8();
+        GateState {
+            id: gate.id,
+            gate_type: gate.gate_type,
+            input_states: vec![unknown; gate.params.inputs.unwrap_or(0)],
+            output_states: vec![unknown; gate.params.outputs.unwrap_or(0)],
+            delay: gate.delay,
+            rise_delay: gate.rise_delay,
+            fall_delay: gate.fall_delay,
+            min_delay: gate.min_delay,
+            max_delay: gate.max_delay,
+            inverted_inputs: gate.params.inverted_inputs,
+            modulus: gate.params.modulus,
+            init_data: gate.params.init_data,
+            pulse_width: gate.params.pulse_width,
+            segments: None,
+            subcircuit: None,
+            group: gate.group,
+        }
+    }
+}
+
+impl SimulationEngine {
+    /// Load a circuit from a JSON netlist (see the module docs for the schema),
+    /// replacing the current one
+    ///
+    /// Fails without touching the loaded circuit if the JSON does not match
+    /// the schema or any wire references a gate that is not in the netlist.
+    /// Otherwise returns the wiring errors `initialize` found (empty when
+    /// every port number is valid).
+    pub fn import_netlist(&mut self, json: &str) -> Result<Vec<WireError>, SimError> {
+        let netlist: Netlist =
+            serde_json::from_str(json).map_err(|e| SimError::InvalidNetlist(e.to_string()))?;
+
+        let mut missing: Vec<String> = netlist
+            .wires
+            .iter()
+            .flat_map(|wire| [&wire.from.gate, &wire.to.gate])
+            .filter(|id| !netlist.gates.iter().any(|gate| &gate.id == *id))
+            .cloned()
+            .collect();
+        if !missing.is_empty() {
+            missing.sort();
+            missing.dedup();
+            return Err(SimError::MissingGates(missing));
+        }
+
+        let wires = netlist
+            .wires
+            .into_iter()
+            .enumerate()
+            .map(|(index, wire)| WireState {
+                id: wire.id.unwrap_or_else(|| format!("w{}", index)),
+                state: WireValue::Scalar(StateType::Unknown.to_u8()),
+                width: wire.width.unwrap_or(1),
+                source_gate_id: wire.from.gate,
+                source_port_index: wire.from.port,
+                target_gate_id: wire.to.gate,
+                target_port_index: wire.to.port,
+                label: wire.label,
+                bidirectional: wire.bidirectional,
+                delay: wire.delay,
+                resolution: wire.resolution,
+            })
+            .collect();
+        let gates = netlist.gates.into_iter().map(GateState::from).collect();
+
+        Ok(self.initialize(gates, wires))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_import_netlist_builds_circuit() {
+        let json = r#"{
+            "gates": [
+                { "id": "a", "type": "TOGGLE" },
+                { "id": "b", "type": "TOGGLE" },
+                { "id": "and1", "type": "AND", "delay": 2, "group": "logic", "params": { "inputs": 2 } },
+                { "id": "led", "type": "LED" }
+            ],
+            "wires": [
+                { "from": { "gate": "a" }, "to": { "gate": "and1", "port": 0 } },
+                { "from": { "gate": "b" }, "to": { "gate": "and1", "port": 1 } },
+                { "id": "out", "from": { "gate": "and1" }, "to": { "gate": "led" }, "label": "y" }
+            ]
+        }"#;
+
+        let mut engine = SimulationEngine::new();
+        assert_eq!(engine.import_netlist(json), Ok(vec![]));
+        engine.set_inputs(vec![("a".to_string(), true), ("b".to_string(), true)]).unwrap();
+        engine.run_until_stable(20);
+
+        let snapshot = engine.get_snapshot();
+        let ids: Vec<&str> = snapshot.wires.iter().map(|w| w.id.as_str()).collect();
+        assert_eq!(ids, vec!["out", "w0", "w1"]);
+        let led = snapshot.gates.iter().find(|g| g.id == "led").unwrap();
+        assert_eq!(led.input_states, vec![StateType::One.to_u8()]);
+        let logic = engine.get_group_snapshot("logic");
+        assert_eq!(logic.gates.len(), 1);
+        assert_eq!(logic.gates[0].group.as_deref(), Some("logic"));
+    }
+
+    #[test]
+    fn test_import_netlist_reports_missing_gates() {
+        let json = r#"{
+            "gates": [{ "id": "a", "type": "TOGGLE" }],
+            "wires": [
+                { "from": { "gate": "a" }, "to": { "gate": "x" } },
+                { "from": { "gate": "y" }, "to": { "gate": "x" } }
+            ]
+        }"#;
+
+        let mut engine = SimulationEngine::new();
+        assert_eq!(
+            engine.import_netlist(json),
+            Err(SimError::MissingGates(vec!["x".to_string(), "y".to_string()]))
+        );
+        assert!(matches!(engine.import_netlist("{ \"gates\": 3 }"), Err(SimError::InvalidNetlist(_))));
+    }
+
+    #[test]
+    fn test_import_netlist_reports_wiring_errors() {
+        let json = r#"{
+            "gates": [{ "id": "a", "type": "TOGGLE" }, { "id": "inv", "type": "NOT" }],
+            "wires": [{ "from": { "gate": "a" }, "to": { "gate": "inv", "port": 7 } }]
+        }"#;
+
+        let mut engine = SimulationEngine::new();
+        assert_eq!(
+            engine.import_netlist(json),
+            Ok(vec![WireError::TargetPortOutOfRange {
+                wire_id: "w0".to_string(),
+                gate_id: "inv".to_string(),
+                port_index: 7,
+                input_count: 1,
+            }])
+        );
+    }
+}
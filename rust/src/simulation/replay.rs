@@ -0,0 +1,179 @@
+//! Deterministic command log for reproducing bug reports
+//!
+//! With logging enabled the engine records every external stimulus (input
+//! changes, circuit edits, resets) and every step request, in order, with
+//! the simulation time it was issued at. The simulation itself is
+//! deterministic, so replaying the log on a fresh engine loaded with the
+//! same circuit reproduces the exact same sequence of states.
+//!
+//! Configuration (breakpoints, assertions, event limits, the floating input
+//! and unknown policies, the delay corner, history) and state restores
+//! (`import_state`, `step_back`) are not recorded; they must match between
+//! recording and replay.
+
+use serde::{Deserialize, Serialize};
+
+use super::engine::SimulationEngine;
+use super::error::SimError;
+use crate::gates::gate::ResetMode;
+use crate::gates::state::StateType;
+use crate::{GateState, WireState};
+
+/// One recorded engine call
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum Command {
+    /// `count` consecutive calls to `step`
+    Step { count: u64 },
+    StepSingleEvent,
+    AdvanceBy { time_units: u64 },
+    RunUntilStable { max_steps: u64 },
+    ToggleInput { gate_id: String },
+    SetInputLevel { gate_id: String, state: u8 },
+    SetInputs { values: Vec<(String, bool)> },
+    AddGate { gate: Box<GateState> },
+    AddWire { wire: WireState },
+    RemoveGate { gate_id: String },
+    SetGateEnabled { gate_id: String, enabled: bool },
+    SetClockPeriod { gate_id: String, period: u64 },
+    SetClockPhase { gate_id: String, offset: u64 },
+    FirePulse { gate_id: String, width: u64 },
+    ForceOutput { gate_id: String, port: usize, state: u8 },
+    ReleaseOutput { gate_id: String, port: usize },
+    Reset { mode: u8 },
+    RandomizeInitialState { seed: u64 },
+    PowerOnReset { hold_cycles: u64 },
+}
+
+/// A command and the simulation time it was issued at
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct LoggedCommand {
+    pub time: u64,
+    #[serde(flatten)]
+    pub command: Command,
+}
+
+impl SimulationEngine {
+    /// The recorded command log as JSON (an empty array when logging is off)
+    pub fn export_command_log(&self) -> String {
+        serde_json::to_string(self.command_log().unwrap_or_default()).unwrap_or_default()
+    }
+
+    /// Re-issue every command of an exported log, in order
+    ///
+    /// Each command must find the engine at the time it was recorded at;
+    /// otherwise the circuit or configuration differs from the recording
+    /// and replay stops with `ReplayDiverged`.
+    pub fn replay_command_log(&mut self, log: &str) -> Result<(), SimError> {
+        let commands: Vec<LoggedCommand> =
+            serde_json::from_str(log).map_err(|e| SimError::InvalidCommandLog(e.to_string()))?;
+
+        for (index, logged) in commands.into_iter().enumerate() {
+            if self.get_current_time() != logged.time {
+                return Err(SimError::ReplayDiverged {
+                    index,
+                    expected_time: logged.time,
+                    actual_time: self.get_current_time(),
+                });
+            }
+            match logged.command {
+                Command::Step { count } => {
+                    for _ in 0..count {
+                        self.step();
+                    }
+                }
+                Command::StepSingleEvent => {
+                    self.step_single_event();
+                }
+                Command::AdvanceBy { time_units } => {
+                    self.advance_by(time_units);
+                }
+                Command::RunUntilStable { max_steps } => {
+                    self.run_until_stable(max_steps);
+                }
+                Command::ToggleInput { gate_id } => self.toggle_input(&gate_id)?,
+                Command::SetInputLevel { gate_id, state } => {
+                    self.set_input_level(&gate_id, StateType::from_u8(state))?
+                }
+                Command::SetInputs { values } => self.set_inputs(values)?,
+                Command::AddGate { gate } => self.add_gate(*gate),
+                Command::AddWire { wire } => self.add_wire(wire),
+                Command::RemoveGate { gate_id } => self.remove_gate(&gate_id)?,
+                Command::SetGateEnabled { gate_id, enabled } => self.set_gate_enabled(&gate_id, enabled)?,
+                Command::SetClockPeriod { gate_id, period } => self.set_clock_period(&gate_id, period)?,
+                Command::SetClockPhase { gate_id, offset } => self.set_clock_phase(&gate_id, offset)?,
+                Command::FirePulse { gate_id, width } => self.fire_pulse(&gate_id, width)?,
+                Command::ForceOutput { gate_id, port, state } => {
+                    self.force_output(&gate_id, port, StateType::from_u8(state))?
+                }
+                Command::ReleaseOutput { gate_id, port } => self.release_output(&gate_id, port)?,
+                Command::Reset { mode } => self.reset(ResetMode::from_u8(mode)),
+                Command::RandomizeInitialState { seed } => self.randomize_initial_state(seed),
+                Command::PowerOnReset { hold_cycles } => self.power_on_reset(hold_cycles),
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{gate, wire};
+
+    fn build() -> SimulationEngine {
+        let mut engine = SimulationEngine::new();
+        engine.initialize(
+            vec![gate("d", "TOGGLE", 0), gate("clk", "CLOCK", 0), gate("dff", "DFF", 2), gate("led", "LED", 1)],
+            vec![wire("w1", "d", 0, "dff", 0), wire("w2", "clk", 0, "dff", 1), wire("w3", "dff", 0, "led", 0)],
+        );
+        engine
+    }
+
+    #[test]
+    fn test_replay_reproduces_recorded_session() {
+        let mut recorded = build();
+        recorded.set_command_logging(true);
+        recorded.run_until_stable(20);
+        recorded.toggle_input("d").unwrap();
+        for _ in 0..3 {
+            recorded.step();
+        }
+        recorded.set_clock_period("clk", 3).unwrap();
+        recorded.advance_by(17);
+        recorded.add_gate(gate("inv", "NOT", 1));
+        recorded.add_wire(wire("w4", "dff", 0, "inv", 0));
+        recorded.toggle_input("d").unwrap();
+        recorded.advance_by(9);
+        assert!(recorded.toggle_input("missing").is_err());
+
+        let log = recorded.export_command_log();
+        let commands: Vec<LoggedCommand> = serde_json::from_str(&log).unwrap();
+        // Consecutive steps share one entry; failed calls are not recorded
+        assert_eq!(commands.len(), 9);
+        assert_eq!(commands[2].command, Command::Step { count: 3 });
+
+        let mut replayed = build();
+        replayed.replay_command_log(&log).unwrap();
+        assert_eq!(replayed.get_current_time(), recorded.get_current_time());
+        assert_eq!(replayed.state_hash(), recorded.state_hash());
+        assert_eq!(replayed.get_snapshot(), recorded.get_snapshot());
+    }
+
+    #[test]
+    fn test_replay_detects_divergence() {
+        let mut recorded = build();
+        recorded.set_command_logging(true);
+        recorded.advance_by(5);
+        recorded.toggle_input("d").unwrap();
+        let log = recorded.export_command_log();
+
+        let mut replayed = build();
+        replayed.advance_by(2);
+        assert_eq!(
+            replayed.replay_command_log(&log),
+            Err(SimError::ReplayDiverged { index: 0, expected_time: 0, actual_time: 2 })
+        );
+        assert!(matches!(replayed.replay_command_log("[{}]"), Err(SimError::InvalidCommandLog(_))));
+    }
+}
@@ -0,0 +1,43 @@
+//! Circuit-building helpers shared by the unit tests
+
+use crate::gates::state::{StateType, WireResolution};
+use crate::{GateState, WireState, WireValue};
+
+/// Gate with `input_count` UNKNOWN inputs and every optional field unset
+pub(crate) fn gate(id: &str, gate_type: &str, input_count: usize) -> GateState {
+    GateState {
+        id: id.to_string(),
+        gate_type: gate_type.to_string(),
+        input_states: vec![StateType::Unknown.to_u8(); input_count],
+        output_states: vec![],
+        delay: None,
+        rise_delay: None,
+        fall_delay: None,
+        min_delay: None,
+        max_delay: None,
+        inverted_inputs: None,
+        modulus: None,
+        init_data: None,
+        pulse_width: None,
+        segments: None,
+        subcircuit: None,
+        group: None,
+    }
+}
+
+/// Single-bit, zero-delay wire from an output port to an input port
+pub(crate) fn wire(id: &str, source: &str, source_port: u32, target: &str, target_port: u32) -> WireState {
+    WireState {
+        id: id.to_string(),
+        state: WireValue::Scalar(StateType::Unknown.to_u8()),
+        width: 1,
+        source_gate_id: source.to_string(),
+        source_port_index: source_port,
+        target_gate_id: target.to_string(),
+        target_port_index: target_port,
+        label: None,
+        bidirectional: false,
+        delay: 0,
+        resolution: WireResolution::Standard,
+    }
+}